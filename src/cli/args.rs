@@ -6,10 +6,13 @@ use clap::{
     },
 };
 use clap_complete::{Generator, Shell, generate};
-use claudiofsr_lib::clear_terminal_screen;
+use claudiofsr_lib::{StrExtension, clear_terminal_screen};
 use std::{io, path::PathBuf, process};
 
-use crate::{ExcelMemoryMode, XmlParserResult, parse_xml_and_print_struct, print_nodes};
+use crate::{
+    CriterioRateio, ExcelMemoryMode, OUTPUT_TEMPLATE, TipoRelacaoCte, XmlParserResult,
+    parse_xml_and_print_struct, print_nodes,
+};
 
 /// Custom Clap styling to mimic a beautiful colored help menu.
 fn get_styles() -> Styles {
@@ -103,6 +106,21 @@ pub struct Arguments {
     #[arg(long, default_value_t = false)]
     pub exibir_correlacoes: bool,
 
+    /// Explain the correlation between two keys (NFe or CTe)
+    ///
+    /// Exibe o menor caminho de documentos que liga as duas chaves, com a fonte de
+    /// cada vínculo (campo do XML ou evento) e o arquivo em que foi lido.
+    ///
+    /// read_xml -ta --explicar <CHAVE_NFE> <CHAVE_CTE>
+    #[arg(
+        long("explicar"),
+        required = false,
+        num_args = 2,
+        value_names = ["CHAVE_1", "CHAVE_2"],
+        value_parser = parse_chave
+    )]
+    pub explicar: Option<Vec<String>>,
+
     /**
     If provided, outputs the completion file for given shell.
 
@@ -174,6 +192,174 @@ pub struct Arguments {
     )]
     pub linhas: Option<u64>,
 
+    /// Set the criterion for apportioning CTe freight onto NFe items
+    ///
+    /// Critério de rateio do valor dos CTes entre os itens das NFes correlacionadas.
+    ///
+    /// Opções disponíveis: valor, peso
+    #[arg(
+        long = "rateio",
+        value_enum,
+        required = false,
+        default_value_t = CriterioRateio::default()
+    )]
+    pub rateio: CriterioRateio,
+
+    /// Set which kinds of CTe relationship propagate NFe links
+    ///
+    /// Tipos de relação entre CTes que propagam as NFes correlacionadas ao longo da cadeia.
+    ///
+    /// Opções disponíveis: complementar, redespacho, redespacho-intermediario, subcontratacao,
+    /// substituicao, anulacao, multimodal, anterior
+    ///
+    /// Padrão: todos, exceto substituicao e anulacao. Sem substituicao na lista, o CTe
+    /// substituto assume as NFes do CTe substituído, que deixa de ser correlacionado a elas.
+    ///
+    /// Exemplo:
+    ///
+    /// read_xml --propagar-nfes complementar,redespacho,subcontratacao
+    #[arg(
+        long = "propagar-nfes",
+        value_enum,
+        value_delimiter = ',',
+        num_args = 1..,
+        required = false,
+        default_values_t = TipoRelacaoCte::PROPAGACAO_PADRAO
+    )]
+    pub propagar_nfes: Vec<TipoRelacaoCte>,
+
+    /// Export the correlation graph as GraphViz DOT and GraphML files
+    ///
+    /// Grava o grafo de CTes e NFes ({tipo} = grafo) com as extensões .dot e .graphml.
+    /// Os nós trazem tipo, número, emitente, valor e situação; as arestas, o tipo de
+    /// relação. Documentos não encontrados no lote são desenhados tracejados.
+    ///
+    /// read_xml -ta --grafo
+    ///
+    /// dot -Tsvg documentos_fiscais-grafo.dot -o grafo.svg
+    #[arg(long("grafo"), default_value_t = false)]
+    pub grafo: bool,
+
+    /// Restrict the exported graph to one freight chain (implies --grafo)
+    ///
+    /// Identificador da cadeia de frete (coluna "Cadeia de Frete").
+    #[arg(long("grafo-cadeia"), required = false, value_parser = parse_chave)]
+    pub grafo_cadeia: Option<String>,
+
+    /// Restrict the exported graph to the neighbourhood of a key (implies --grafo)
+    ///
+    /// Exporta apenas os documentos a até --grafo-saltos arestas da chave (NFe ou CTe).
+    ///
+    /// read_xml -ta --grafo-chave <CHAVE> --grafo-saltos 3
+    #[arg(long("grafo-chave"), required = false, value_parser = parse_chave)]
+    pub grafo_chave: Option<String>,
+
+    /// Set the maximum number of hops from --grafo-chave
+    #[arg(long("grafo-saltos"), required = false, default_value_t = 2)]
+    pub grafo_saltos: usize,
+
+    /// Set the CNPJ base (8 digits) of the audited company
+    ///
+    /// CNPJ base da empresa auditada. Aceita o CNPJ completo ou formatado:
+    /// apenas os 8 primeiros dígitos são considerados.
+    ///
+    /// Cada NFe e CTe recebe o papel da empresa (emitente, destinatário, tomador,
+    /// remetente etc.) e a direção da operação: Entrada, Saída ou Não Relacionado.
+    /// Além dos arquivos completos, são gerados arquivos separados com os sufixos
+    /// "-entradas", "-saidas" e "-nao_relacionados".
+    ///
+    /// Também é usado para verificar se a empresa é a tomadora do frete na apuração
+    /// do crédito de PIS/COFINS sobre fretes.
+    ///
+    /// read_xml -ta --cnpj-base 12.345.678
+    #[arg(long("cnpj-base"), required = false, value_parser = parse_cnpj_base)]
+    pub cnpj_base: Option<String>,
+
+    /// Write a separate set of output files for each company (CNPJ base)
+    ///
+    /// Lista de CNPJs base separados por vírgula. Sem valores, as empresas são
+    /// descobertas automaticamente entre os emitentes e destinatários das NFes
+    /// e os tomadores dos CTes (ver --min-documentos).
+    ///
+    /// As correlações são calculadas sobre todos os documentos lidos. Em seguida,
+    /// cada empresa recebe arquivos com o sufixo do seu CNPJ base contendo apenas
+    /// os documentos em que participa, classificados do seu ponto de vista.
+    ///
+    /// read_xml -ta --empresas 12.345.678,98765432
+    ///
+    /// read_xml -ta --empresas --min-documentos 50
+    #[arg(
+        long("empresas"),
+        required = false,
+        num_args = 0..,
+        value_delimiter = ',',
+        value_parser = parse_cnpj_base
+    )]
+    pub empresas: Option<Vec<String>>,
+
+    /// Set the minimum number of documents for a company to be discovered
+    ///
+    /// Usado com --empresas sem valores: ignora CNPJs base que participam
+    /// de menos documentos (NFes e CTes) do que o mínimo informado.
+    #[arg(long("min-documentos"), required = false, default_value_t = 1)]
+    pub min_documentos: usize,
+
+    /// Set the tolerance (in %) for the CTe cargo reconciliation
+    ///
+    /// Tolerância percentual na conferência entre o peso/valor da carga declarados
+    /// no CTe e a soma das NFes correlacionadas.
+    ///
+    /// Acima deste limite, a coluna "Divergência de Carga" recebe "Sim".
+    ///
+    /// read_xml -ta --tolerancia 2.5
+    #[arg(
+        long("tolerancia"),
+        required = false,
+        default_value_t = 5.0,
+        value_parser = parse_tolerancia
+    )]
+    pub tolerancia: f64,
+
+    /// Suggest NFes for CTes without any correlated NFe, with a confidence score
+    ///
+    /// Para CTes válidos sem NFes declaradas ou herdadas, propõe NFes com base em evidências:
+    /// remetente/destinatário do CTe iguais ao emitente/destinatário da NFe, transportadora
+    /// da NFe igual ao emitente do CTe, datas de emissão próximas e valor da carga próximo
+    /// do valor da NFe. As sugestões não alteram as correlações: são gravadas na tabela
+    /// "Vínculos Sugeridos" ({tipo} = ctes-vinculos_sugeridos).
+    ///
+    /// read_xml -ta --sugerir-vinculos --janela-dias 3 --confianca-minima 0.7
+    #[arg(long("sugerir-vinculos"), default_value_t = false)]
+    pub sugerir_vinculos: bool,
+
+    /// Set the maximum difference (in days) between CTe and NFe issue dates
+    #[arg(long("janela-dias"), required = false, default_value_t = 5)]
+    pub janela_dias: u32,
+
+    /// Set the minimum confidence (0.0 to 1.0) of a suggested link
+    #[arg(
+        long("confianca-minima"),
+        required = false,
+        default_value_t = 0.6,
+        value_parser = parse_confianca
+    )]
+    pub confianca_minima: f64,
+
+    /// Overlay the corrections of the latest CC-e onto the CTe columns
+    ///
+    /// Aplica as correções estruturadas da Carta de Correção vigente de cada CTe (a de
+    /// maior nSeqEvento entre as homologadas) sobre as colunas equivalentes: CFOP, valor da
+    /// carga, nomes e municípios/UF dos participantes. As colunas sobrescritas são listadas
+    /// em "Colunas Corrigidas por CC-e". Correções do valor da prestação, vedadas pelo
+    /// art. 58-B, § 1º, do Convênio SINIEF 06/89, não são aplicadas, apenas sinalizadas.
+    ///
+    /// Todas as CC-es de NFes e CTes constam da tabela "Cartas de Correção"
+    /// ({tipo} = cartas_correcao), aplicadas ou não.
+    ///
+    /// read_xml -ta --aplicar-cce
+    #[arg(long("aplicar-cce"), default_value_t = false)]
+    pub aplicar_cce: bool,
+
     /// Set maximum depth to recursively search XML files
     ///
     /// Avoid descending into directories when the depth is exceeded
@@ -184,6 +370,43 @@ pub struct Arguments {
     #[arg(short('n'), long("nodes"), required = false)]
     pub nodes: Option<PathBuf>,
 
+    /// Set the directory where output files are written
+    ///
+    /// Diretório dos arquivos gerados (planilhas, CSVs e arquivos de chaves).
+    /// É criado caso não exista. Padrão: diretório atual.
+    ///
+    /// read_xml -ta -o /tmp/auditoria
+    #[arg(short('o'), long("output-dir"), required = false)]
+    pub output_dir: Option<PathBuf>,
+
+    /// Set the output filename template (without extension)
+    ///
+    /// Modelo do nome dos arquivos gerados. Marcadores disponíveis:
+    ///
+    /// {tipo}: tabela exportada (ctes, nfes, nfes-documentos, efinanceiras etc.), obrigatório;
+    ///
+    /// {data}: data da execução (AAAA-MM-DD);
+    ///
+    /// {cnpj}: CNPJ base da empresa (--cnpj-base ou --empresas), omitido se ausente;
+    ///
+    /// {run}: identificador da execução (AAAAMMDD_HHMMSS).
+    ///
+    /// read_xml -ta --nome-arquivo "{cnpj}-{tipo}-{data}"
+    #[arg(
+        long("nome-arquivo"),
+        required = false,
+        default_value = OUTPUT_TEMPLATE,
+        value_parser = parse_output_template
+    )]
+    pub nome_arquivo: String,
+
+    /// Write all tables as sheets of a single XLSX workbook
+    ///
+    /// Reúne CTes, NFes, e-Financeiras e tabelas derivadas em abas de um único
+    /// arquivo XLSX ({tipo} = planilhas). Os arquivos CSV continuam separados.
+    #[arg(long("planilha-unica"), default_value_t = false)]
+    pub planilha_unica: bool,
+
     /// Set the XML file path, otherwise recursively search
     /// for XML files in the current directory
     #[arg(short('p'), long("path"), required = false)]
@@ -226,6 +449,76 @@ pub struct Arguments {
     pub verbose: bool,
 }
 
+/// Extrai os 8 dígitos do CNPJ base a partir do CNPJ completo, formatado ou não.
+fn parse_cnpj_base(value: &str) -> Result<String, String> {
+    let digits = value.remove_non_digits();
+    match digits.len() {
+        8 | 14 => Ok(digits[..8].to_string()),
+        _ => Err(format!(
+            "CNPJ base inválido: '{value}' (informe 8 ou 14 dígitos)"
+        )),
+    }
+}
+
+/// Extrai os 44 dígitos da chave de acesso de uma NFe ou CTe, formatada ou não.
+fn parse_chave(value: &str) -> Result<String, String> {
+    let digits = value.remove_non_digits();
+    match digits.len() {
+        44 => Ok(digits),
+        _ => Err(format!(
+            "Chave de acesso inválida: '{value}' (informe 44 dígitos)"
+        )),
+    }
+}
+
+/// Valida a tolerância percentual: um número finito e não negativo.
+fn parse_tolerancia(value: &str) -> Result<f64, String> {
+    match value.trim().parse::<f64>() {
+        Ok(tolerancia) if tolerancia.is_finite() && tolerancia >= 0.0 => Ok(tolerancia),
+        _ => Err(format!(
+            "Tolerância inválida: '{value}' (informe um percentual maior ou igual a zero)"
+        )),
+    }
+}
+
+/// Valida a confiança mínima: uma fração entre 0 e 1.
+fn parse_confianca(value: &str) -> Result<f64, String> {
+    match value.trim().parse::<f64>() {
+        Ok(confianca) if (0.0..=1.0).contains(&confianca) => Ok(confianca),
+        _ => Err(format!(
+            "Confiança inválida: '{value}' (informe um valor entre 0.0 e 1.0)"
+        )),
+    }
+}
+
+/// Valida o modelo de nome dos arquivos de saída.
+///
+/// Exige o marcador `{tipo}` (para que as tabelas não se sobrescrevam) e rejeita
+/// marcadores desconhecidos e separadores de diretório.
+fn parse_output_template(value: &str) -> Result<String, String> {
+    if !value.contains("{tipo}") {
+        return Err(format!("Modelo '{value}' sem o marcador {{tipo}}"));
+    }
+
+    if value.contains(['/', '\\']) {
+        return Err(format!(
+            "Modelo '{value}' não deve conter diretórios (use --output-dir)"
+        ));
+    }
+
+    let restante = ["{tipo}", "{data}", "{cnpj}", "{run}"]
+        .iter()
+        .fold(value.to_string(), |acc, token| acc.replace(token, ""));
+
+    if restante.contains(['{', '}']) {
+        return Err(format!(
+            "Modelo '{value}' com marcador desconhecido (use {{tipo}}, {{data}}, {{cnpj}} ou {{run}})"
+        ));
+    }
+
+    Ok(value.to_string())
+}
+
 impl Arguments {
     /// Build Arguments struct
     pub fn build() -> XmlParserResult<Arguments> {
@@ -252,6 +545,11 @@ impl Arguments {
         Ok(args)
     }
 
+    /// Verifica se o grafo de correlações deve ser exportado (`--grafo` ou um de seus filtros).
+    pub fn exportar_grafo(&self) -> bool {
+        self.grafo || self.grafo_cadeia.is_some() || self.grafo_chave.is_some()
+    }
+
     /// Print shell completions to standard output
     fn print_completions<G>(&self, gnt: G)
     where
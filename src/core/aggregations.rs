@@ -19,16 +19,16 @@ use crate::{
 };
 
 #[derive(Debug, Default, Clone, Eq, PartialEq, Ord, PartialOrd, Hash, Serialize)]
-struct NFeKeys<'a> {
-    ncm: &'a str,
-    descricao: &'a str,
+pub(crate) struct NFeKeys<'a> {
+    pub(crate) ncm: &'a str,
+    pub(crate) descricao: &'a str,
 }
 
 #[derive(Debug, Default, Clone, Serialize)]
-struct NFeItens<'a> {
-    keys: NFeKeys<'a>,
-    valor: f64,
-    pct: f64,
+pub(crate) struct NFeItens<'a> {
+    pub(crate) keys: NFeKeys<'a>,
+    pub(crate) valor: f64,
+    pub(crate) pct: f64,
 }
 
 impl fmt::Display for NFeItens<'_> {
@@ -48,6 +48,20 @@ pub fn get_nfes_grouped_by_ncm_description(
     nfe_info: &BTreeMap<KeyDoc, Vec<InfoNfe>>,
     arguments: &Arguments,
 ) -> Vec<String> {
+    get_itens_por_ncm_descricao(nfes, nfe_info)
+        .into_iter()
+        // Collect at most the N largest items
+        .take(arguments.itens)
+        .map(|item| item.to_string())
+        .collect()
+}
+
+/// Soma o valor dos itens das NF-es por (NCM, descrição), com a porcentagem
+/// sobre o valor total, em ordem decrescente de valor e sem limite de itens.
+pub(crate) fn get_itens_por_ncm_descricao<'a>(
+    nfes: &HashSet<String>,
+    nfe_info: &'a BTreeMap<KeyDoc, Vec<InfoNfe>>,
+) -> Vec<NFeItens<'a>> {
     let tuples: Vec<(NFeKeys, f64)> = nfes
         .par_iter() // rayon: parallel iterator
         .flat_map(|nfe| {
@@ -89,9 +103,6 @@ pub fn get_nfes_grouped_by_ncm_description(
                     item.keys.descricao,
                 )
             })
-            // Collect at most the N largest items
-            .take(arguments.itens)
-            .map(|item| item.to_string())
             .collect(),
         _ => Vec::new(),
     }
@@ -234,3 +245,23 @@ pub fn get_total_value_nfes(
         .sum::<Option<f64>>()
         .map(|sum| sum.round_float(2))
 }
+
+/// Calcula a divergência entre um valor declarado e o valor de referência.
+///
+/// Retorna `(declarado - referência, percentual sobre a referência)`.
+///
+/// O percentual não é calculado quando a referência é nula.
+pub fn get_divergencia(
+    declarado: Option<f64>,
+    referencia: Option<f64>,
+) -> (Option<f64>, Option<f64>) {
+    match (declarado, referencia) {
+        (Some(declarado), Some(referencia)) => {
+            let diferenca = declarado - referencia;
+            let porcentagem =
+                (referencia != 0.0).then(|| ((diferenca / referencia) * 100.0).round_float(2));
+            (Some(diferenca.round_float(3)), porcentagem)
+        }
+        _ => (None, None),
+    }
+}
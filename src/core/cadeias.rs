@@ -0,0 +1,409 @@
+//! # Cadeias de Frete
+//!
+//! Uma cadeia é uma componente conexa do grafo de documentos: os CT-es ligados pelas
+//! relações que propagam NF-es (`--propagar-nfes`, além da substituição) e as NF-es
+//! declaradas por eles. CT-es cancelados e chaves referenciadas mas ausentes do lote
+//! também são membros, para que a tabela "Cadeias" aponte as lacunas de cada cadeia.
+//! Um CT-e cancelado integra a cadeia de um de seus documentos, mas não une cadeias distintas.
+//!
+//! O identificador da cadeia é a menor chave de CT-e lida entre os seus membros:
+//! não depende da ordem de leitura dos arquivos e se repete entre execuções sobre o mesmo lote.
+
+use chrono::NaiveDate;
+use claudiofsr_lib::RoundFloat;
+use rust_xlsxwriter::serialize_option_datetime_to_excel;
+use serde::{Deserialize, Serialize};
+use std::collections::{BTreeMap, BTreeSet, HashMap};
+use struct_iterable::Iterable;
+
+use crate::{
+    DisjointSet, GetKey, InfoExtension, TipoRelacaoCte, serialize_vec_string,
+    xml_structs::{cte::InfoCte, nfe_documento::InfoNfeDocumento},
+};
+
+/// Linha da tabela "Cadeias": resumo de uma cadeia de frete.
+#[derive(Debug, Default, Serialize, Deserialize, Clone, Iterable)]
+pub struct InfoCadeia {
+    #[serde(rename = "Cadeia de Frete", default)]
+    pub cadeia: Option<String>,
+
+    /// CT-es lidos, inclusive cancelados.
+    #[serde(rename = "Quantidade de CTes", default)]
+    pub quantidade_ctes: usize,
+
+    /// NF-es lidas, inclusive canceladas.
+    #[serde(rename = "Quantidade de NFes", default)]
+    pub quantidade_nfes: usize,
+
+    /// Chaves referenciadas pelos CT-es que não constam do lote.
+    #[serde(rename = "Quantidade de Chaves Não Encontradas", default)]
+    pub quantidade_nao_encontrados: usize,
+
+    /// Soma do valor da prestação (vTPrest) dos CT-es válidos.
+    #[serde(rename = "Valor Total de CTes", default)]
+    pub valor_total_ctes: f64,
+
+    /// Soma do valor (vNF) das NF-es válidas.
+    #[serde(rename = "Valor Total de NFes", default)]
+    pub valor_total_nfes: f64,
+
+    /// Emitentes dos CT-es: "CNPJ - Nome".
+    #[serde(
+        rename = "Transportadoras",
+        serialize_with = "serialize_vec_string",
+        default
+    )]
+    pub transportadoras: Vec<String>,
+
+    #[serde(
+        rename = "Data de Emissão Inicial",
+        serialize_with = "serialize_option_datetime_to_excel",
+        default
+    )]
+    pub data_inicial: Option<NaiveDate>,
+
+    #[serde(
+        rename = "Data de Emissão Final",
+        serialize_with = "serialize_option_datetime_to_excel",
+        default
+    )]
+    pub data_final: Option<NaiveDate>,
+
+    /// "Sim" se algum CT-e ou NF-e da cadeia foi cancelado.
+    #[serde(rename = "Possui Cancelados", default)]
+    pub possui_cancelados: Option<String>,
+
+    /// "Sim" se alguma chave referenciada não consta do lote.
+    #[serde(rename = "Possui Não Encontrados", default)]
+    pub possui_nao_encontrados: Option<String>,
+}
+
+impl InfoExtension for InfoCadeia {}
+
+impl GetKey for InfoCadeia {
+    fn get_chave(&self) -> Option<String> {
+        self.cadeia.clone()
+    }
+}
+
+/// Atribui a cada chave (CT-e ou NF-e) o identificador da sua cadeia de frete.
+///
+/// As arestas partem dos CT-es lidos: NF-es declaradas (`rem/infNFe` e `infDoc/infNFe`) ou
+/// correlacionadas a documentos em papel, e CT-es referenciados por relações dos tipos em `propagar` ou de substituição.
+///
+/// As arestas de CT-es cancelados não unem cadeias: o cancelado é anexado à cadeia em que já
+/// está ou à do vizinho de menor chave, e apenas os vizinhos ainda sem cadeia o acompanham.
+pub fn get_componentes(ctes: &[InfoCte], propagar: &[TipoRelacaoCte]) -> HashMap<String, String> {
+    let arestas: Vec<(&str, bool, Vec<&str>)> = ctes
+        .iter()
+        .filter_map(|info| {
+            let cte = info.cte.as_deref()?;
+            let relacionados = info
+                .relacoes
+                .iter()
+                .filter(|relacao| {
+                    propagar.contains(&relacao.tipo) || relacao.tipo == TipoRelacaoCte::Substituicao
+                })
+                .map(|relacao| relacao.referenciado());
+            let vizinhos = [
+                &info.remetente_nfes,
+                &info.nfes_vinculados,
+                &info.nfes_papel,
+            ]
+            .into_iter()
+            .flatten()
+            .map(String::as_str)
+            .chain(relacionados)
+            .filter(|&chave| chave != cte)
+            .collect();
+            Some((cte, info.is_canceled(), vizinhos))
+        })
+        .collect();
+
+    let mut indices: HashMap<&str, usize> = HashMap::new();
+    let mut chaves: Vec<&str> = Vec::new();
+    for chave in arestas
+        .iter()
+        .flat_map(|(cte, _, vizinhos)| std::iter::once(*cte).chain(vizinhos.iter().copied()))
+    {
+        indices.entry(chave).or_insert_with(|| {
+            chaves.push(chave);
+            chaves.len() - 1
+        });
+    }
+
+    let mut dsu = DisjointSet::new(chaves.len());
+    let mut ligados = vec![false; chaves.len()];
+    for (cte, _, vizinhos) in arestas.iter().filter(|(_, cancelado, _)| !cancelado) {
+        ligados[indices[cte]] = true;
+        for vizinho in vizinhos {
+            ligados[indices[vizinho]] = true;
+            dsu.union(indices[cte], indices[vizinho]);
+        }
+    }
+
+    // CT-es cancelados, em ordem de chave para que o resultado não dependa da leitura
+    let mut cancelados: Vec<&(&str, bool, Vec<&str>)> = arestas
+        .iter()
+        .filter(|(_, cancelado, _)| *cancelado)
+        .collect();
+    cancelados.sort_by_key(|(cte, ..)| *cte);
+
+    for (cte, _, vizinhos) in cancelados {
+        let indice = indices[cte];
+        let ancora = if ligados[indice] {
+            Some(indice)
+        } else {
+            vizinhos
+                .iter()
+                .map(|vizinho| indices[vizinho])
+                .filter(|&vizinho| ligados[vizinho])
+                .min_by_key(|&vizinho| chaves[vizinho])
+        };
+        if let Some(ancora) = ancora {
+            dsu.union(indice, ancora);
+        }
+        ligados[indice] = true;
+
+        for vizinho in vizinhos.iter().map(|vizinho| indices[vizinho]) {
+            if !ligados[vizinho] {
+                ligados[vizinho] = true;
+                dsu.union(indice, vizinho);
+            }
+        }
+    }
+
+    // Identificador: menor chave de CT-e lida em cada componente
+    let mut identificadores: HashMap<usize, &str> = HashMap::new();
+    for (cte, ..) in &arestas {
+        let raiz = dsu.find(indices[cte]);
+        identificadores
+            .entry(raiz)
+            .and_modify(|menor| *menor = (*menor).min(*cte))
+            .or_insert(*cte);
+    }
+
+    chaves
+        .iter()
+        .enumerate()
+        .map(|(indice, chave)| {
+            let raiz = dsu.find(indice);
+            (chave.to_string(), identificadores[&raiz].to_string())
+        })
+        .collect()
+}
+
+/// Consolida uma linha por cadeia, em ordem crescente de identificador.
+pub fn get_cadeias(
+    componentes: &HashMap<String, String>,
+    ctes: &[InfoCte],
+    documentos: &[InfoNfeDocumento],
+) -> Vec<InfoCadeia> {
+    let ctes: HashMap<&str, &InfoCte> = ctes
+        .iter()
+        .filter_map(|info| info.cte.as_deref().map(|cte| (cte, info)))
+        .collect();
+    let documentos: HashMap<&str, &InfoNfeDocumento> = documentos
+        .iter()
+        .filter_map(|documento| documento.nfe.as_deref().map(|nfe| (nfe, documento)))
+        .collect();
+
+    let mut membros: BTreeMap<&str, Vec<&str>> = BTreeMap::new();
+    for (chave, cadeia) in componentes {
+        membros.entry(cadeia).or_default().push(chave);
+    }
+
+    membros
+        .into_iter()
+        .map(|(cadeia, chaves)| {
+            let mut linha = InfoCadeia {
+                cadeia: Some(cadeia.to_string()),
+                ..Default::default()
+            };
+            let mut transportadoras: BTreeSet<String> = BTreeSet::new();
+            let mut datas: Vec<NaiveDate> = Vec::new();
+            let mut cancelados = false;
+
+            for chave in chaves {
+                if let Some(info) = ctes.get(chave) {
+                    linha.quantidade_ctes += 1;
+                    cancelados |= info.is_canceled();
+                    if info.is_valid() {
+                        linha.valor_total_ctes += info.valor_total.unwrap_or_default();
+                    }
+                    let transportadora = [
+                        info.emitente_cnpj.as_ref().or(info.emitente_cpf.as_ref()),
+                        info.emitente_nome.as_ref(),
+                    ]
+                    .into_iter()
+                    .flatten()
+                    .map(String::as_str)
+                    .collect::<Vec<_>>()
+                    .join(" - ");
+                    if !transportadora.is_empty() {
+                        transportadoras.insert(transportadora);
+                    }
+                    datas.extend(info.data_emissao);
+                } else if let Some(documento) = documentos.get(chave) {
+                    linha.quantidade_nfes += 1;
+                    cancelados |= !documento.is_valid();
+                    if documento.is_valid() {
+                        linha.valor_total_nfes += documento.valor_total_nfe.unwrap_or_default();
+                    }
+                    datas.extend(documento.data_emissao);
+                } else {
+                    linha.quantidade_nao_encontrados += 1;
+                }
+            }
+
+            linha.valor_total_ctes = linha.valor_total_ctes.round_float(2);
+            linha.valor_total_nfes = linha.valor_total_nfes.round_float(2);
+            linha.transportadoras = transportadoras.into_iter().collect();
+            linha.data_inicial = datas.iter().min().copied();
+            linha.data_final = datas.iter().max().copied();
+            linha.possui_cancelados = Some(sim_ou_nao(cancelados));
+            linha.possui_nao_encontrados = Some(sim_ou_nao(linha.quantidade_nao_encontrados > 0));
+            linha
+        })
+        .collect()
+}
+
+fn sim_ou_nao(valor: bool) -> String {
+    if valor { "Sim" } else { "Não" }.to_string()
+}
+
+//----------------------------------------------------------------------------//
+//                                   Tests                                    //
+//----------------------------------------------------------------------------//
+
+/// Run tests with:
+/// cargo test -- --show-output tests_cadeias
+#[cfg(test)]
+mod tests_cadeias {
+    use super::*;
+    use crate::{
+        RelacaoCte,
+        core::fixtures::{self, CteXml, chaves, docs_fiscais},
+    };
+
+    fn cte(chave: &str, nfes: &[&str], valor: f64, dia: u32) -> InfoCte {
+        let mut info = fixtures::cte(chave);
+        info.emitente_cnpj = Some("12.345.678/0001-90".to_string());
+        info.emitente_nome = Some("Transportadora A".to_string());
+        info.remetente_nfes = chaves(nfes);
+        info.valor_total = Some(valor);
+        info.data_emissao = NaiveDate::from_ymd_opt(2025, 3, dia);
+        info
+    }
+
+    fn documento(chave: &str, valor: f64) -> InfoNfeDocumento {
+        let mut documento = fixtures::documento(chave);
+        documento.valor_total_nfe = Some(valor);
+        documento
+    }
+
+    #[test]
+    /// `cargo test -- --show-output cadeias_de_frete`
+    fn cadeias_de_frete() {
+        let mut cte3 = cte("cte3", &[], 3.0, 3);
+        cte3.relacoes.push(RelacaoCte::xml(
+            "cte2",
+            "cte3",
+            TipoRelacaoCte::Complementar,
+            "infCteComp",
+        ));
+        let mut cte9 = cte("cte9", &["nfe9"], 9.0, 9);
+        cte9.cancelado = Some("Cancelado".to_string());
+
+        let ctes = vec![
+            cte("cte2", &["nfe1", "nfe2"], 2.0, 2),
+            cte3,
+            cte("cte5", &["nfe5"], 5.0, 5),
+            cte9,
+        ];
+        let documentos = vec![documento("nfe1", 100.0), documento("nfe5", 500.0)];
+
+        let componentes = get_componentes(&ctes, &TipoRelacaoCte::PROPAGACAO_PADRAO);
+        assert_eq!(componentes["cte3"], "cte2");
+        assert_eq!(componentes["nfe2"], "cte2");
+        assert_eq!(componentes["nfe9"], "cte9");
+
+        let cadeias = get_cadeias(&componentes, &ctes, &documentos);
+        println!("cadeias: {cadeias:#?}");
+        assert_eq!(cadeias.len(), 3);
+
+        let cadeia = &cadeias[0];
+        assert_eq!(cadeia.cadeia.as_deref(), Some("cte2"));
+        assert_eq!(
+            (
+                cadeia.quantidade_ctes,
+                cadeia.quantidade_nfes,
+                cadeia.quantidade_nao_encontrados
+            ),
+            (2, 1, 1)
+        );
+        assert_eq!(cadeia.valor_total_ctes, 5.0);
+        assert_eq!(cadeia.valor_total_nfes, 100.0);
+        assert_eq!(
+            cadeia.transportadoras,
+            ["12.345.678/0001-90 - Transportadora A"]
+        );
+        assert_eq!(cadeia.data_inicial, NaiveDate::from_ymd_opt(2025, 3, 2));
+        assert_eq!(cadeia.data_final, NaiveDate::from_ymd_opt(2025, 3, 3));
+        assert_eq!(cadeia.possui_nao_encontrados.as_deref(), Some("Sim"));
+        assert_eq!(cadeia.possui_cancelados.as_deref(), Some("Não"));
+
+        let cancelada = &cadeias[2];
+        assert_eq!(cancelada.valor_total_ctes, 0.0);
+        assert_eq!(cancelada.possui_cancelados.as_deref(), Some("Sim"));
+    }
+
+    /**
+        cte_a: [nfe_1]; cte_b: [nfe_2]; cte_c (cancelado): [nfe_1, nfe_2, nfe_3]
+
+        O CTe cancelado entra na cadeia de nfe_1 (menor chave), com a nfe_3 que só ele
+        declara, sem unir as cadeias de cte_a e cte_b.
+
+        `cargo test -- --show-output cte_cancelado_nao_une_cadeias`
+    */
+    #[test]
+    fn cte_cancelado_nao_une_cadeias() {
+        const CTE_A: &str = "35250134567890123456570060000003011000003016";
+        const CTE_B: &str = "35250134567890123456570060000003021000003024";
+        const CTE_C: &str = "35250134567890123456570060000003031000003032";
+        const NFE_1: &str = "35250156789012345678550010000030011000030011";
+        const NFE_2: &str = "35250156789012345678550010000030021000030029";
+        const NFE_3: &str = "35250156789012345678550010000030031000030037";
+
+        let mut docs = docs_fiscais(
+            &[
+                CteXml::new(CTE_A, &[NFE_1]),
+                CteXml::new(CTE_B, &[NFE_2]),
+                CteXml::new(CTE_C, &[NFE_1, NFE_2, NFE_3]),
+            ],
+            &[NFE_1, NFE_2, NFE_3],
+        );
+
+        // Cancelamento homologado, como registrado na leitura dos eventos
+        for info in docs
+            .ctes
+            .iter_mut()
+            .filter(|info| info.cte.as_deref() == Some(CTE_C))
+        {
+            info.cancelado = Some("Cancelado".to_string());
+        }
+
+        let componentes = get_componentes(&docs.ctes, &TipoRelacaoCte::PROPAGACAO_PADRAO);
+        println!("componentes: {componentes:#?}");
+
+        assert_eq!(componentes[NFE_1], CTE_A);
+        assert_eq!(componentes[NFE_2], CTE_B);
+        assert_eq!(componentes[CTE_C], CTE_A);
+        assert_eq!(componentes[NFE_3], CTE_A);
+
+        let cadeias = get_cadeias(&componentes, &docs.ctes, &docs.nfes_documentos);
+        assert_eq!(cadeias.len(), 2);
+        assert_eq!(cadeias[0].possui_cancelados.as_deref(), Some("Sim"));
+        assert_eq!(cadeias[1].possui_cancelados.as_deref(), Some("Não"));
+    }
+}
@@ -0,0 +1,410 @@
+//! # Detalhes e Prazo dos Cancelamentos
+//!
+//! O cancelamento chega por evento (NF-e 110111/110112, CT-e 110111) ou, nos leiautes
+//! antigos, por arquivo `procCancNFe`/`procCancCTe`. Além da marcação "Cancelado", cada
+//! NF-e e CT-e recebe a data, o protocolo, a justificativa e a origem do cancelamento
+//! mais antigo encontrado.
+//!
+//! A coluna "Alerta do Cancelamento" assinala:
+//! - cancelamento após o prazo legal, contado da autorização de uso: 24 horas para a
+//!   NF-e e 168 horas (7 dias) para o CT-e;
+//! - CT-e cancelado após a autorização de MDF-e que o relaciona (evento 310610 sem o
+//!   correspondente 310611) ou após a autorização de CT-e substituto presente no lote.
+
+use chrono::{DateTime, NaiveDateTime, TimeDelta};
+use rayon::prelude::*;
+use std::collections::HashMap;
+
+use crate::{
+    TipoRelacaoCte,
+    xml_structs::{
+        cancelamento_cte::InfoCteCancel, cancelamento_nfe::InfoNfeCancel, cte::InfoCte,
+        cte_evento::InfoCteEvento, nfe_documento::InfoNfeDocumento, nfe_evento::InfoNfeEvento,
+    },
+};
+
+/// Prazo de cancelamento da NF-e, em horas da autorização de uso.
+const PRAZO_NFE_HORAS: i64 = 24;
+
+/// Prazo de cancelamento do CT-e, em horas da autorização de uso.
+const PRAZO_CTE_HORAS: i64 = 168;
+
+/// Eventos de CT-e: MDF-e Autorizado e MDF-e Cancelado.
+const MDFE_AUTORIZADO: &str = "310610";
+const MDFE_CANCELADO: &str = "310611";
+
+/// Data e hora em UTC; sem fuso horário, assume-se o horário de Brasília (UTC-03:00).
+pub(crate) fn data_hora(texto: &str) -> Option<NaiveDateTime> {
+    let texto = texto.trim();
+    DateTime::parse_from_rfc3339(texto)
+        .map(|dh| dh.naive_utc())
+        .or_else(|_| {
+            NaiveDateTime::parse_from_str(texto, "%Y-%m-%dT%H:%M:%S")
+                .map(|dh| dh + TimeDelta::hours(3))
+        })
+        .ok()
+}
+
+/// Cancelamento de um documento, por evento ou por arquivo de cancelamento.
+#[derive(Debug)]
+struct Cancelamento<'a> {
+    dh: Option<&'a str>,
+    dh_utc: Option<NaiveDateTime>,
+    protocolo: Option<&'a str>,
+    justificativa: Option<&'a str>,
+    origem: String,
+}
+
+impl<'a> Cancelamento<'a> {
+    fn new(
+        origem: String,
+        dh: &'a Option<String>,
+        protocolo: &'a Option<String>,
+        justificativa: &'a Option<String>,
+    ) -> Self {
+        Self {
+            dh: dh.as_deref(),
+            dh_utc: dh.as_deref().and_then(data_hora),
+            protocolo: protocolo.as_deref(),
+            justificativa: justificativa.as_deref(),
+            origem,
+        }
+    }
+
+    fn do_evento(
+        tp_evento: &Option<String>,
+        dh_evento: &'a Option<String>,
+        protocolo: &'a Option<String>,
+        detalhe: &'a Option<String>,
+    ) -> Self {
+        let origem = match tp_evento {
+            Some(tp_evento) => format!("Evento {tp_evento}"),
+            None => "Evento".to_string(),
+        };
+        // O texto do evento de cancelamento é a justificativa (`xJust`)
+        Self::new(origem, dh_evento, protocolo, detalhe)
+    }
+
+    /// Cancelamentos sem data ficam por último.
+    fn ordem(&self) -> (bool, Option<NaiveDateTime>) {
+        (self.dh_utc.is_none(), self.dh_utc)
+    }
+
+    /// Alerta de cancelamento posterior ao prazo contado da autorização de uso.
+    fn alerta_prazo(&self, dh_autorizacao: &Option<String>, prazo_horas: i64) -> Option<String> {
+        let autorizacao = dh_autorizacao.as_deref().and_then(data_hora)?;
+        let decorrido = self.dh_utc? - autorizacao;
+        (decorrido > TimeDelta::hours(prazo_horas)).then(|| {
+            format!(
+                "Cancelado {}h após a autorização (prazo de {prazo_horas}h)",
+                decorrido.num_hours()
+            )
+        })
+    }
+}
+
+/// O cancelamento mais antigo de cada chave.
+fn mais_antigos<'a>(
+    cancelamentos: impl Iterator<Item = (&'a str, Cancelamento<'a>)>,
+) -> HashMap<&'a str, Cancelamento<'a>> {
+    let mut mapa: HashMap<&str, Cancelamento> = HashMap::new();
+    for (chave, cancelamento) in cancelamentos {
+        let anterior = mapa
+            .get(chave)
+            .is_some_and(|atual| atual.ordem() <= cancelamento.ordem());
+        if !anterior {
+            mapa.insert(chave, cancelamento);
+        }
+    }
+    mapa
+}
+
+/// Preenche as colunas de cancelamento das NF-es e o alerta de prazo (24 horas).
+pub fn adicionar_cancelamentos_nfe(
+    documentos: &mut [InfoNfeDocumento],
+    eventos_nfe: &[InfoNfeEvento],
+    cancelamentos_nfe: &[InfoNfeCancel],
+) {
+    let eventos = eventos_nfe
+        .iter()
+        .filter(|evento| evento.cancelado)
+        .filter_map(|evento| {
+            let cancelamento = Cancelamento::do_evento(
+                &evento.tp_evento,
+                &evento.dh_evento,
+                &evento.protocolo,
+                &evento.detalhe,
+            );
+            Some((evento.nfe.as_deref()?, cancelamento))
+        });
+
+    let arquivos = cancelamentos_nfe
+        .iter()
+        .filter(|cancelamento| cancelamento.cancelado)
+        .filter_map(|info| {
+            let cancelamento = Cancelamento::new(
+                "procCancNFe".to_string(),
+                &info.dh_cancelamento,
+                &info.protocolo,
+                &info.justificativa,
+            );
+            Some((info.nfe.as_deref()?, cancelamento))
+        });
+
+    let cancelamentos = mais_antigos(eventos.chain(arquivos));
+
+    documentos.par_iter_mut().for_each(|documento| {
+        let Some(cancelamento) = documento
+            .nfe
+            .as_deref()
+            .and_then(|nfe| cancelamentos.get(nfe))
+        else {
+            return;
+        };
+        documento.dh_cancelamento = cancelamento.dh.map(str::to_string);
+        documento.protocolo_cancelamento = cancelamento.protocolo.map(str::to_string);
+        documento.justificativa_cancelamento = cancelamento.justificativa.map(str::to_string);
+        documento.origem_cancelamento = Some(cancelamento.origem.clone());
+        documento.alerta_cancelamento =
+            cancelamento.alerta_prazo(&documento.dh_autorizacao, PRAZO_NFE_HORAS);
+    });
+}
+
+/// Preenche as colunas de cancelamento dos CT-es e os alertas de prazo (7 dias),
+/// de MDF-e autorizado e de CT-e substituto anteriores ao cancelamento.
+///
+/// Requer as relações de substituição já incorporadas
+/// (ver [`adicionar_eventos_cte`](crate::adicionar_eventos_cte)).
+pub fn adicionar_cancelamentos_cte(
+    ctes: &mut [InfoCte],
+    eventos_cte: &[InfoCteEvento],
+    cancelamentos_cte: &[InfoCteCancel],
+) {
+    let eventos = eventos_cte
+        .iter()
+        .filter(|evento| evento.cancelado)
+        .filter_map(|evento| {
+            let cancelamento = Cancelamento::do_evento(
+                &evento.tp_evento,
+                &evento.dh_evento,
+                &evento.protocolo,
+                &evento.detalhe,
+            );
+            Some((evento.cte.as_deref()?, cancelamento))
+        });
+
+    let arquivos = cancelamentos_cte
+        .iter()
+        .filter(|cancelamento| cancelamento.cancelado)
+        .filter_map(|info| {
+            let cancelamento = Cancelamento::new(
+                "procCancCTe".to_string(),
+                &info.dh_cancelamento,
+                &info.protocolo,
+                &info.justificativa,
+            );
+            Some((info.cte.as_deref()?, cancelamento))
+        });
+
+    let cancelamentos = mais_antigos(eventos.chain(arquivos));
+
+    // Eventos de MDF-e de cada CT-e, em ordem cronológica
+    let mut mdfes: HashMap<&str, Vec<(NaiveDateTime, &str)>> = HashMap::new();
+    for evento in eventos_cte {
+        if let (Some(cte), Some(tp_evento), Some(dh)) = (
+            evento.cte.as_deref(),
+            evento.tp_evento.as_deref(),
+            evento.dh_evento.as_deref().and_then(data_hora),
+        ) && [MDFE_AUTORIZADO, MDFE_CANCELADO].contains(&tp_evento)
+        {
+            mdfes.entry(cte).or_default().push((dh, tp_evento));
+        }
+    }
+    mdfes.values_mut().for_each(|eventos| eventos.sort());
+
+    // CT-es substitutos de cada CT-e, com a data e hora da autorização
+    let autorizacoes: HashMap<&str, NaiveDateTime> = ctes
+        .iter()
+        .filter_map(|info| {
+            let dh = info.dh_autorizacao.as_deref().and_then(data_hora)?;
+            Some((info.cte.as_deref()?, dh))
+        })
+        .collect();
+
+    let mut substitutos: HashMap<String, Vec<(String, NaiveDateTime)>> = HashMap::new();
+    for relacao in ctes.iter().flat_map(|info| &info.relacoes) {
+        if relacao.tipo == TipoRelacaoCte::Substituicao
+            && relacao.original != relacao.derivado
+            && let Some(dh) = autorizacoes.get(relacao.derivado.as_str())
+        {
+            let lista = substitutos.entry(relacao.original.clone()).or_default();
+            if !lista
+                .iter()
+                .any(|(derivado, _)| *derivado == relacao.derivado)
+            {
+                lista.push((relacao.derivado.clone(), *dh));
+            }
+        }
+    }
+
+    ctes.par_iter_mut().for_each(|info| {
+        let Some(cte) = info.cte.as_deref() else {
+            return;
+        };
+        let Some(cancelamento) = cancelamentos.get(cte) else {
+            return;
+        };
+
+        let mut alertas: Vec<String> = Vec::new();
+        alertas.extend(cancelamento.alerta_prazo(&info.dh_autorizacao, PRAZO_CTE_HORAS));
+
+        if let Some(dh_cancelamento) = cancelamento.dh_utc {
+            // Último evento de MDF-e antes do cancelamento: autorizado e não cancelado
+            let mdfe = mdfes.get(cte).and_then(|eventos| {
+                eventos
+                    .iter()
+                    .rev()
+                    .find(|(dh, _)| *dh < dh_cancelamento)
+                    .filter(|(_, tp_evento)| *tp_evento == MDFE_AUTORIZADO)
+            });
+            alertas.extend(mdfe.map(|(dh, _)| {
+                format!(
+                    "Cancelado após a autorização de MDF-e em {}",
+                    dh.format("%Y-%m-%d")
+                )
+            }));
+
+            alertas.extend(
+                substitutos
+                    .get(cte)
+                    .into_iter()
+                    .flatten()
+                    .filter(|(_, dh)| *dh < dh_cancelamento)
+                    .map(|(derivado, _)| {
+                        format!("Cancelado após a autorização do CTe substituto {derivado}")
+                    }),
+            );
+        }
+
+        info.dh_cancelamento = cancelamento.dh.map(str::to_string);
+        info.protocolo_cancelamento = cancelamento.protocolo.map(str::to_string);
+        info.justificativa_cancelamento = cancelamento.justificativa.map(str::to_string);
+        info.origem_cancelamento = Some(cancelamento.origem.clone());
+        info.alerta_cancelamento = (!alertas.is_empty()).then(|| alertas.join("; "));
+    });
+}
+
+//----------------------------------------------------------------------------//
+//                                   Tests                                    //
+//----------------------------------------------------------------------------//
+
+/// Run tests with:
+/// cargo test -- --show-output tests_cancelamentos
+#[cfg(test)]
+mod tests_cancelamentos {
+    use super::*;
+    use crate::RelacaoCte;
+
+    #[test]
+    /// `cargo test -- --show-output cancelamento_de_nfe_fora_do_prazo`
+    fn cancelamento_de_nfe_fora_do_prazo() {
+        let eventos_nfe = vec![InfoNfeEvento {
+            nfe: Some("nfe1".to_string()),
+            cancelado: true,
+            tp_evento: Some("110111".to_string()),
+            dh_evento: Some("2025-03-11T14:00:00-03:00".to_string()),
+            protocolo: Some("135250000000001".to_string()),
+            detalhe: Some("Erro no valor da operação".to_string()),
+            ..Default::default()
+        }];
+        let cancelamentos_nfe = vec![InfoNfeCancel {
+            nfe: Some("nfe2".to_string()),
+            cancelado: true,
+            dh_cancelamento: Some("2025-03-10T12:00:00".to_string()),
+            protocolo: Some("135250000000002".to_string()),
+            ..Default::default()
+        }];
+
+        let mut documentos: Vec<InfoNfeDocumento> = ["nfe1", "nfe2", "nfe3"]
+            .into_iter()
+            .map(|nfe| {
+                let mut documento = InfoNfeDocumento::default();
+                documento.nfe = Some(nfe.to_string());
+                documento.dh_autorizacao = Some("2025-03-10T10:00:00-03:00".to_string());
+                documento
+            })
+            .collect();
+
+        adicionar_cancelamentos_nfe(&mut documentos, &eventos_nfe, &cancelamentos_nfe);
+        println!("documentos: {documentos:#?}");
+
+        assert_eq!(
+            documentos[0].origem_cancelamento.as_deref(),
+            Some("Evento 110111")
+        );
+        assert_eq!(
+            documentos[0].justificativa_cancelamento.as_deref(),
+            Some("Erro no valor da operação")
+        );
+        assert_eq!(
+            documentos[0].alerta_cancelamento.as_deref(),
+            Some("Cancelado 28h após a autorização (prazo de 24h)")
+        );
+        assert_eq!(
+            documentos[1].origem_cancelamento.as_deref(),
+            Some("procCancNFe")
+        );
+        assert_eq!(documentos[1].alerta_cancelamento, None);
+        assert_eq!(documentos[2].dh_cancelamento, None);
+    }
+
+    #[test]
+    /// `cargo test -- --show-output cancelamento_de_cte_apos_mdfe_e_substituto`
+    fn cancelamento_de_cte_apos_mdfe_e_substituto() {
+        let evento = |tp_evento: &str, dh_evento: &str, cancelado: bool| InfoCteEvento {
+            cte: Some("cte1".to_string()),
+            cancelado,
+            tp_evento: Some(tp_evento.to_string()),
+            dh_evento: Some(dh_evento.to_string()),
+            ..Default::default()
+        };
+        let eventos_cte = vec![
+            evento("310610", "2025-03-01T08:00:00-03:00", false),
+            evento("310611", "2025-03-02T08:00:00-03:00", false),
+            evento("310610", "2025-03-03T08:00:00-03:00", false),
+            evento("110111", "2025-03-12T08:00:00-03:00", true),
+        ];
+
+        let mut cte1 = InfoCte::default();
+        cte1.cte = Some("cte1".to_string());
+        cte1.dh_autorizacao = Some("2025-03-01T07:00:00-03:00".to_string());
+
+        let mut cte2 = InfoCte::default();
+        cte2.cte = Some("cte2".to_string());
+        cte2.dh_autorizacao = Some("2025-03-05T07:00:00-03:00".to_string());
+        cte2.relacoes = vec![RelacaoCte::xml(
+            "cte1",
+            "cte2",
+            TipoRelacaoCte::Substituicao,
+            "infCteSub",
+        )];
+
+        let mut ctes = vec![cte1, cte2];
+        adicionar_cancelamentos_cte(&mut ctes, &eventos_cte, &[]);
+        println!("ctes: {ctes:#?}");
+
+        assert_eq!(
+            ctes[0].dh_cancelamento.as_deref(),
+            Some("2025-03-12T08:00:00-03:00")
+        );
+        assert_eq!(
+            ctes[0].alerta_cancelamento.as_deref(),
+            Some(
+                "Cancelado 265h após a autorização (prazo de 168h); \
+                 Cancelado após a autorização de MDF-e em 2025-03-03; \
+                 Cancelado após a autorização do CTe substituto cte2"
+            )
+        );
+        assert_eq!(ctes[1].origem_cancelamento, None);
+    }
+}
@@ -0,0 +1,383 @@
+//! # Cartas de Correção Eletrônicas (CC-e)
+//!
+//! A CC-e (evento 110110) corrige campos que não alteram o valor do imposto nem a
+//! identificação das partes: peso, dados cadastrais, observações etc. Na NF-e a
+//! correção é um texto livre (`xCorrecao`); no CT-e, uma lista estruturada de
+//! `grupoAlterado`/`campoAlterado`/`valorAlterado`.
+//!
+//! Cada nova CC-e substitui as anteriores e deve trazer todas as correções em vigor,
+//! de modo que apenas a de maior `nSeqEvento` de cada documento, entre as homologadas
+//! pela SEFAZ, é a CC-e vigente. Opcionalmente (`--aplicar-cce`), as correções da CC-e
+//! vigente dos CT-es são aplicadas sobre as colunas equivalentes de [`InfoCte`].
+//!
+//! No CT-e, a CC-e não pode alterar o valor da prestação, que determina o valor do imposto
+//! (art. 58-B, § 1º, do Convênio SINIEF 06/89): essa correção é apenas sinalizada na tabela,
+//! nunca aplicada. O CFOP informado errado é corrigido como os demais campos.
+
+use rayon::prelude::*;
+use serde::{Deserialize, Serialize};
+use std::collections::{BTreeSet, HashMap};
+use struct_iterable::Iterable;
+
+use crate::{
+    GetKey, InfoExtension, OptExt,
+    xml_structs::{
+        cte::InfoCte, cte_evento::InfoCteEvento, nfe_evento::InfoNfeEvento,
+        ret_evento::is_situacao_homologada,
+    },
+};
+
+/// Código do evento Carta de Correção, comum a NF-e e CT-e.
+const CARTA_DE_CORRECAO: &str = "110110";
+
+/// Campos do CT-e (`grupoAlterado`, `campoAlterado`) que a CC-e não pode corrigir.
+const CORRECOES_VEDADAS: [(&str, &str); 2] = [("vPrest", "vTPrest"), ("vPrest", "vRec")];
+
+const CORRECAO_VEDADA: &str =
+    "Correção vedada (art. 58-B, § 1º, Convênio SINIEF 06/89): não aplicada";
+
+/// Linha da tabela "Cartas de Correção": uma correção de uma CC-e.
+///
+/// A CC-e de NF-e gera uma única linha com o texto da correção; a de CT-e,
+/// uma linha por `infCorrecao`.
+#[derive(Debug, Default, Serialize, Deserialize, Clone, PartialEq, Iterable)]
+pub struct InfoCartaCorrecao {
+    #[serde(rename = "Chave do Documento Fiscal", default)]
+    pub chave: Option<String>,
+
+    /// "NFe" ou "CTe".
+    #[serde(rename = "Tipo de Documento", default)]
+    pub documento: Option<String>,
+
+    #[serde(rename = "Nº Sequencial da CC-e", default)]
+    pub n_seq_evento: Option<u32>,
+
+    #[serde(rename = "Data e Hora da CC-e", default)]
+    pub dh_evento: Option<String>,
+
+    #[serde(rename = "Protocolo da CC-e", default)]
+    pub protocolo: Option<String>,
+
+    /// Retorno da SEFAZ: `cStat - xMotivo`.
+    #[serde(rename = "Situação do Registro", default)]
+    pub situacao: Option<String>,
+
+    /// "Sim" para a CC-e homologada de maior `nSeqEvento` do documento.
+    #[serde(rename = "CC-e Vigente", default)]
+    pub vigente: Option<String>,
+
+    #[serde(rename = "Grupo Alterado", default)]
+    pub grupo: Option<String>,
+
+    #[serde(rename = "Campo Alterado", default)]
+    pub campo: Option<String>,
+
+    /// Ocorrência do grupo alterado, quando o grupo se repete.
+    #[serde(rename = "Nº do Item Alterado", default)]
+    pub item: Option<String>,
+
+    #[serde(rename = "Valor Alterado", default)]
+    pub valor: Option<String>,
+
+    /// Texto livre da CC-e de NF-e (`xCorrecao`).
+    #[serde(rename = "Texto da Correção", default)]
+    pub texto: Option<String>,
+
+    /// Coluna da tabela de CTes sobrescrita pela correção (opção `--aplicar-cce`).
+    #[serde(rename = "Coluna Corrigida no CTe", default)]
+    pub coluna: Option<String>,
+
+    /// Correção de campo que a CC-e não pode alterar (valor da prestação).
+    #[serde(rename = "Observação", default)]
+    pub observacao: Option<String>,
+}
+
+impl InfoExtension for InfoCartaCorrecao {}
+
+impl GetKey for InfoCartaCorrecao {
+    fn get_chave(&self) -> Option<String> {
+        self.chave.clone()
+    }
+}
+
+impl InfoCartaCorrecao {
+    fn is_vigente(&self) -> bool {
+        self.vigente.as_deref() == Some("Sim")
+    }
+
+    fn is_homologada(&self) -> bool {
+        is_situacao_homologada(self.situacao.as_deref())
+    }
+}
+
+fn cartas_nfe(evento: &InfoNfeEvento) -> Vec<InfoCartaCorrecao> {
+    vec![InfoCartaCorrecao {
+        chave: evento.nfe.clone(),
+        documento: Some("NFe".to_string()),
+        n_seq_evento: evento.n_seq_evento,
+        dh_evento: evento.dh_evento.clone(),
+        protocolo: evento.protocolo.clone(),
+        situacao: evento.situacao.clone(),
+        texto: evento.x_correcao.clone(),
+        ..Default::default()
+    }]
+}
+
+fn cartas_cte(evento: &InfoCteEvento) -> Vec<InfoCartaCorrecao> {
+    let carta = InfoCartaCorrecao {
+        chave: evento.cte.clone(),
+        documento: Some("CTe".to_string()),
+        n_seq_evento: evento.n_seq_evento,
+        dh_evento: evento.dh_evento.clone(),
+        protocolo: evento.protocolo.clone(),
+        situacao: evento.situacao.clone(),
+        ..Default::default()
+    };
+
+    if evento.correcoes.is_empty() {
+        return vec![carta];
+    }
+
+    evento
+        .correcoes
+        .iter()
+        .map(|correcao| {
+            let grupo = correcao.grupo_alterado.get_not_empty();
+            let campo = correcao.campo_alterado.get_not_empty();
+            let vedada = grupo
+                .as_deref()
+                .zip(campo.as_deref())
+                .is_some_and(|(g, c)| CORRECOES_VEDADAS.contains(&(g.trim(), c.trim())));
+            InfoCartaCorrecao {
+                item: correcao.nro_item_alterado.get_not_empty(),
+                valor: correcao.valor_alterado.get_not_empty(),
+                observacao: vedada.then(|| CORRECAO_VEDADA.to_string()),
+                grupo,
+                campo,
+                ..carta.clone()
+            }
+        })
+        .collect()
+}
+
+/// Tabela "Cartas de Correção": as correções de todas as CC-es de NF-es e CT-es.
+///
+/// Ordenada por chave, `nSeqEvento` e grupo/campo alterado. A coluna "CC-e Vigente" assinala as
+/// linhas da CC-e de maior `nSeqEvento` de cada documento entre as homologadas (`cStat` 135
+/// ou 136): uma CC-e rejeitada não substitui as anteriores.
+pub fn get_cartas_correcao(
+    eventos_nfe: &[InfoNfeEvento],
+    eventos_cte: &[InfoCteEvento],
+) -> Vec<InfoCartaCorrecao> {
+    let mut cartas: Vec<InfoCartaCorrecao> = eventos_nfe
+        .par_iter()
+        .filter(|evento| evento.tp_evento.as_deref() == Some(CARTA_DE_CORRECAO))
+        .flat_map_iter(cartas_nfe)
+        .chain(
+            eventos_cte
+                .par_iter()
+                .filter(|evento| evento.tp_evento.as_deref() == Some(CARTA_DE_CORRECAO))
+                .flat_map_iter(cartas_cte),
+        )
+        .filter(|carta| carta.chave.is_some())
+        .collect();
+
+    cartas.par_sort_by(|a, b| {
+        (
+            &a.chave,
+            a.n_seq_evento,
+            &a.grupo,
+            &a.campo,
+            &a.item,
+            &a.valor,
+            &a.texto,
+        )
+            .cmp(&(
+                &b.chave,
+                b.n_seq_evento,
+                &b.grupo,
+                &b.campo,
+                &b.item,
+                &b.valor,
+                &b.texto,
+            ))
+    });
+    // A mesma CC-e baixada em mais de um arquivo
+    cartas.dedup();
+
+    let ultimas: HashMap<String, Option<u32>> = cartas
+        .iter()
+        .filter(|carta| carta.is_homologada())
+        .fold(HashMap::new(), |mut ultimas, carta| {
+            if let Some(chave) = &carta.chave {
+                ultimas.insert(chave.clone(), carta.n_seq_evento);
+            }
+            ultimas
+        });
+
+    cartas.par_iter_mut().for_each(|carta| {
+        let vigente = carta.is_homologada()
+            && carta
+                .chave
+                .as_ref()
+                .is_some_and(|chave| ultimas.get(chave) == Some(&carta.n_seq_evento));
+        carta.vigente = Some(if vigente { "Sim" } else { "Não" }.to_string());
+    });
+
+    cartas
+}
+
+/// Aplica as correções da CC-e vigente de cada CT-e sobre as colunas equivalentes.
+///
+/// Preenche "Colunas Corrigidas por CC-e" em [`InfoCte`] e "Coluna Corrigida no CTe"
+/// nas linhas aplicadas de `cartas`. Correções sem coluna correspondente ou vedadas
+/// permanecem apenas na tabela "Cartas de Correção".
+pub fn aplicar_cartas_correcao(ctes: &mut [InfoCte], cartas: &mut [InfoCartaCorrecao]) {
+    let mut por_chave: HashMap<&str, Vec<usize>> = HashMap::new();
+    for (indice, carta) in cartas.iter().enumerate() {
+        if carta.is_vigente()
+            && carta.observacao.is_none()
+            && carta.documento.as_deref() == Some("CTe")
+            && let Some(chave) = carta.chave.as_deref()
+        {
+            por_chave.entry(chave).or_default().push(indice);
+        }
+    }
+
+    let aplicadas: Vec<(usize, &'static str)> = ctes
+        .par_iter_mut()
+        .flat_map_iter(|info| {
+            let indices = info
+                .cte
+                .as_deref()
+                .and_then(|cte| por_chave.get(cte))
+                .map(Vec::as_slice)
+                .unwrap_or_default();
+
+            let aplicadas: Vec<(usize, &'static str)> = indices
+                .iter()
+                .filter_map(|&indice| {
+                    let carta = &cartas[indice];
+                    let (Some(grupo), Some(campo), Some(valor)) =
+                        (&carta.grupo, &carta.campo, &carta.valor)
+                    else {
+                        return None;
+                    };
+                    info.aplicar_correcao(grupo, campo, valor)
+                        .map(|coluna| (indice, coluna))
+                })
+                .collect();
+
+            let colunas: BTreeSet<&str> = aplicadas.iter().map(|(_, coluna)| *coluna).collect();
+            if !colunas.is_empty() {
+                info.colunas_corrigidas = Some(colunas.into_iter().collect::<Vec<_>>().join(", "));
+            }
+            aplicadas
+        })
+        .collect();
+
+    for (indice, coluna) in aplicadas {
+        cartas[indice].coluna = Some(coluna.to_string());
+    }
+}
+
+//----------------------------------------------------------------------------//
+//                                   Tests                                    //
+//----------------------------------------------------------------------------//
+
+/// Run tests with:
+/// cargo test -- --show-output tests_cartas_correcao
+#[cfg(test)]
+mod tests_cartas_correcao {
+    use super::*;
+    use crate::xml_structs::cte_evento::InfCorrecao;
+
+    fn cce_cte(n_seq: u32, correcoes: &[(&str, &str, &str)]) -> InfoCteEvento {
+        InfoCteEvento {
+            cte: Some("cte1".to_string()),
+            tp_evento: Some(CARTA_DE_CORRECAO.to_string()),
+            n_seq_evento: Some(n_seq),
+            correcoes: correcoes
+                .iter()
+                .map(|(grupo, campo, valor)| InfCorrecao {
+                    grupo_alterado: Some(grupo.to_string()),
+                    campo_alterado: Some(campo.to_string()),
+                    valor_alterado: Some(valor.to_string()),
+                    nro_item_alterado: None,
+                })
+                .collect(),
+            situacao: Some("135 - Evento registrado e vinculado ao CT-e".to_string()),
+            ..Default::default()
+        }
+    }
+
+    #[test]
+    /// `cargo test -- --show-output cce_vigente_aplicada`
+    fn cce_vigente_aplicada() {
+        let eventos_nfe = vec![InfoNfeEvento {
+            nfe: Some("nfe1".to_string()),
+            tp_evento: Some(CARTA_DE_CORRECAO.to_string()),
+            n_seq_evento: Some(1),
+            x_correcao: Some("Peso bruto correto: 1.250 kg".to_string()),
+            situacao: Some("135 - Evento registrado e vinculado a NF-e".to_string()),
+            ..Default::default()
+        }];
+        let segunda = cce_cte(
+            2,
+            &[
+                ("infCarga", "vCarga", "1500.00"),
+                ("compl", "xObs", "Entrega agendada"),
+                ("vPrest", "vTPrest", "2000.00"),
+                ("ide", "CFOP", "5353"),
+            ],
+        );
+        let mut rejeitada = cce_cte(3, &[("infCarga", "vCarga", "3000.00")]);
+        rejeitada.situacao = Some("573 - Rejeição: Duplicidade de Evento".to_string());
+
+        let eventos_cte = vec![
+            cce_cte(1, &[("infCarga", "vCarga", "999.00")]),
+            segunda.clone(),
+            segunda, // mesma CC-e em outro arquivo
+            rejeitada,
+        ];
+
+        let mut cartas = get_cartas_correcao(&eventos_nfe, &eventos_cte);
+        println!("cartas: {cartas:#?}");
+
+        // A CC-e rejeitada (nSeqEvento 3) não substitui a anterior
+        assert_eq!(cartas.len(), 7);
+        let vigentes: Vec<bool> = cartas.iter().map(InfoCartaCorrecao::is_vigente).collect();
+        assert_eq!(vigentes, [false, true, true, true, true, false, true]);
+        assert_eq!(
+            cartas[6].texto.as_deref(),
+            Some("Peso bruto correto: 1.250 kg")
+        );
+        assert_eq!(cartas[2].observacao, None);
+        assert_eq!(cartas[4].observacao.as_deref(), Some(CORRECAO_VEDADA));
+
+        let mut info = InfoCte::default();
+        info.cte = Some("cte1".to_string());
+        info.valor_carga = Some(1000.0);
+        info.valor_total = Some(1200.0);
+        let mut ctes = vec![info];
+        aplicar_cartas_correcao(&mut ctes, &mut cartas);
+
+        // Apenas a CC-e vigente é aplicada; xObs não tem coluna equivalente
+        // e o valor da prestação não pode ser corrigido, ao contrário do CFOP
+        assert_eq!(ctes[0].valor_carga, Some(1500.0));
+        assert_eq!(ctes[0].valor_total, Some(1200.0));
+        assert_eq!(
+            ctes[0].colunas_corrigidas.as_deref(),
+            Some("CFOP (Código Fiscal de Operações e Prestações), Valor da Carga")
+        );
+        assert_eq!(cartas[0].coluna, None);
+        assert_eq!(cartas[1].coluna, None);
+        assert_eq!(
+            cartas[2].coluna.as_deref(),
+            Some("CFOP (Código Fiscal de Operações e Prestações)")
+        );
+        assert_eq!(cartas[3].coluna.as_deref(), Some("Valor da Carga"));
+        assert_eq!(cartas[4].coluna, None);
+        assert_eq!(cartas[5].coluna, None);
+    }
+}
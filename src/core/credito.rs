@@ -0,0 +1,378 @@
+//! # Crédito de PIS/COFINS sobre Fretes
+//!
+//! Classifica cada CT-e quanto ao direito de crédito das contribuições não cumulativas
+//! (Leis 10.637/2002 e 10.833/2003) a partir do papel do tomador do serviço, da empresa
+//! auditada e dos itens das NF-es correlacionadas (CFOP, CST e consumidor final).
+//!
+//! * Tomador remetente/expedidor: frete na operação de venda (art. 3º, IX).
+//! * Tomador destinatário/recebedor: frete integrante do custo de aquisição de bens
+//!   para revenda ou insumos (art. 3º, I e II).
+//!
+//! Transferências entre estabelecimentos, aquisições para uso e consumo e bens adquiridos
+//! sem incidência das contribuições não geram crédito.
+
+use chrono::{Datelike, NaiveDate};
+use claudiofsr_lib::RoundFloat;
+use serde::{Deserialize, Serialize};
+use std::collections::{BTreeMap, HashSet};
+use struct_iterable::Iterable;
+
+use crate::{
+    InfoExtension,
+    xml_structs::{agente::TOMADOR_DO_SERVICO, cte::InfoCte, nfe::InfoNfe},
+};
+
+/// Alíquota de crédito de PIS/PASEP no regime não cumulativo (%).
+pub const ALIQ_CREDITO_PIS: f64 = 1.65;
+
+/// Alíquota de crédito de COFINS no regime não cumulativo (%).
+pub const ALIQ_CREDITO_COFINS: f64 = 7.6;
+
+/// CSTs de saída cujas receitas são tributadas pelas contribuições.
+///
+/// Bens adquiridos com CST de alíquota zero, monofásico, isenção, suspensão ou
+/// sem incidência não geram crédito (art. 3º, § 2º, II), tampouco o frete da aquisição.
+const CST_TRIBUTADO: [u8; 4] = [1, 2, 3, 5];
+
+/// Verifica se o item é tributado tanto pelo PIS quanto pela COFINS.
+///
+/// Os CSTs das duas contribuições costumam coincidir, mas são informados em grupos
+/// distintos da NF-e: basta um deles sem incidência para afastar o crédito do frete.
+fn is_cst_tributado(item: &InfoNfe) -> bool {
+    [item.cst_pis, item.cst_cofins]
+        .into_iter()
+        .all(|cst| cst.is_some_and(|cst| CST_TRIBUTADO.contains(&cst)))
+}
+
+/// Natureza da operação do item conforme o CFOP (sem o dígito de origem/destino).
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+enum NaturezaCfop {
+    Venda,
+    Transferencia,
+    Devolucao,
+    Outras,
+}
+
+impl NaturezaCfop {
+    fn from_cfop(cfop: Option<u16>) -> Self {
+        match cfop.map(|c| c % 1000) {
+            Some(151..=159 | 408 | 409 | 552 | 557) => Self::Transferencia,
+            Some(201..=213 | 410..=413 | 553 | 556 | 660..=662) => Self::Devolucao,
+            Some(101..=125 | 401..=405) => Self::Venda,
+            _ => Self::Outras,
+        }
+    }
+}
+
+/// Papel do tomador que define a natureza do frete.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+enum OperacaoFrete {
+    Venda,
+    Aquisicao,
+}
+
+/// Resultado da classificação de um CT-e quanto ao crédito sobre o frete.
+#[derive(Debug, Default, Clone, PartialEq)]
+pub struct ClassificacaoCredito {
+    /// "Sim", "Não" ou "Indeterminado".
+    pub credito: Option<String>,
+    pub base: Option<f64>,
+    pub fundamentacao: Option<String>,
+}
+
+impl ClassificacaoCredito {
+    fn new(credito: &str, base: Option<f64>, fundamentacao: String) -> Self {
+        Self {
+            credito: Some(credito.to_string()),
+            base,
+            fundamentacao: Some(fundamentacao),
+        }
+    }
+}
+
+/// Classifica o CT-e quanto ao crédito de PIS/COFINS sobre o frete.
+///
+/// * `itens`: itens das NF-es correlacionadas ao CT-e.
+/// * `consumidor_final`: chaves das NF-es emitidas para consumidor final (`indFinal = 1`).
+/// * `cnpj_base`: CNPJ base (8 dígitos) da empresa auditada, se informado.
+///
+/// A base de cálculo é a parcela do valor do CT-e proporcional ao valor dos itens elegíveis.
+pub fn classificar_credito_frete(
+    info: &InfoCte,
+    itens: &[&InfoNfe],
+    consumidor_final: &HashSet<&str>,
+    cnpj_base: Option<&str>,
+) -> ClassificacaoCredito {
+    let Some(&papel) = TOMADOR_DO_SERVICO.get(&info.tomador_codigo) else {
+        return ClassificacaoCredito::new(
+            "Indeterminado",
+            None,
+            "Tomador do serviço não identificado".to_string(),
+        );
+    };
+
+    if let Some(cnpj_base) = cnpj_base
+        && !info.tomador_pertence_a(cnpj_base)
+    {
+        return ClassificacaoCredito::new(
+            "Não",
+            None,
+            format!("Tomador ({papel}) não é a empresa auditada: ônus do frete de terceiro"),
+        );
+    }
+
+    let operacao = match info.tomador_codigo {
+        Some(0 | 1) => OperacaoFrete::Venda,
+        Some(2 | 3) => OperacaoFrete::Aquisicao,
+        _ => {
+            return ClassificacaoCredito::new(
+                "Indeterminado",
+                None,
+                format!(
+                    "Tomador ({papel}): natureza do frete (venda ou aquisição) não determinada"
+                ),
+            );
+        }
+    };
+
+    if itens.is_empty() {
+        return ClassificacaoCredito::new(
+            "Indeterminado",
+            None,
+            format!("Tomador ({papel}): sem itens de NFes correlacionadas"),
+        );
+    }
+
+    let mut valor_total = 0.0;
+    let mut valor_elegivel = 0.0;
+    let mut elegiveis = 0;
+    let mut transferencias = 0;
+    let mut devolucoes = 0;
+    let mut uso_consumo = 0;
+    let mut cst_sem_credito = 0;
+    let mut outras = 0;
+
+    for item in itens {
+        let valor = item.v_prod.unwrap_or_default();
+        valor_total += valor;
+
+        let para_consumo = item
+            .nfe
+            .as_deref()
+            .is_some_and(|nfe| consumidor_final.contains(nfe));
+        let cst_tributado = is_cst_tributado(item);
+
+        match (NaturezaCfop::from_cfop(item.cfop), operacao) {
+            (NaturezaCfop::Transferencia, _) => transferencias += 1,
+            (NaturezaCfop::Devolucao, _) => devolucoes += 1,
+            (NaturezaCfop::Outras, _) => outras += 1,
+            (NaturezaCfop::Venda, OperacaoFrete::Aquisicao) if para_consumo => uso_consumo += 1,
+            (NaturezaCfop::Venda, OperacaoFrete::Aquisicao) if !cst_tributado => {
+                cst_sem_credito += 1
+            }
+            (NaturezaCfop::Venda, _) => {
+                elegiveis += 1;
+                valor_elegivel += valor;
+            }
+        }
+    }
+
+    let fundamento = match operacao {
+        OperacaoFrete::Venda => "frete na operação de venda (art. 3º, IX, Lei 10.833/2003)",
+        OperacaoFrete::Aquisicao => {
+            "frete na aquisição de bens para revenda ou insumos (art. 3º, I e II, Lei 10.833/2003)"
+        }
+    };
+
+    let mut motivos = vec![format!(
+        "Tomador ({papel}): {fundamento}; {elegiveis} de {} itens elegíveis",
+        itens.len()
+    )];
+    for (quantidade, motivo) in [
+        (transferencias, "de transferência"),
+        (devolucoes, "de devolução"),
+        (uso_consumo, "para uso e consumo (consumidor final)"),
+        (cst_sem_credito, "com CST sem incidência das contribuições"),
+        (outras, "com CFOP que não corresponde a venda"),
+    ] {
+        if quantidade > 0 {
+            motivos.push(format!("{quantidade} itens {motivo}"));
+        }
+    }
+    let fundamentacao = motivos.join("; ");
+
+    let base = match (info.valor_total, valor_total > 0.0) {
+        (Some(valor_cte), true) => Some((valor_cte * valor_elegivel / valor_total).round_float(2)),
+        _ => None,
+    };
+
+    match base {
+        Some(base) if base > 0.0 => ClassificacaoCredito::new("Sim", Some(base), fundamentacao),
+        _ => ClassificacaoCredito::new("Não", None, fundamentacao),
+    }
+}
+
+/// Linha da tabela "Créditos de Frete": totais mensais por data de emissão do CT-e.
+#[derive(Debug, Default, Serialize, Deserialize, Clone, Iterable)]
+pub struct InfoCreditoFrete {
+    /// Mês de emissão dos CT-es, no formato `AAAA-MM`.
+    #[serde(rename = "Ano e Mês de Emissão", default)]
+    pub ano_mes: String,
+
+    /// Quantidade de CT-es válidos emitidos no mês.
+    #[serde(rename = "Quantidade de CTes", default)]
+    pub quantidade_ctes: usize,
+
+    /// Quantidade de CT-es com crédito apurável.
+    #[serde(rename = "Quantidade de CTes com Crédito", default)]
+    pub quantidade_ctes_credito: usize,
+
+    /// Soma do valor da prestação (vTPrest) dos CT-es válidos.
+    #[serde(rename = "Valor Total de CTes", default)]
+    pub valor_total_ctes: f64,
+
+    /// Soma da base de cálculo do crédito.
+    #[serde(rename = "Valor da Base de Cálculo do Crédito", default)]
+    pub base_credito: f64,
+
+    /// Crédito de PIS/PASEP à alíquota de 1,65%.
+    #[serde(rename = "Valor do Crédito de PIS/PASEP", default)]
+    pub credito_pis: f64,
+
+    /// Crédito de COFINS à alíquota de 7,6%.
+    #[serde(rename = "Valor do Crédito de COFINS", default)]
+    pub credito_cofins: f64,
+}
+
+impl InfoExtension for InfoCreditoFrete {}
+
+/// Consolida os CT-es válidos por mês de emissão.
+pub fn get_creditos_por_mes(ctes: &[InfoCte]) -> Vec<InfoCreditoFrete> {
+    let mut meses: BTreeMap<NaiveDate, InfoCreditoFrete> = BTreeMap::new();
+
+    for info in ctes.iter().filter(|info| info.is_valid()) {
+        let Some(data) = info.data_emissao else {
+            continue;
+        };
+        let Some(mes) = data.with_day(1) else {
+            continue;
+        };
+
+        let linha = meses.entry(mes).or_insert_with(|| InfoCreditoFrete {
+            ano_mes: mes.format("%Y-%m").to_string(),
+            ..Default::default()
+        });
+
+        linha.quantidade_ctes += 1;
+        linha.valor_total_ctes += info.valor_total.unwrap_or_default();
+        if let Some(base) = info.base_credito_frete {
+            linha.quantidade_ctes_credito += 1;
+            linha.base_credito += base;
+        }
+    }
+
+    meses
+        .into_values()
+        .map(|mut linha| {
+            linha.valor_total_ctes = linha.valor_total_ctes.round_float(2);
+            linha.base_credito = linha.base_credito.round_float(2);
+            linha.credito_pis = (linha.base_credito * ALIQ_CREDITO_PIS / 100.0).round_float(2);
+            linha.credito_cofins =
+                (linha.base_credito * ALIQ_CREDITO_COFINS / 100.0).round_float(2);
+            linha
+        })
+        .collect()
+}
+
+//----------------------------------------------------------------------------//
+//                                   Tests                                    //
+//----------------------------------------------------------------------------//
+
+/// Run tests with:
+/// cargo test -- --show-output tests_credito
+#[cfg(test)]
+mod tests_credito {
+    use super::*;
+    use crate::core::fixtures;
+
+    fn cte(tomador_codigo: u8, valor_total: f64) -> InfoCte {
+        let mut info = fixtures::cte("cte1");
+        info.tomador_codigo = Some(tomador_codigo);
+        info.destinatario_cnpj = Some("12.345.678/0001-99".to_string());
+        info.remetente_cnpj = Some("98.765.432/0001-10".to_string());
+        info.valor_total = Some(valor_total);
+        info.data_emissao = NaiveDate::from_ymd_opt(2025, 1, 15);
+        info
+    }
+
+    fn item(nfe: &str, cfop: u16, cst: u8, v_prod: f64) -> InfoNfe {
+        let mut info = fixtures::item(nfe, 1, v_prod);
+        info.cfop = Some(cfop);
+        info.cst_pis = Some(cst);
+        info.cst_cofins = Some(cst);
+        info
+    }
+
+    #[test]
+    /// `cargo test -- --show-output credito_na_aquisicao`
+    fn credito_na_aquisicao() {
+        // Tomador destinatário (empresa auditada 12.345.678)
+        let mut info = cte(3, 100.0);
+        let revenda = item("nfe1", 6102, 1, 300.0);
+        let aliquota_zero = item("nfe1", 6102, 6, 100.0);
+        let transferencia = item("nfe1", 6152, 1, 100.0);
+        let mut cofins_isenta = item("nfe1", 6102, 1, 100.0);
+        cofins_isenta.cst_cofins = Some(7);
+        let itens = [&revenda, &aliquota_zero, &transferencia, &cofins_isenta];
+
+        let classificacao =
+            classificar_credito_frete(&info, &itens, &HashSet::new(), Some("12345678"));
+        println!("classificacao: {classificacao:#?}");
+        assert_eq!(classificacao.credito, Some("Sim".to_string()));
+        assert_eq!(classificacao.base, Some(50.0));
+        assert!(classificacao.fundamentacao.is_some_and(|f| {
+            f.contains("1 de 4 itens elegíveis")
+                && f.contains("2 itens com CST sem incidência das contribuições")
+        }));
+
+        // Aquisição por consumidor final: uso e consumo
+        let consumo = HashSet::from(["nfe1"]);
+        let classificacao = classificar_credito_frete(&info, &itens, &consumo, None);
+        assert_eq!(classificacao.credito, Some("Não".to_string()));
+        assert_eq!(classificacao.base, None);
+
+        // Tomador diferente da empresa auditada
+        let classificacao =
+            classificar_credito_frete(&info, &itens, &HashSet::new(), Some("11111111"));
+        assert_eq!(classificacao.credito, Some("Não".to_string()));
+
+        // Totais mensais
+        info.credito_frete = Some("Sim".to_string());
+        info.base_credito_frete = Some(60.0);
+        let creditos = get_creditos_por_mes(&[info, cte(3, 50.0)]);
+        assert_eq!(creditos.len(), 1);
+        assert_eq!(creditos[0].ano_mes, "2025-01");
+        assert_eq!(creditos[0].quantidade_ctes, 2);
+        assert_eq!(creditos[0].quantidade_ctes_credito, 1);
+        assert_eq!(creditos[0].valor_total_ctes, 150.0);
+        assert_eq!(creditos[0].credito_pis, 0.99);
+        assert_eq!(creditos[0].credito_cofins, 4.56);
+    }
+
+    #[test]
+    /// `cargo test -- --show-output credito_na_venda`
+    fn credito_na_venda() {
+        // Tomador remetente: frete na venda independe do CST dos itens
+        let info = cte(0, 80.0);
+        let venda = item("nfe2", 5102, 6, 500.0);
+        let classificacao = classificar_credito_frete(&info, &[&venda], &HashSet::new(), None);
+        assert_eq!(classificacao.credito, Some("Sim".to_string()));
+        assert_eq!(classificacao.base, Some(80.0));
+
+        // Sem tomador identificado
+        let mut info = cte(0, 80.0);
+        info.tomador_codigo = None;
+        let classificacao = classificar_credito_frete(&info, &[&venda], &HashSet::new(), None);
+        assert_eq!(classificacao.credito, Some("Indeterminado".to_string()));
+    }
+}
@@ -2,23 +2,35 @@
 //!
 //! Gerencia a consolidação global de notas fiscais, conhecimentos de transporte e e-Financeira.
 
-use claudiofsr_lib::HashSetExtension;
+use chrono::NaiveDate;
+use claudiofsr_lib::{HashSetExtension, RoundFloat, StrExtension};
 use itertools::Itertools;
 use rayon::prelude::*;
 use std::{
     collections::{BTreeMap, BTreeSet, HashMap, HashSet},
     fs::File,
     io::Write,
+    path::Path,
 };
 
 use crate::{
-    Arguments, Correlacoes, GraphExtension, Information, KeyDoc, UniqueIdentification,
-    XmlParserResult, get_ctes_grouped_by_payer, get_nfes_grouped_by_ncm_description,
-    get_total_value_ctes, get_total_value_nfes,
+    Arguments, Correlacoes, Direcao, GetID, GetKey, GraphExtension, InfoCadeia, InfoCartaCorrecao,
+    InfoCreditoFrete, InfoCteCte, InfoCteNcm, InfoCteNfe, InfoDocumentoPapel, InfoEvento,
+    InfoInconsistenciaEvento, InfoNfeNfe, InfoVinculoSugerido, Information, KeyDoc, Perspectiva,
+    RelacaoCte, TabelaExportada, TipoRelacaoCte, UniqueIdentification, XmlParserResult,
+    adicionar_cancelamentos_cte, adicionar_cancelamentos_nfe, adicionar_entregas,
+    adicionar_manifestacoes, adicionar_substituicoes_cte, aplicar_cartas_correcao,
+    classificar_credito_frete, cnpj_pertence_a, correlacionar_documentos_papel,
+    explicar_correlacao, get_cadeias, get_cartas_correcao, get_cnpj_base, get_componentes,
+    get_creditos_por_mes, get_ctes_ctes, get_ctes_grouped_by_payer, get_ctes_ncms, get_ctes_nfes,
+    get_historico_eventos, get_inconsistencias_eventos, get_nfes_grouped_by_ncm_description,
+    get_rateio_frete, get_referencias_nfes, get_total_value_ctes, get_total_value_nfes,
+    get_ultimos_eventos, get_vinculos_sugeridos, restringir_manifestacao,
     xml_structs::{
-        cancelamento_cte::InfoCteCancel, cancelamento_nfe::InfoNfeCancel, cte::InfoCte,
-        cte_evento::InfoCteEvento, efinanceira::InfoEFinanceira, nfe::InfoNfe,
-        nfe_evento::InfoNfeEvento,
+        cancelamento_cte::InfoCteCancel, cancelamento_nfe::InfoNfeCancel, cobranca::InfoDuplicata,
+        cte::InfoCte, cte_evento::InfoCteEvento, efinanceira::InfoEFinanceira, nfe::InfoNfe,
+        nfe_detalhamento::InfoVolume, nfe_documento::InfoNfeDocumento, nfe_evento::InfoNfeEvento,
+        pagamento::InfoPagamento,
     },
 };
 
@@ -31,6 +43,21 @@ pub trait KeysExtension {
 pub struct DocsFiscais {
     pub ctes: Vec<InfoCte>,
     pub nfes: Vec<InfoNfe>,
+    pub nfes_documentos: Vec<InfoNfeDocumento>,
+    pub pagamentos: Vec<InfoPagamento>,
+    pub duplicatas: Vec<InfoDuplicata>,
+    pub volumes: Vec<InfoVolume>,
+    pub creditos_frete: Vec<InfoCreditoFrete>,
+    pub ctes_nfes: Vec<InfoCteNfe>,
+    pub ctes_ctes: Vec<InfoCteCte>,
+    pub ctes_ncms: Vec<InfoCteNcm>,
+    pub nfes_nfes: Vec<InfoNfeNfe>,
+    pub cadeias: Vec<InfoCadeia>,
+    pub documentos_papel: Vec<InfoDocumentoPapel>,
+    pub vinculos_sugeridos: Vec<InfoVinculoSugerido>,
+    pub eventos: Vec<InfoEvento>,
+    pub cartas_correcao: Vec<InfoCartaCorrecao>,
+    pub inconsistencias_eventos: Vec<InfoInconsistenciaEvento>,
     pub eventos_cte: Vec<InfoCteEvento>,
     pub eventos_nfe: Vec<InfoNfeEvento>,
     pub cancel_cte: Vec<InfoCteCancel>,
@@ -45,16 +72,67 @@ impl DocsFiscais {
 
     /// Retorna a quantidade de tipos distintos de documentos populados no lote.
     pub fn total(&self) -> usize {
-        usize::from(!self.ctes.is_empty())
-            + usize::from(!self.nfes.is_empty())
-            + usize::from(!self.efinanceiras.is_empty())
+        self.tabelas()
+            .iter()
+            .filter(|tabela| !tabela.linhas.is_empty())
+            .count()
+    }
+
+    /// Tabelas exportadas, na ordem dos arquivos gravados e das abas da planilha única.
+    pub fn tabelas(&self) -> Vec<TabelaExportada<'_>> {
+        vec![
+            TabelaExportada::new("ctes", "CTes", &self.ctes),
+            TabelaExportada::new("nfes", "NFes", &self.nfes),
+            TabelaExportada::new("nfes-documentos", "NFes Documentos", &self.nfes_documentos),
+            TabelaExportada::new("nfes-pagamentos", "Pagamentos", &self.pagamentos),
+            TabelaExportada::new("nfes-duplicatas", "Duplicatas", &self.duplicatas),
+            TabelaExportada::new("nfes-volumes", "Volumes", &self.volumes),
+            TabelaExportada::new(
+                "ctes-creditos_frete",
+                "Créditos de Frete",
+                &self.creditos_frete,
+            ),
+            TabelaExportada::new("ctes-nfes", "CTes x NFes", &self.ctes_nfes),
+            TabelaExportada::new("ctes-ctes", "CTes x CTes", &self.ctes_ctes),
+            TabelaExportada::new("ctes-ncms", "CTes x NCMs", &self.ctes_ncms),
+            TabelaExportada::new("nfes-nfes", "NFes x NFes", &self.nfes_nfes),
+            TabelaExportada::new("cadeias", "Cadeias", &self.cadeias),
+            TabelaExportada::new(
+                "ctes-documentos_papel",
+                "Documentos em Papel",
+                &self.documentos_papel,
+            ),
+            TabelaExportada::new(
+                "ctes-vinculos_sugeridos",
+                "Vínculos Sugeridos",
+                &self.vinculos_sugeridos,
+            ),
+            TabelaExportada::new("eventos", "Eventos", &self.eventos),
+            TabelaExportada::new(
+                "cartas_correcao",
+                "Cartas de Correção",
+                &self.cartas_correcao,
+            ),
+            TabelaExportada::new(
+                "eventos-inconsistencias",
+                "Eventos — inconsistências",
+                &self.inconsistencias_eventos,
+            ),
+            TabelaExportada::new("efinanceiras", "eFinanceiras", &self.efinanceiras),
+        ]
     }
 
     /// Adiciona a variante de informação ao repositório correspondente.
     pub fn add_information(&mut self, info: Information) {
         match info {
             Information::Cte(c) => self.ctes.push(*c),
-            Information::Nfe(n) => self.nfes.extend(n),
+            Information::Nfe(n) => {
+                self.nfes.extend(n.itens);
+                self.nfes_documentos.push(n.documento);
+                self.pagamentos.extend(n.pagamentos);
+                self.duplicatas.extend(n.duplicatas);
+                self.volumes.extend(n.volumes);
+            }
             Information::EventoCte(e) => self.eventos_cte.push(*e),
             Information::EventoNfe(e) => self.eventos_nfe.push(*e),
             Information::CancelamentoCte(c) => self.cancel_cte.push(*c),
@@ -68,6 +146,10 @@ impl DocsFiscais {
     pub fn unique(&mut self) {
         rayon::scope(|s| {
             s.spawn(|_| self.nfes = self.nfes.get_unique_id());
+            s.spawn(|_| self.nfes_documentos = self.nfes_documentos.get_unique_id());
+            s.spawn(|_| self.pagamentos = self.pagamentos.get_unique_id());
+            s.spawn(|_| self.duplicatas = self.duplicatas.get_unique_id());
+            s.spawn(|_| self.volumes = self.volumes.get_unique_id());
             s.spawn(|_| self.ctes = self.ctes.get_unique_id());
         });
 
@@ -109,6 +191,45 @@ impl DocsFiscais {
                 });
             },
         );
+
+        // Uma linha por NFe: mesma ordem da tabela de itens.
+        self.nfes_documentos.par_sort_by_key(|documento| {
+            (
+                documento.emitente_cnpj.clone(),
+                documento.emitente_cpf.clone(),
+                documento.data_emissao,
+                documento.nfe.clone(),
+            )
+        });
+
+        // Pagamentos, duplicatas e volumes seguem a NFe de origem e, dentro dela, a ordem do XML.
+        self.pagamentos.par_sort_by_key(|pagamento| {
+            (
+                pagamento.emitente_cnpj.clone(),
+                pagamento.emitente_cpf.clone(),
+                pagamento.data_emissao,
+                pagamento.nfe.clone(),
+                pagamento.n_pag,
+            )
+        });
+        self.duplicatas.par_sort_by_key(|duplicata| {
+            (
+                duplicata.emitente_cnpj.clone(),
+                duplicata.emitente_cpf.clone(),
+                duplicata.data_emissao,
+                duplicata.nfe.clone(),
+                duplicata.ordem,
+            )
+        });
+        self.volumes.par_sort_by_key(|volume| {
+            (
+                volume.emitente_cnpj.clone(),
+                volume.emitente_cpf.clone(),
+                volume.data_emissao,
+                volume.nfe.clone(),
+                volume.ordem,
+            )
+        });
     }
 
     /// Relacionar KeyDoc com InfoCte
@@ -204,24 +325,41 @@ impl DocsFiscais {
             .collect()
     }
 
+    /// Relações tipadas entre CTes declaradas nos XMLs e nos eventos, sem duplicidades
+    fn get_relacoes_ctes(&self) -> Vec<RelacaoCte> {
+        let mut relacoes: Vec<RelacaoCte> = self
+            .ctes
+            .par_iter()
+            .flat_map_iter(|info| info.relacoes.iter().cloned())
+            .collect();
+        relacoes.par_sort();
+        relacoes.dedup_by(|a, b| a.mesma_relacao(b));
+        relacoes
+    }
+
     /// CTe relacionado a CTes
     ///
     /// HashMap<chave_cte, HashSet<chave_cte>
+    ///
+    /// Apenas as relações dos tipos em `propagar` são consideradas.
     fn groupby_cte_ctes(
         &self,
         cte_info: &BTreeMap<KeyDoc, Vec<InfoCte>>,
+        propagar: &[TipoRelacaoCte],
     ) -> HashMap<String, HashSet<String>> {
         self.ctes
             .par_iter()
+            .filter(|info| !info.is_canceled()) // CTe cancelado não liga CTes entre si
             .filter_map(|info| {
                 if let Some(cte_a) = &info.cte {
                     let ctes: HashSet<String> = info
-                        .get_correlated_ctes()
-                        .into_iter()
-                        .filter(|cte_complementar| {
-                            let chave_valida = KeyDoc::new(cte_complementar, true);
-                            cte_complementar != cte_a && cte_info.contains_key(&chave_valida)
-                        })
+                        .relacoes
+                        .iter()
+                        .filter(|relacao| propagar.contains(&relacao.tipo))
+                        .filter(|relacao| relacao.cte_disponivel(relacao.referenciado(), cte_info))
+                        .map(|relacao| relacao.referenciado())
+                        .filter(|&cte_relacionado| cte_relacionado != cte_a)
+                        .map(String::from)
                         .collect();
 
                     Some((cte_a.clone(), ctes))
@@ -265,7 +403,14 @@ impl DocsFiscais {
     /// CTe contém 1 item
     ///
     /// NFe contém vários itens
-    pub fn get_correlations(&mut self, arguments: &Arguments) {
+    ///
+    /// Retorna as correlações para consultas posteriores (ex.: [`explicar_correlacao`]).
+    pub fn get_correlations(&mut self, arguments: &Arguments) -> Correlacoes {
+        // 0. Documentos em papel: as NFes encontradas somam-se às chaves declaradas
+        self.add_documentos_papel();
+        // CTes substituídos ou anulados deixam de ser válidos antes do agrupamento
+        adicionar_substituicoes_cte(&mut self.ctes);
+
         // 1. Primeira etapa: Agrupamento inicial
         let (cte_info, nfe_info) =
             rayon::join(|| self.groupby_cte_info(), || self.groupby_nfe_info());
@@ -277,12 +422,16 @@ impl DocsFiscais {
 
         let mut cte_ctes: HashMap<String, HashSet<String>> = HashMap::new();
         let mut cte_nfes: HashMap<String, HashSet<String>> = HashMap::new();
+        let mut relacoes_ctes: Vec<RelacaoCte> = Vec::new();
+
+        let propagar = &arguments.propagar_nfes;
 
         rayon::scope(|s| {
             s.spawn(|_| ctes_nao_encontrados = self.get_ctes_nao_encontrados(&cte_info));
             s.spawn(|_| nfes_nao_encontrados = self.get_nfes_nao_encontrados(&nfe_info));
-            s.spawn(|_| cte_ctes = self.groupby_cte_ctes(&cte_info));
+            s.spawn(|_| cte_ctes = self.groupby_cte_ctes(&cte_info, propagar));
             s.spawn(|_| cte_nfes = self.groupby_cte_nfes(&nfe_info));
+            s.spawn(|_| relacoes_ctes = self.get_relacoes_ctes());
         });
 
         // 3. Processamento Sequencial de Lógica de Negócio
@@ -295,6 +444,21 @@ impl DocsFiscais {
         cte_ctes.expand_ctes(false);
         cte_nfes.expand_nfes(&cte_ctes);
 
+        // Sem propagação, o CTe substituto assume as NFes do substituído
+        if !propagar.contains(&TipoRelacaoCte::Substituicao) {
+            let substituicoes: Vec<(&str, &str)> = relacoes_ctes
+                .iter()
+                .filter(|relacao| relacao.tipo == TipoRelacaoCte::Substituicao)
+                .filter(|relacao| {
+                    [&relacao.original, &relacao.derivado]
+                        .into_iter()
+                        .all(|cte| relacao.cte_disponivel(cte, &cte_info))
+                })
+                .map(|relacao| (relacao.original.as_str(), relacao.derivado.as_str()))
+                .collect();
+            cte_nfes.replace_nfes(&substituicoes, &cte_ctes);
+        }
+
         // Filtros e Inversão de Mapas
         let cte_nfes = cte_nfes.filtrar_nfes_validos(&nfe_info);
         let nfe_ctes = cte_nfes.get_nfe_ctes().filtrar_ctes_validos(&cte_info);
@@ -304,6 +468,7 @@ impl DocsFiscais {
             nfe_info,
             cte_nfes,
             nfe_ctes,
+            relacoes_ctes,
         };
 
         if arguments.exibir_correlacoes {
@@ -311,15 +476,132 @@ impl DocsFiscais {
             print_nfe_ctes(&correlacoes, arguments);
         }
 
+        if let Some([origem, destino]) = arguments.explicar.as_deref() {
+            print_explicacao(&correlacoes, propagar, origem, destino);
+        }
+
         self.add_info_nfes_to_cte(&correlacoes, arguments);
+        self.add_conferencia_de_carga(&correlacoes, arguments);
         self.add_info_ctes_to_nfe(&correlacoes, arguments);
+        self.add_rateio_de_frete(&correlacoes, arguments);
+        self.add_credito_de_frete(&correlacoes, arguments);
+        self.add_info_ctes_to_nfe_documento(&correlacoes, arguments);
+        self.add_manifestacoes();
+        self.add_referencias_nfes(&correlacoes);
+        self.add_relacionamentos(&correlacoes, arguments);
+        self.add_cadeias(arguments);
+        self.add_vinculos_sugeridos(&correlacoes, arguments);
+
+        correlacoes
+    }
+
+    /// Detalhar os cancelamentos de NFes e CTes: data, protocolo, justificativa e origem
+    ///
+    /// Assinala cancelamentos fora do prazo e CTes cancelados após MDF-e ou CTe substituto.
+    pub fn add_cancelamentos(&mut self) {
+        rayon::join(
+            || adicionar_cancelamentos_cte(&mut self.ctes, &self.eventos_cte, &self.cancel_cte),
+            || {
+                adicionar_cancelamentos_nfe(
+                    &mut self.nfes_documentos,
+                    &self.eventos_nfe,
+                    &self.cancel_nfe,
+                )
+            },
+        );
+    }
+
+    /// Reunir todos os eventos de NFes e CTes na tabela "Eventos"
+    ///
+    /// Cada NFe e CTe recebe o resumo do seu evento mais recente na coluna "Último Evento".
+    pub fn add_historico_eventos(&mut self) {
+        self.eventos = get_historico_eventos(&self.eventos_nfe, &self.eventos_cte);
+        let ultimos = get_ultimos_eventos(&self.eventos);
+
+        rayon::join(
+            || {
+                self.ctes.par_iter_mut().for_each(|info| {
+                    info.ultimo_evento = info
+                        .cte
+                        .as_deref()
+                        .and_then(|cte| ultimos.get(cte).cloned());
+                })
+            },
+            || {
+                self.nfes_documentos.par_iter_mut().for_each(|documento| {
+                    documento.ultimo_evento = documento
+                        .nfe
+                        .as_deref()
+                        .and_then(|nfe| ultimos.get(nfe).cloned());
+                })
+            },
+        );
+    }
+
+    /// Validar os eventos de NFes e CTes na tabela "Eventos — inconsistências"
+    ///
+    /// Eventos órfãos, fora de sequência, anteriores à emissão ou não homologados.
+    /// Requer a tabela "Eventos" (ver [`DocsFiscais::add_historico_eventos`]).
+    pub fn add_inconsistencias_eventos(&mut self) {
+        let emissoes: HashMap<&str, Option<NaiveDate>> = self
+            .ctes
+            .iter()
+            .filter_map(|info| info.cte.as_deref().map(|cte| (cte, info.data_emissao)))
+            .chain(self.nfes_documentos.iter().filter_map(|documento| {
+                documento
+                    .nfe
+                    .as_deref()
+                    .map(|nfe| (nfe, documento.data_emissao))
+            }))
+            .collect();
+
+        self.inconsistencias_eventos = get_inconsistencias_eventos(&self.eventos, &emissoes);
+    }
+
+    /// Reunir as Cartas de Correção de NFes e CTes na tabela "Cartas de Correção"
+    ///
+    /// Com `--aplicar-cce`, a CC-e vigente de cada CTe sobrescreve as colunas equivalentes.
+    pub fn add_cartas_correcao(&mut self, arguments: &Arguments) {
+        self.cartas_correcao = get_cartas_correcao(&self.eventos_nfe, &self.eventos_cte);
+        if arguments.aplicar_cce {
+            aplicar_cartas_correcao(&mut self.ctes, &mut self.cartas_correcao);
+        }
+    }
+
+    /// Preencher as colunas de entrega de CTes e NFes (comprovante e insucesso de entrega)
+    pub fn add_entregas(&mut self) {
+        adicionar_entregas(
+            &mut self.ctes,
+            &mut self.nfes_documentos,
+            &self.eventos_nfe,
+            &self.eventos_cte,
+        );
+    }
+
+    /// Preencher o estado da Manifestação do Destinatário de cada NFe
+    ///
+    /// Requer a coluna de CTes das NFes já preenchida. A restrição às NFes destinadas à
+    /// empresa auditada é feita em [`DocsFiscais::aplicar_perspectiva`].
+    pub fn add_manifestacoes(&mut self) {
+        adicionar_manifestacoes(&mut self.nfes_documentos, &self.eventos_nfe);
+    }
+
+    /// Correlacionar Notas Fiscais em papel e outros documentos (`infNF`/`infOutros`) às NFes
+    ///
+    /// As referências sem NFe correspondente única formam a tabela "Documentos em Papel".
+    pub fn add_documentos_papel(&mut self) {
+        self.documentos_papel =
+            correlacionar_documentos_papel(&mut self.ctes, &self.nfes_documentos);
     }
 
     /// Adicionar informações de NFes em CTe
+    ///
+    /// CTes substituídos ou anulados mantêm as colunas de NFes, sinalizados pela coluna
+    /// "Substituído ou Anulado".
     pub fn add_info_nfes_to_cte(&mut self, correlacoes: &Correlacoes, arguments: &Arguments) {
         self.ctes
             .par_iter_mut() // rayon parallel iterator
-            .filter(|info| info.is_valid()) // remover cte cancelado
+            .filter(|info| !info.is_canceled()) // remover cte cancelado
             .for_each(|info| {
                 if let Some(cte) = &info.cte
                     && let Some(nfes) = correlacoes.cte_nfes.get(cte)
@@ -332,6 +614,50 @@ impl DocsFiscais {
             });
     }
 
+    /// Confrontar peso e valor da carga de cada CTe com as NFes correlacionadas
+    ///
+    /// Os pesos (pesoB/pesoL) e o valor (vNF) são obtidos da tabela de documentos (1 linha por NFe).
+    /// Cada soma só é apurada se todas as NFes correlacionadas informam o campo; caso contrário,
+    /// a respectiva conferência é inconclusiva e não é feita.
+    pub fn add_conferencia_de_carga(&mut self, correlacoes: &Correlacoes, arguments: &Arguments) {
+        let documentos: HashMap<&str, &InfoNfeDocumento> = self
+            .nfes_documentos
+            .iter()
+            .filter(|documento| documento.is_valid())
+            .filter_map(|documento| documento.nfe.as_deref().map(|nfe| (nfe, documento)))
+            .collect();
+
+        self.ctes
+            .par_iter_mut() // rayon parallel iterator
+            .filter(|info| info.is_valid()) // remover cte cancelado
+            .for_each(|info| {
+                if let Some(cte) = &info.cte
+                    && let Some(nfes) = correlacoes.cte_nfes.get(cte)
+                {
+                    let docs: Vec<&InfoNfeDocumento> = nfes
+                        .iter()
+                        .filter_map(|nfe| documentos.get(nfe.as_str()).copied())
+                        .collect();
+
+                    // Com dados parciais (NFe sem o campo), a soma ficaria subestimada e a
+                    // conferência acusaria uma falsa divergência: o total é omitido.
+                    let completas = !docs.is_empty() && docs.len() == nfes.len();
+                    let somar = |campo: fn(&InfoNfeDocumento) -> Option<f64>| {
+                        completas
+                            .then(|| docs.iter().map(|&doc| campo(doc)).sum::<Option<f64>>())
+                            .flatten()
+                    };
+
+                    info.peso_bruto_nfes = somar(|doc| doc.peso_bruto).map(|p| p.round_float(3));
+                    info.peso_liquido_nfes =
+                        somar(|doc| doc.peso_liquido).map(|p| p.round_float(3));
+                    info.valor_nfes = somar(|doc| doc.valor_total_nfe).map(|v| v.round_float(2));
+
+                    info.conferir_carga(arguments.tolerancia);
+                }
+            });
+    }
+
     /// Adicionar informações de CTes em NFe
     pub fn add_info_ctes_to_nfe(&mut self, correlacoes: &Correlacoes, arguments: &Arguments) {
         self.nfes
@@ -349,27 +675,409 @@ impl DocsFiscais {
             });
     }
 
+    /// Ratear o valor dos CTes entre os itens das NFes correlacionadas
+    ///
+    /// Ao contrário de `valor_total_ctes`, a soma de `valor_frete_rateado` não repete o frete.
+    pub fn add_rateio_de_frete(&mut self, correlacoes: &Correlacoes, arguments: &Arguments) {
+        let pesos: HashMap<&str, f64> = self
+            .nfes_documentos
+            .iter()
+            .filter(|documento| documento.is_valid())
+            .filter_map(|documento| {
+                let nfe = documento.nfe.as_deref()?;
+                let peso = documento.peso_bruto.or(documento.peso_liquido)?;
+                Some((nfe, peso))
+            })
+            .collect();
+
+        let rateio = get_rateio_frete(correlacoes, &pesos, arguments.rateio);
+
+        self.nfes
+            .par_iter_mut() // rayon parallel iterator
+            .filter(|info| info.is_valid()) // remover nfe cancelado
+            .for_each(|info| {
+                if let Some(id) = info.get_id() {
+                    info.valor_frete_rateado = rateio.get(&id).copied();
+                }
+            });
+    }
+
+    /// Classificar o crédito de PIS/COFINS sobre o frete de cada CTe e consolidar os totais mensais
+    pub fn add_credito_de_frete(&mut self, correlacoes: &Correlacoes, arguments: &Arguments) {
+        let consumidor_final: HashSet<&str> = self
+            .nfes_documentos
+            .iter()
+            .filter(|documento| documento.is_valid() && documento.is_consumidor_final())
+            .filter_map(|documento| documento.nfe.as_deref())
+            .collect();
+
+        let cnpj_base = arguments.cnpj_base.as_deref();
+
+        self.ctes
+            .par_iter_mut() // rayon parallel iterator
+            .filter(|info| info.is_valid()) // remover cte cancelado
+            .for_each(|info| {
+                let itens: Vec<&InfoNfe> = info
+                    .cte
+                    .as_ref()
+                    .and_then(|cte| correlacoes.cte_nfes.get(cte))
+                    .into_iter()
+                    .flatten()
+                    .filter_map(|nfe| correlacoes.nfe_info.get(&KeyDoc::new(nfe, true)))
+                    .flatten()
+                    .collect();
+
+                let classificacao =
+                    classificar_credito_frete(info, &itens, &consumidor_final, cnpj_base);
+                info.credito_frete = classificacao.credito;
+                info.base_credito_frete = classificacao.base;
+                info.fundamentacao_credito_frete = classificacao.fundamentacao;
+            });
+
+        self.creditos_frete = get_creditos_por_mes(&self.ctes);
+    }
+
+    /// Adicionar informações de CTes na linha de cabeçalho de cada NFe
+    pub fn add_info_ctes_to_nfe_documento(
+        &mut self,
+        correlacoes: &Correlacoes,
+        arguments: &Arguments,
+    ) {
+        self.nfes_documentos
+            .par_iter_mut() // rayon parallel iterator
+            .filter(|documento| documento.is_valid()) // remover nfe cancelado
+            .for_each(|documento| {
+                if let Some(nfe) = &documento.nfe
+                    && let Some(ctes) = correlacoes.nfe_ctes.get(nfe)
+                {
+                    documento.ctes = ctes.to_vec_sorted();
+                    documento.tomadores =
+                        get_ctes_grouped_by_payer(ctes, &correlacoes.cte_info, arguments);
+                    documento.valor_total_ctes = get_total_value_ctes(ctes, &correlacoes.cte_info);
+                }
+            });
+    }
+
+    /// Inverter as referências `NFref` entre NFes (devoluções, complementares e ajustes)
+    ///
+    /// Requer as colunas de CTes já preenchidas para herdar os CTes da NFe original.
+    pub fn add_referencias_nfes(&mut self, correlacoes: &Correlacoes) {
+        self.nfes_nfes = get_referencias_nfes(&mut self.nfes_documentos, &correlacoes.nfe_ctes);
+    }
+
+    /// Gerar as tabelas normalizadas CTe x NFe, CTe x CTe e CTe x NCM
+    ///
+    /// Uma linha por relacionamento, em vez das listas `[chave1, chave2, ...]` das tabelas principais.
+    pub fn add_relacionamentos(&mut self, correlacoes: &Correlacoes, arguments: &Arguments) {
+        let ctes = &self.ctes;
+        let (ctes_nfes, (ctes_ctes, ctes_ncms)) = rayon::join(
+            || get_ctes_nfes(ctes, correlacoes),
+            || {
+                rayon::join(
+                    || get_ctes_ctes(correlacoes, &arguments.propagar_nfes),
+                    || get_ctes_ncms(ctes, correlacoes),
+                )
+            },
+        );
+
+        self.ctes_nfes = ctes_nfes;
+        self.ctes_ctes = ctes_ctes;
+        self.ctes_ncms = ctes_ncms;
+    }
+
+    /// Identificar as cadeias de frete (componentes conexas de CTes e NFes)
+    ///
+    /// Cada linha de CTe, NFe e documento de NFe recebe o identificador da sua cadeia.
+    pub fn add_cadeias(&mut self, arguments: &Arguments) {
+        let componentes = get_componentes(&self.ctes, &arguments.propagar_nfes);
+
+        rayon::scope(|s| {
+            s.spawn(|_| {
+                self.ctes.par_iter_mut().for_each(|info| {
+                    info.cadeia = info.cte.as_ref().and_then(|c| componentes.get(c).cloned());
+                })
+            });
+            s.spawn(|_| {
+                self.nfes.par_iter_mut().for_each(|info| {
+                    info.cadeia = info.nfe.as_ref().and_then(|n| componentes.get(n).cloned());
+                })
+            });
+            s.spawn(|_| {
+                self.nfes_documentos.par_iter_mut().for_each(|documento| {
+                    documento.cadeia = documento
+                        .nfe
+                        .as_ref()
+                        .and_then(|n| componentes.get(n).cloned());
+                })
+            });
+        });
+
+        self.cadeias = get_cadeias(&componentes, &self.ctes, &self.nfes_documentos);
+    }
+
+    /// Sugerir NFes para CTes sem NFes correlacionadas (`--sugerir-vinculos`)
+    ///
+    /// As sugestões ficam na tabela "Vínculos Sugeridos" e não alteram `cte_nfes`.
+    pub fn add_vinculos_sugeridos(&mut self, correlacoes: &Correlacoes, arguments: &Arguments) {
+        if arguments.sugerir_vinculos {
+            self.vinculos_sugeridos = get_vinculos_sugeridos(
+                &self.ctes,
+                &self.nfes_documentos,
+                correlacoes,
+                arguments.janela_dias,
+                arguments.confianca_minima,
+            );
+        }
+    }
+
+    /// Classificar NFes e CTes em relação à empresa auditada (`--cnpj-base`)
+    ///
+    /// Pagamentos, duplicatas e volumes seguem a direção da NFe de origem (ver [`DocsFiscais::filtrar`]).
+    /// A Manifestação do Destinatário é mantida apenas nas NFes destinadas à empresa.
+    pub fn aplicar_perspectiva(&mut self, cnpj_base: &str) {
+        rayon::scope(|s| {
+            s.spawn(|_| {
+                self.nfes
+                    .par_iter_mut()
+                    .for_each(|info| info.aplicar_perspectiva(cnpj_base))
+            });
+            s.spawn(|_| {
+                self.nfes_documentos.par_iter_mut().for_each(|documento| {
+                    documento.aplicar_perspectiva(cnpj_base);
+                    restringir_manifestacao(documento, cnpj_base);
+                })
+            });
+            s.spawn(|_| {
+                self.ctes
+                    .par_iter_mut()
+                    .for_each(|info| info.aplicar_perspectiva(cnpj_base))
+            });
+        });
+    }
+
+    /// Retorna um novo repositório contendo apenas as NFes e os CTes das chaves informadas.
+    ///
+    /// Os totais mensais de crédito sobre fretes são recalculados sobre os CTes retidos e
+    /// as tabelas de relacionamentos seguem os CTes retidos.
+    /// As tabelas "Eventos", "Cartas de Correção" e "Eventos — inconsistências" são filtradas
+    /// pelas chaves retidas; os eventos e cancelamentos lidos dos XMLs (`eventos_nfe`,
+    /// `eventos_cte`, `cancel_nfe`, `cancel_cte`) e as e-Financeiras não são copiados.
+    pub fn filtrar(&self, nfes: &HashSet<&str>, ctes: &HashSet<&str>) -> DocsFiscais {
+        fn reter<T>(linhas: &[T], chaves: &HashSet<&str>) -> Vec<T>
+        where
+            T: GetKey + Clone + Send + Sync,
+        {
+            linhas
+                .par_iter()
+                .filter(|linha| {
+                    linha
+                        .get_chave()
+                        .is_some_and(|chave| chaves.contains(chave.as_str()))
+                })
+                .cloned()
+                .collect()
+        }
+
+        let ctes_retidos = reter(&self.ctes, ctes);
+        let cadeias: HashSet<&str> = ctes_retidos
+            .iter()
+            .filter_map(|info| info.cadeia.as_deref())
+            .collect();
+        let cadeias_retidas = reter(&self.cadeias, &cadeias);
+
+        DocsFiscais {
+            creditos_frete: get_creditos_por_mes(&ctes_retidos),
+            ctes: ctes_retidos,
+            nfes: reter(&self.nfes, nfes),
+            nfes_documentos: reter(&self.nfes_documentos, nfes),
+            pagamentos: reter(&self.pagamentos, nfes),
+            duplicatas: reter(&self.duplicatas, nfes),
+            volumes: reter(&self.volumes, nfes),
+            ctes_nfes: reter(&self.ctes_nfes, ctes),
+            ctes_ctes: self
+                .ctes_ctes
+                .iter()
+                .filter(|relacao| relacao.envolve(ctes))
+                .cloned()
+                .collect(),
+            ctes_ncms: reter(&self.ctes_ncms, ctes),
+            nfes_nfes: self
+                .nfes_nfes
+                .iter()
+                .filter(|relacao| relacao.envolve(nfes))
+                .cloned()
+                .collect(),
+            cadeias: cadeias_retidas,
+            documentos_papel: reter(&self.documentos_papel, ctes),
+            vinculos_sugeridos: reter(&self.vinculos_sugeridos, ctes),
+            eventos: self
+                .eventos
+                .iter()
+                .filter(|evento| {
+                    evento
+                        .chave
+                        .as_deref()
+                        .is_some_and(|chave| nfes.contains(chave) || ctes.contains(chave))
+                })
+                .cloned()
+                .collect(),
+            cartas_correcao: self
+                .cartas_correcao
+                .iter()
+                .filter(|carta| {
+                    carta
+                        .chave
+                        .as_deref()
+                        .is_some_and(|chave| nfes.contains(chave) || ctes.contains(chave))
+                })
+                .cloned()
+                .collect(),
+            inconsistencias_eventos: self
+                .inconsistencias_eventos
+                .iter()
+                .filter(|linha| {
+                    linha
+                        .chave
+                        .as_deref()
+                        .is_some_and(|chave| nfes.contains(chave) || ctes.contains(chave))
+                })
+                .cloned()
+                .collect(),
+            ..Default::default()
+        }
+    }
+
+    /// Separa NFes e CTes em Entradas, Saídas e Não Relacionados à empresa auditada
+    ///
+    /// Requer [`DocsFiscais::aplicar_perspectiva`] previamente executado.
+    pub fn separar_por_direcao(&self) -> Vec<(Direcao, DocsFiscais)> {
+        Direcao::ALL
+            .into_iter()
+            .map(|direcao| {
+                let nfes: HashSet<&str> = self
+                    .nfes_documentos
+                    .iter()
+                    .filter(|documento| documento.get_direcao() == Some(direcao))
+                    .filter_map(|documento| documento.nfe.as_deref())
+                    .collect();
+
+                let ctes: HashSet<&str> = self
+                    .ctes
+                    .iter()
+                    .filter(|info| info.get_direcao() == Some(direcao))
+                    .filter_map(|info| info.cte.as_deref())
+                    .collect();
+
+                (direcao, self.filtrar(&nfes, &ctes))
+            })
+            .collect()
+    }
+
+    /// Descobrir as empresas (CNPJ base) presentes no lote
+    ///
+    /// Considera emitentes e destinatários das NFes e tomadores dos CTes. São retornadas,
+    /// em ordem crescente, apenas as empresas que participam de ao menos `min_documentos` documentos.
+    pub fn get_cnpj_bases(&self, min_documentos: usize) -> Vec<String> {
+        let bases_nfes = self.nfes_documentos.iter().map(|documento| {
+            [
+                get_cnpj_base(&documento.emitente_cnpj),
+                get_cnpj_base(&documento.destinatario_cnpj),
+            ]
+            .into_iter()
+            .flatten()
+            .collect::<BTreeSet<String>>()
+        });
+
+        let bases_ctes = self.ctes.iter().map(|info| {
+            info.get_cnpj_cpf_base_do_tomador()
+                .map(|base| base.remove_non_digits())
+                .filter(|base| base.len() == 8) // CPF não possui CNPJ base
+                .into_iter()
+                .collect::<BTreeSet<String>>()
+        });
+
+        let mut contagem: BTreeMap<String, usize> = BTreeMap::new();
+        for base in bases_nfes.chain(bases_ctes).flatten() {
+            *contagem.entry(base).or_default() += 1;
+        }
+
+        contagem
+            .into_iter()
+            .filter(|(_, quantidade)| *quantidade >= min_documentos.max(1))
+            .map(|(base, _)| base)
+            .collect()
+    }
+
+    /// Retorna os documentos em que a empresa participa, classificados do seu ponto de vista
+    ///
+    /// As correlações são resolvidas previamente sobre todo o lote, de modo que as cadeias
+    /// de CTes que atravessam empresas permanecem completas no subconjunto.
+    ///
+    /// Os totais mensais de crédito sobre fretes consideram apenas os CTes tomados pela empresa
+    /// e as e-Financeiras retidas são aquelas em que a empresa é a titular declarada.
+    /// A Manifestação do Destinatário é recalculada e mantida nas NFes destinadas à empresa.
+    pub fn filtrar_empresa(&self, cnpj_base: &str) -> DocsFiscais {
+        let nfes: HashSet<&str> = self
+            .nfes_documentos
+            .iter()
+            .filter(|documento| documento.participa(cnpj_base))
+            .filter_map(|documento| documento.nfe.as_deref())
+            .collect();
+
+        let ctes: HashSet<&str> = self
+            .ctes
+            .iter()
+            .filter(|info| info.participa(cnpj_base))
+            .filter_map(|info| info.cte.as_deref())
+            .collect();
+
+        let mut docs = self.filtrar(&nfes, &ctes);
+        // O lote pode já estar restrito a outra empresa (--cnpj-base)
+        adicionar_manifestacoes(&mut docs.nfes_documentos, &self.eventos_nfe);
+        docs.aplicar_perspectiva(cnpj_base);
+
+        let ctes_tomados: Vec<InfoCte> = docs
+            .ctes
+            .iter()
+            .filter(|info| info.tomador_pertence_a(cnpj_base))
+            .cloned()
+            .collect();
+        docs.creditos_frete = get_creditos_por_mes(&ctes_tomados);
+
+        docs.efinanceiras = self
+            .efinanceiras
+            .iter()
+            .filter(|efin| cnpj_pertence_a(&efin.ni_do_declarado, cnpj_base))
+            .cloned()
+            .collect();
+
+        docs
+    }
+
     /// Salva as chaves de CT-e apuradas em arquivos particionados de texto.
-    pub fn print_ctes(&self, filename: &str, size: usize) -> XmlParserResult<()> {
+    pub fn print_ctes(&self, filename: impl AsRef<Path>, size: usize) -> XmlParserResult<()> {
         let chaves = self.ctes.get_chaves();
         print_chaves(&chaves, filename, size)
     }
 
     /// Salva as chaves de NF-e apuradas em arquivos particionados de texto.
-    pub fn print_nfes(&self, filename: &str, size: usize) -> XmlParserResult<()> {
+    pub fn print_nfes(&self, filename: impl AsRef<Path>, size: usize) -> XmlParserResult<()> {
         let chaves = self.nfes.get_chaves();
         print_chaves(&chaves, filename, size)
     }
 }
 
 /// Salva um conjunto de chaves únicas ordenadas em blocos de até `size` linhas.
+///
+/// `filename` pode incluir o diretório: `saida/ctes` gera `saida/ctes-00001.txt`, ...
 pub fn print_chaves(
     all_keys: &BTreeSet<String>,
-    filename: &str,
+    filename: impl AsRef<Path>,
     size: usize,
 ) -> XmlParserResult<()> {
     for (index, chaves) in all_keys.iter().chunks(size).into_iter().enumerate() {
-        let file_txt = format!("{filename}-{:05}.txt", index + 1);
+        let mut file_txt = filename.as_ref().as_os_str().to_os_string();
+        file_txt.push(format!("-{:05}.txt", index + 1));
         let mut output = File::create(file_txt)?;
         for chave in chaves {
             writeln!(output, "{chave}")?;
@@ -414,6 +1122,19 @@ pub fn print_nfe_ctes(correlacoes: &Correlacoes, arguments: &Arguments) {
     println!("nfe_ctes.len(): {}\n", correlacoes.nfe_ctes.len());
 }
 
+/// Exibe o menor caminho que justifica a correlação entre duas chaves (`--explicar`).
+pub fn print_explicacao(
+    correlacoes: &Correlacoes,
+    propagar: &[TipoRelacaoCte],
+    origem: &str,
+    destino: &str,
+) {
+    match explicar_correlacao(correlacoes, propagar, origem, destino) {
+        Some(explicacao) => println!("{explicacao}"),
+        None => println!("Nenhuma correlação encontrada entre {origem} e {destino}.\n"),
+    }
+}
+
 fn show_docs(doc_tipo: &str, docs: &[String]) {
     let size = docs.len();
 
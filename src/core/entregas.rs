@@ -0,0 +1,374 @@
+//! # Comprovante e Insucesso de Entrega
+//!
+//! O transportador registra a entrega da carga com o Comprovante de Entrega (CT-e 110180,
+//! NF-e 110130): data e hora, documento e nome de quem recebeu e a geolocalização do
+//! ponto de entrega. A tentativa frustrada é registrada como Insucesso na Entrega (CT-e
+//! 110190, NF-e 110192), com o motivo. Ambos podem ser cancelados por eventos próprios,
+//! que referenciam o protocolo do evento cancelado.
+//!
+//! Cada CT-e e NF-e recebe as colunas do comprovante mais recente não cancelado, os dias
+//! decorridos desde a emissão e o último insucesso registrado. Apenas os eventos
+//! homologados pela SEFAZ (`cStat` 135 ou 136) são considerados, inclusive os cancelamentos.
+
+use chrono::NaiveDate;
+use rayon::prelude::*;
+use serde::{Deserialize, Serialize};
+use std::collections::{HashMap, HashSet};
+
+use crate::{
+    descrever_codigo, get_naive_date_from_yyyy_mm_dd,
+    xml_structs::{
+        cte::InfoCte, cte_evento::InfoCteEvento, nfe_documento::InfoNfeDocumento,
+        nfe_evento::InfoNfeEvento,
+    },
+};
+
+/// Comprovante de Entrega do CT-e e da NF-e.
+const COMPROVANTES: [&str; 2] = ["110180", "110130"];
+
+/// Insucesso na Entrega do CT-e e da NF-e.
+const INSUCESSOS: [&str; 2] = ["110190", "110192"];
+
+/// Motivo do insucesso na entrega (`tpMotivo`).
+const MOTIVOS_INSUCESSO: [(&str, &str); 4] = [
+    ("1", "Recebedor não Encontrado"),
+    ("2", "Recusa do Recebedor"),
+    ("3", "Endereço Inexistente"),
+    ("4", "Outros"),
+];
+
+/// Dados dos eventos de entrega, comuns a NF-e e CT-e.
+///
+/// Nos eventos de cancelamento, apenas `protocolo_cancelado` é preenchido.
+#[derive(Debug, Default, Serialize, Deserialize, Clone, PartialEq)]
+pub struct EventoEntrega {
+    /// Data e hora da entrega (`dhEntrega`) ou da tentativa (`dhTentativaEntrega`).
+    pub dh_entrega: Option<String>,
+    /// Documento de identificação de quem recebeu a carga (`nDoc`).
+    pub documento: Option<String>,
+    /// Nome de quem recebeu a carga (`xNome`).
+    pub nome: Option<String>,
+    /// Latitude do ponto de entrega ou da tentativa (`latitude`).
+    pub latitude: Option<String>,
+    /// Longitude do ponto de entrega ou da tentativa (`longitude`).
+    pub longitude: Option<String>,
+    /// Número da tentativa de entrega (`nTentativa`).
+    pub n_tentativa: Option<String>,
+    /// Motivo do insucesso: `tpMotivo - descrição`, seguido da justificativa, se houver.
+    pub motivo: Option<String>,
+    /// Protocolo do comprovante ou do insucesso cancelado por este evento.
+    pub protocolo_cancelado: Option<String>,
+}
+
+/// Descreve o motivo do insucesso: `tpMotivo - descrição: xJustMotivo`.
+pub fn descrever_motivo_insucesso(
+    tp_motivo: &Option<String>,
+    x_just_motivo: &Option<String>,
+) -> Option<String> {
+    let motivo = descrever_codigo(tp_motivo, &MOTIVOS_INSUCESSO);
+    let justificativa = x_just_motivo
+        .as_deref()
+        .map(str::trim)
+        .filter(|texto| !texto.is_empty());
+
+    match (motivo, justificativa) {
+        (Some(motivo), Some(justificativa)) => Some(format!("{motivo}: {justificativa}")),
+        (motivo, justificativa) => motivo.or(justificativa.map(str::to_string)),
+    }
+}
+
+/// Evento de entrega de uma chave, independente do tipo de documento.
+#[derive(Debug)]
+struct Registro<'a> {
+    chave: &'a str,
+    tp_evento: &'a str,
+    ordem: (Option<&'a str>, Option<u32>),
+    protocolo: Option<&'a str>,
+    entrega: &'a EventoEntrega,
+}
+
+/// Comprovante e insucesso vigentes de um documento.
+#[derive(Debug, Default)]
+struct Entrega<'a> {
+    comprovante: Option<&'a Registro<'a>>,
+    insucesso: Option<&'a Registro<'a>>,
+}
+
+/// Colunas de entrega de [`InfoCte`] e [`InfoNfeDocumento`].
+struct ColunasEntrega {
+    entregue_em: Option<String>,
+    dias_ate_entrega: Option<i64>,
+    documento: Option<String>,
+    nome: Option<String>,
+    latitude: Option<String>,
+    longitude: Option<String>,
+    insucesso: Option<String>,
+}
+
+impl Entrega<'_> {
+    fn colunas(&self, data_emissao: Option<NaiveDate>) -> ColunasEntrega {
+        let comprovante = self.comprovante.map(|registro| registro.entrega);
+        let entregue_em = comprovante.and_then(|entrega| entrega.dh_entrega.clone());
+        let dias_ate_entrega = get_naive_date_from_yyyy_mm_dd(&entregue_em)
+            .zip(data_emissao)
+            .map(|(entrega, emissao)| (entrega - emissao).num_days());
+
+        let insucesso = self.insucesso.map(|registro| {
+            let entrega = registro.entrega;
+            let data = entrega
+                .dh_entrega
+                .as_deref()
+                .or(registro.ordem.0)
+                .and_then(|dh| dh.get(..10));
+            let tentativa = entrega
+                .n_tentativa
+                .as_deref()
+                .map(|n| format!("tentativa {n}"));
+            let motivo = [entrega.motivo.clone(), tentativa]
+                .into_iter()
+                .flatten()
+                .collect::<Vec<_>>()
+                .join(", ");
+            match data {
+                Some(data) => format!("{data}: {motivo}"),
+                None => motivo,
+            }
+        });
+
+        ColunasEntrega {
+            entregue_em,
+            dias_ate_entrega,
+            documento: comprovante.and_then(|entrega| entrega.documento.clone()),
+            nome: comprovante.and_then(|entrega| entrega.nome.clone()),
+            latitude: comprovante.and_then(|entrega| entrega.latitude.clone()),
+            longitude: comprovante.and_then(|entrega| entrega.longitude.clone()),
+            insucesso,
+        }
+    }
+}
+
+fn registros_nfe(eventos_nfe: &[InfoNfeEvento]) -> impl Iterator<Item = Registro<'_>> {
+    eventos_nfe
+        .iter()
+        .filter(|evento| evento.is_homologado())
+        .filter_map(|evento| {
+            Some(Registro {
+                chave: evento.nfe.as_deref()?,
+                tp_evento: evento.tp_evento.as_deref()?,
+                ordem: (evento.dh_evento.as_deref(), evento.n_seq_evento),
+                protocolo: evento.protocolo.as_deref(),
+                entrega: evento.entrega.as_ref()?,
+            })
+        })
+}
+
+fn registros_cte(eventos_cte: &[InfoCteEvento]) -> impl Iterator<Item = Registro<'_>> {
+    eventos_cte
+        .iter()
+        .filter(|evento| evento.is_homologado())
+        .filter_map(|evento| {
+            Some(Registro {
+                chave: evento.cte.as_deref()?,
+                tp_evento: evento.tp_evento.as_deref()?,
+                ordem: (evento.dh_evento.as_deref(), evento.n_seq_evento),
+                protocolo: evento.protocolo.as_deref(),
+                entrega: evento.entrega.as_ref()?,
+            })
+        })
+}
+
+/// Comprovante e insucesso mais recentes e não cancelados de cada chave.
+fn get_entregas<'a>(registros: &'a [Registro<'a>]) -> HashMap<&'a str, Entrega<'a>> {
+    let cancelados: HashSet<(&str, &str)> = registros
+        .iter()
+        .filter_map(|registro| {
+            let protocolo = registro.entrega.protocolo_cancelado.as_deref()?;
+            Some((registro.chave, protocolo.trim()))
+        })
+        .collect();
+
+    let is_cancelado = |registro: &Registro| {
+        registro
+            .protocolo
+            .is_some_and(|protocolo| cancelados.contains(&(registro.chave, protocolo.trim())))
+    };
+
+    let mut entregas: HashMap<&str, Entrega> = HashMap::new();
+    for registro in registros.iter().filter(|registro| !is_cancelado(registro)) {
+        let entrega = if COMPROVANTES.contains(&registro.tp_evento) {
+            &mut entregas.entry(registro.chave).or_default().comprovante
+        } else if INSUCESSOS.contains(&registro.tp_evento) {
+            &mut entregas.entry(registro.chave).or_default().insucesso
+        } else {
+            continue;
+        };
+
+        if entrega.is_none_or(|atual| registro.ordem > atual.ordem) {
+            *entrega = Some(registro);
+        }
+    }
+
+    entregas
+}
+
+/// Preenche as colunas de entrega dos CT-es e das NF-es a partir dos seus eventos.
+pub fn adicionar_entregas(
+    ctes: &mut [InfoCte],
+    documentos: &mut [InfoNfeDocumento],
+    eventos_nfe: &[InfoNfeEvento],
+    eventos_cte: &[InfoCteEvento],
+) {
+    let registros: Vec<Registro> = registros_cte(eventos_cte)
+        .chain(registros_nfe(eventos_nfe))
+        .collect();
+    let entregas = get_entregas(&registros);
+
+    ctes.par_iter_mut().for_each(|info| {
+        let Some(entrega) = info.cte.as_deref().and_then(|cte| entregas.get(cte)) else {
+            return;
+        };
+        let colunas = entrega.colunas(info.data_emissao);
+        info.entregue_em = colunas.entregue_em;
+        info.dias_ate_entrega = colunas.dias_ate_entrega;
+        info.entrega_documento = colunas.documento;
+        info.entrega_nome = colunas.nome;
+        info.entrega_latitude = colunas.latitude;
+        info.entrega_longitude = colunas.longitude;
+        info.insucesso_entrega = colunas.insucesso;
+    });
+
+    documentos.par_iter_mut().for_each(|documento| {
+        let Some(entrega) = documento.nfe.as_deref().and_then(|nfe| entregas.get(nfe)) else {
+            return;
+        };
+        let colunas = entrega.colunas(documento.data_emissao);
+        documento.entregue_em = colunas.entregue_em;
+        documento.dias_ate_entrega = colunas.dias_ate_entrega;
+        documento.entrega_documento = colunas.documento;
+        documento.entrega_nome = colunas.nome;
+        documento.entrega_latitude = colunas.latitude;
+        documento.entrega_longitude = colunas.longitude;
+        documento.insucesso_entrega = colunas.insucesso;
+    });
+}
+
+//----------------------------------------------------------------------------//
+//                                   Tests                                    //
+//----------------------------------------------------------------------------//
+
+/// Run tests with:
+/// cargo test -- --show-output tests_entregas
+#[cfg(test)]
+mod tests_entregas {
+    use super::*;
+
+    fn evento_cte(
+        tp_evento: &str,
+        dh_evento: &str,
+        protocolo: &str,
+        entrega: EventoEntrega,
+    ) -> InfoCteEvento {
+        InfoCteEvento {
+            cte: Some("cte1".to_string()),
+            tp_evento: Some(tp_evento.to_string()),
+            n_seq_evento: Some(1),
+            dh_evento: Some(dh_evento.to_string()),
+            protocolo: Some(protocolo.to_string()),
+            entrega: Some(entrega),
+            situacao: Some("135 - Evento registrado e vinculado ao CT-e".to_string()),
+            ..Default::default()
+        }
+    }
+
+    fn comprovante(dh_entrega: &str, nome: &str) -> EventoEntrega {
+        EventoEntrega {
+            dh_entrega: Some(dh_entrega.to_string()),
+            documento: Some("12345678900".to_string()),
+            nome: Some(nome.to_string()),
+            latitude: Some("-23.550520".to_string()),
+            longitude: Some("-46.633308".to_string()),
+            ..Default::default()
+        }
+    }
+
+    #[test]
+    /// `cargo test -- --show-output comprovante_vigente_e_insucesso`
+    fn comprovante_vigente_e_insucesso() {
+        let insucesso = EventoEntrega {
+            dh_entrega: Some("2025-03-11T09:00:00-03:00".to_string()),
+            n_tentativa: Some("1".to_string()),
+            motivo: descrever_motivo_insucesso(&Some("2".to_string()), &None),
+            ..Default::default()
+        };
+        let cancelamento = EventoEntrega {
+            protocolo_cancelado: Some("prot2".to_string()),
+            ..Default::default()
+        };
+        let mut rejeitado = evento_cte(
+            "110180",
+            "2025-03-14T08:05:00-03:00",
+            "prot5",
+            comprovante("2025-03-14T08:00:00-03:00", "José"),
+        );
+        rejeitado.situacao = Some("631 - Rejeição: Duplicidade de evento".to_string());
+
+        let eventos_cte = vec![
+            evento_cte("110190", "2025-03-11T09:05:00-03:00", "prot1", insucesso),
+            evento_cte(
+                "110180",
+                "2025-03-12T10:05:00-03:00",
+                "prot3",
+                comprovante("2025-03-12T10:00:00-03:00", "Maria"),
+            ),
+            evento_cte(
+                "110180",
+                "2025-03-13T15:05:00-03:00",
+                "prot2",
+                comprovante("2025-03-13T15:00:00-03:00", "João"),
+            ),
+            evento_cte("110181", "2025-03-13T16:00:00-03:00", "prot4", cancelamento),
+            rejeitado,
+        ];
+
+        let mut info = InfoCte::default();
+        info.cte = Some("cte1".to_string());
+        info.data_emissao = NaiveDate::from_ymd_opt(2025, 3, 10);
+        let mut ctes = vec![info];
+
+        adicionar_entregas(&mut ctes, &mut [], &[], &eventos_cte);
+        println!("ctes: {ctes:#?}");
+
+        // O comprovante de 13/03 foi cancelado e o de 14/03, rejeitado: vale o de 12/03
+        assert_eq!(
+            ctes[0].entregue_em.as_deref(),
+            Some("2025-03-12T10:00:00-03:00")
+        );
+        assert_eq!(ctes[0].dias_ate_entrega, Some(2));
+        assert_eq!(ctes[0].entrega_nome.as_deref(), Some("Maria"));
+        assert_eq!(ctes[0].entrega_latitude.as_deref(), Some("-23.550520"));
+        assert_eq!(
+            ctes[0].insucesso_entrega.as_deref(),
+            Some("2025-03-11: 2 - Recusa do Recebedor, tentativa 1")
+        );
+    }
+
+    #[test]
+    /// `cargo test -- --show-output motivo_do_insucesso`
+    fn motivo_do_insucesso() {
+        let motivo = |tp: &str, just: &str| {
+            descrever_motivo_insucesso(
+                &Some(tp.to_string()).filter(|s| !s.is_empty()),
+                &Some(just.to_string()),
+            )
+        };
+        assert_eq!(
+            motivo("4", " Portão fechado "),
+            Some("4 - Outros: Portão fechado".to_string())
+        );
+        assert_eq!(
+            motivo("3", ""),
+            Some("3 - Endereço Inexistente".to_string())
+        );
+        assert_eq!(motivo("", "Sem motivo"), Some("Sem motivo".to_string()));
+    }
+}
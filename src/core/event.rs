@@ -1,21 +1,58 @@
 //! # Acoplamento de Eventos e Cancelamentos
 //!
-//! Aplica cancelamentos e complementos aos documentos fiscais correspondentes.
+//! Aplica cancelamentos e complementos aos documentos fiscais correspondentes,
+//! bem como as substituições e anulações de CT-es.
 
 use rayon::prelude::*;
-use std::collections::HashMap;
+use std::collections::{BTreeSet, HashMap, HashSet};
 
 use crate::{
-    GroupByHashMapExt,
+    GetKey, GroupByHashMapExt, TipoRelacaoCte,
     xml_structs::{
-        cancelamento_cte::InfoCteCancel, cancelamento_nfe::InfoNfeCancel, cte::InfoCte,
-        cte_evento::InfoCteEvento, nfe::InfoNfe, nfe_evento::InfoNfeEvento,
+        cancelamento_cte::InfoCteCancel, cancelamento_nfe::InfoNfeCancel, cobranca::InfoDuplicata,
+        cte::InfoCte, cte_evento::InfoCteEvento, nfe::InfoNfe, nfe_detalhamento::InfoVolume,
+        nfe_documento::InfoNfeDocumento, nfe_evento::InfoNfeEvento, pagamento::InfoPagamento,
     },
 };
 
+/// Linhas de NF-e (itens ou cabeçalho) que recebem a marcação de cancelamento.
+pub trait Cancelavel: GetKey + Send {
+    fn set_cancelado(&mut self);
+}
+
+impl Cancelavel for InfoNfe {
+    fn set_cancelado(&mut self) {
+        self.cancelado = Some("Sim".to_string());
+    }
+}
+
+impl Cancelavel for InfoNfeDocumento {
+    fn set_cancelado(&mut self) {
+        self.cancelado = Some("Sim".to_string());
+    }
+}
+
+impl Cancelavel for InfoPagamento {
+    fn set_cancelado(&mut self) {
+        self.cancelado = Some("Sim".to_string());
+    }
+}
+
+impl Cancelavel for InfoDuplicata {
+    fn set_cancelado(&mut self) {
+        self.cancelado = Some("Sim".to_string());
+    }
+}
+
+impl Cancelavel for InfoVolume {
+    fn set_cancelado(&mut self) {
+        self.cancelado = Some("Sim".to_string());
+    }
+}
+
 /// Atualiza as NF-es marcando-as como canceladas com base em eventos e retornos homologados.
-pub fn adicionar_eventos_nfe(
-    nfes: &mut [InfoNfe],
+pub fn adicionar_eventos_nfe<T: Cancelavel>(
+    nfes: &mut [T],
     eventos_nfe: &[InfoNfeEvento],
     cancelamentos_nfe: &[InfoNfeCancel],
 ) {
@@ -29,18 +66,18 @@ pub fn adicionar_eventos_nfe(
         cancelamentos_nfe.group_by_hashmap_key_vector_v2();
 
     nfes.par_iter_mut().for_each(|info_nfe| {
-        if let Some(nfe) = &info_nfe.nfe {
-            if let Some(eventos) = eventos_map.get(nfe)
+        if let Some(nfe) = info_nfe.get_chave() {
+            if let Some(eventos) = eventos_map.get(&nfe)
                 && eventos.iter().any(|e| e.cancelado)
             {
-                info_nfe.cancelado = Some("Sim".to_string());
+                info_nfe.set_cancelado();
                 return;
             }
 
-            if let Some(cancelamentos) = cancel_map.get(nfe)
+            if let Some(cancelamentos) = cancel_map.get(&nfe)
                 && cancelamentos.iter().any(|c| c.cancelado)
             {
-                info_nfe.cancelado = Some("Sim".to_string());
+                info_nfe.set_cancelado();
             }
         }
     });
@@ -71,6 +108,7 @@ pub fn adicionar_eventos_cte(
                     // append() or extend() or concat()
                     // https://rustjobs.dev/blog/vector-concatenation-in-rust/
                     info_cte.cte_complementar.extend(evento.get_ctes());
+                    info_cte.relacoes.extend(evento.get_relacoes());
                 }
             }
 
@@ -82,3 +120,136 @@ pub fn adicionar_eventos_cte(
         }
     });
 }
+
+/// Marca os CT-es substituídos (`infCteSub` ou evento) ou anulados (`infCteAnu`), bem como
+/// os próprios CT-es de anulação.
+///
+/// Requer os eventos já incorporados às relações (ver [`adicionar_eventos_cte`]).
+/// CT-es substituto ou de anulação cancelados não produzem efeito.
+/// O CT-e marcado deixa de ser válido ([`InfoCte::is_valid`]) e sai dos totais, agrupamentos
+/// e do crédito sobre o frete, mas a relação continua na tabela "CTes x CTes".
+pub fn adicionar_substituicoes_cte(ctes: &mut [InfoCte]) {
+    let cancelados: HashSet<&str> = ctes
+        .iter()
+        .filter(|info| info.is_canceled())
+        .filter_map(|info| info.cte.as_deref())
+        .collect();
+
+    let mut substituicoes: HashMap<String, BTreeSet<String>> = HashMap::new();
+    let mut anulacoes: HashMap<String, BTreeSet<String>> = HashMap::new();
+    for relacao in ctes.iter().flat_map(|info| &info.relacoes) {
+        let descricao = match relacao.tipo {
+            TipoRelacaoCte::Substituicao => "Substituído por",
+            TipoRelacaoCte::Anulacao => "Anulado por",
+            _ => continue,
+        };
+        if relacao.original == relacao.derivado || cancelados.contains(relacao.derivado.as_str()) {
+            continue;
+        }
+        substituicoes
+            .entry(relacao.original.clone())
+            .or_default()
+            .insert(format!("{descricao} {}", relacao.derivado));
+        if relacao.tipo == TipoRelacaoCte::Anulacao {
+            anulacoes
+                .entry(relacao.derivado.clone())
+                .or_default()
+                .insert(format!("Anula {}", relacao.original));
+        }
+    }
+
+    ctes.par_iter_mut()
+        .filter(|info| !info.is_canceled())
+        .for_each(|info| {
+            let descrever = |mapa: &HashMap<String, BTreeSet<String>>| {
+                info.cte
+                    .as_ref()
+                    .and_then(|cte| mapa.get(cte))
+                    .map(|descricoes| descricoes.iter().cloned().collect::<Vec<_>>().join("; "))
+            };
+            let (substituido, anulacao) = (descrever(&substituicoes), descrever(&anulacoes));
+            info.substituido = substituido;
+            info.anulacao = anulacao;
+        });
+}
+
+//----------------------------------------------------------------------------//
+//                                   Tests                                    //
+//----------------------------------------------------------------------------//
+
+/// Run tests with:
+/// cargo test -- --show-output tests_substituicoes_cte
+#[cfg(test)]
+mod tests_substituicoes_cte {
+    use super::*;
+    use crate::{RelacaoCte, core::fixtures};
+
+    fn cte(chave: &str, relacoes: Vec<RelacaoCte>) -> InfoCte {
+        let mut info = fixtures::cte(chave);
+        info.relacoes = relacoes;
+        info
+    }
+
+    #[test]
+    /// `cargo test -- --show-output substituido_e_anulado`
+    fn substituido_e_anulado() {
+        let mut substituto_cancelado = cte(
+            "cte6",
+            vec![RelacaoCte::xml(
+                "cte5",
+                "cte6",
+                TipoRelacaoCte::Substituicao,
+                "infCteSub",
+            )],
+        );
+        substituto_cancelado.cancelado = Some("Sim".to_string());
+
+        let mut ctes = vec![
+            cte("cte1", Vec::new()),
+            cte(
+                "cte2",
+                vec![RelacaoCte::xml(
+                    "cte1",
+                    "cte2",
+                    TipoRelacaoCte::Substituicao,
+                    "infCteSub",
+                )],
+            ),
+            cte(
+                "cte3",
+                vec![RelacaoCte::evento(
+                    "cte3",
+                    "cte7",
+                    TipoRelacaoCte::Substituicao,
+                    "evCTeSubstituido",
+                )],
+            ),
+            cte(
+                "cte4",
+                vec![RelacaoCte::xml(
+                    "cte0",
+                    "cte4",
+                    TipoRelacaoCte::Anulacao,
+                    "infCteAnu",
+                )],
+            ),
+            cte("cte0", Vec::new()),
+            cte("cte5", Vec::new()),
+            substituto_cancelado,
+        ];
+
+        adicionar_substituicoes_cte(&mut ctes);
+
+        assert_eq!(ctes[0].substituido.as_deref(), Some("Substituído por cte2"));
+        assert_eq!(ctes[2].substituido.as_deref(), Some("Substituído por cte7"));
+        assert_eq!(ctes[4].substituido.as_deref(), Some("Anulado por cte4"));
+        assert_eq!(ctes[3].anulacao.as_deref(), Some("Anula cte0"));
+        assert!(!ctes[0].is_valid() && !ctes[2].is_valid() && !ctes[4].is_valid());
+
+        // O substituto continua válido; o CT-e de anulação não
+        assert!(ctes[1].is_valid() && !ctes[3].is_valid());
+
+        // Substituto cancelado: o original continua válido
+        assert!(ctes[5].is_valid());
+    }
+}
@@ -0,0 +1,469 @@
+//! # Explicação de Correlações
+//!
+//! Quando o fecho transitivo liga uma NF-e a um CT-e distante vários saltos, a auditoria
+//! precisa justificar o vínculo ("por que este frete está nesta nota?"). Este módulo
+//! reconstrói o menor caminho de documentos entre duas chaves (NF-e ou CT-e) no mesmo grafo
+//! usado por [`GraphExtension::expand_ctes`] e [`GraphExtension::expand_nfes`].
+//!
+//! Cada aresta informa a sua fonte (campo do XML ou evento) e o arquivo em que foi lida,
+//! de modo que a explicação possa ser copiada para os papéis de trabalho.
+//!
+//! Observação: sem `substituicao` em `--propagar-nfes`, a aresta de substituição é percorrida
+//! apenas do CT-e substituído para o substituto, no sentido em que as NF-es são transferidas.
+//!
+//! Um par NF-e/CT-e só é explicado se constar das correlações exportadas
+//! ([`Correlacoes::nfe_ctes`], já restritas a documentos válidos): o CT-e substituído,
+//! por exemplo, tem caminho até as suas NF-es, mas não é exportado como correlacionado.
+//!
+//! [`Correlacoes::nfe_ctes`]: crate::Correlacoes::nfe_ctes
+//!
+//! [`GraphExtension::expand_ctes`]: crate::GraphExtension::expand_ctes
+//! [`GraphExtension::expand_nfes`]: crate::GraphExtension::expand_nfes
+
+use std::{
+    collections::{HashMap, HashSet, VecDeque},
+    fmt, iter,
+};
+
+use crate::{Correlacoes, KeyDoc, TipoRelacaoCte};
+
+/// Aresta do grafo de correlações acompanhada da sua justificativa.
+#[derive(Debug, Clone, PartialEq)]
+pub struct ArestaCorrelacao {
+    pub de: String,
+    pub para: String,
+    /// Ex.: "CTe A declara a NFe B" ou "CTe A é o redespacho do CTe B".
+    pub descricao: String,
+    /// Campo do XML ou evento que declara a aresta.
+    pub fonte: String,
+    /// Arquivo XML em que a aresta foi lida, se conhecido.
+    pub arquivo: Option<String>,
+}
+
+impl fmt::Display for ArestaCorrelacao {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        write!(f, "{} ({}", self.descricao, self.fonte)?;
+        if let Some(arquivo) = &self.arquivo {
+            write!(f, "; arquivo: {arquivo}")?;
+        }
+        write!(f, ")")
+    }
+}
+
+/// Menor caminho de documentos entre duas chaves.
+#[derive(Debug, Clone, PartialEq)]
+pub struct Explicacao {
+    pub origem: String,
+    pub destino: String,
+    /// Arestas na ordem em que são percorridas de `origem` até `destino`.
+    pub arestas: Vec<ArestaCorrelacao>,
+}
+
+impl Explicacao {
+    /// Chaves dos documentos percorridos, de `origem` até `destino`.
+    pub fn documentos(&self) -> Vec<&str> {
+        iter::once(self.origem.as_str())
+            .chain(self.arestas.iter().map(|aresta| aresta.para.as_str()))
+            .collect()
+    }
+}
+
+impl fmt::Display for Explicacao {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        writeln!(
+            f,
+            "Correlação entre {} e {} ({} aresta(s)):",
+            self.origem,
+            self.destino,
+            self.arestas.len()
+        )?;
+        writeln!(f, "Caminho: {}", self.documentos().join(" -> "))?;
+        self.arestas
+            .iter()
+            .enumerate()
+            .try_for_each(|(i, aresta)| writeln!(f, "{:>3}. {aresta}", i + 1))
+    }
+}
+
+/// Grafo não ponderado de CT-es e NF-es com as arestas que alimentam o fecho transitivo.
+///
+/// As NF-es são sempre extremidades do caminho: duas NF-es de um mesmo CT-e não
+/// ligam CT-es entre si, assim como em [`GraphExtension::expand_nfes`].
+///
+/// [`GraphExtension::expand_nfes`]: crate::GraphExtension::expand_nfes
+#[derive(Debug, Default)]
+pub struct GrafoCorrelacoes {
+    arestas: Vec<ArestaCorrelacao>,
+    /// chave -> [(índice da aresta, vizinho)], ordenado pelo vizinho.
+    adjacencias: HashMap<String, Vec<(usize, String)>>,
+    nfes: HashSet<String>,
+    /// NF-e -> CT-es, como nas colunas exportadas.
+    nfe_ctes: HashMap<String, HashSet<String>>,
+}
+
+impl GrafoCorrelacoes {
+    /// Monta o grafo com os mesmos critérios de [`DocsFiscais::get_correlations`]:
+    ///
+    /// * CT-e -> NF-e: chaves de NF-es válidas declaradas em CT-es não cancelados;
+    /// * CT-e -> CT-e: relações dos tipos em `propagar` cujos dois CT-es estão disponíveis
+    ///   (ver [`RelacaoCte::cte_disponivel`]).
+    ///
+    /// [`DocsFiscais::get_correlations`]: crate::DocsFiscais::get_correlations
+    /// [`RelacaoCte::cte_disponivel`]: crate::RelacaoCte::cte_disponivel
+    pub fn new(correlacoes: &Correlacoes, propagar: &[TipoRelacaoCte]) -> Self {
+        let mut grafo = Self {
+            nfe_ctes: correlacoes.nfe_ctes.clone(),
+            ..Default::default()
+        };
+        let nfe_valida = |nfe: &str| correlacoes.nfe_info.contains_key(&KeyDoc::new(nfe, true));
+
+        // CT-es cancelados não correlacionam. Os substituídos, embora inválidos,
+        // ainda repassam as suas NF-es ao substituto.
+        for info in correlacoes
+            .cte_info
+            .values()
+            .filter_map(|infos| infos.first())
+            .filter(|info| !info.is_canceled())
+        {
+            let Some(cte) = &info.cte else {
+                continue;
+            };
+
+            let campos = [
+                (&info.remetente_nfes, "XML do CTe: rem/infNFe/chave"),
+                (
+                    &info.nfes_vinculados,
+                    "XML do CTe: infCTeNorm/infDoc/infNFe/chave",
+                ),
+                (
+                    &info.nfes_papel,
+                    "XML do CTe: infCTeNorm/infDoc/infNF ou infOutros (remetente, série e número)",
+                ),
+            ];
+
+            for (nfes, fonte) in campos {
+                for nfe in nfes.iter().filter(|&nfe| nfe != cte && nfe_valida(nfe)) {
+                    grafo.nfes.insert(nfe.clone());
+                    grafo.adicionar(
+                        ArestaCorrelacao {
+                            de: cte.clone(),
+                            para: nfe.clone(),
+                            descricao: format!("CTe {cte} declara a NFe {nfe}"),
+                            fonte: fonte.to_string(),
+                            arquivo: info.arquivo.clone(),
+                        },
+                        false,
+                    );
+                }
+            }
+        }
+
+        for relacao in &correlacoes.relacoes_ctes {
+            let disponivel = |cte: &str| relacao.cte_disponivel(cte, &correlacoes.cte_info);
+            if relacao.original == relacao.derivado
+                || !disponivel(relacao.referenciado())
+                || !disponivel(relacao.declarante())
+            {
+                continue;
+            }
+
+            // Sem propagação, as NF-es seguem apenas do CT-e substituído para o substituto
+            let direcionada = match relacao.tipo {
+                tipo if propagar.contains(&tipo) => false,
+                TipoRelacaoCte::Substituicao => true,
+                _ => continue,
+            };
+
+            grafo.adicionar(
+                ArestaCorrelacao {
+                    de: relacao.original.clone(),
+                    para: relacao.derivado.clone(),
+                    descricao: relacao.descrever(),
+                    fonte: relacao.fonte.to_string(),
+                    arquivo: relacao.arquivo.clone(),
+                },
+                direcionada,
+            );
+        }
+
+        grafo
+            .adjacencias
+            .values_mut()
+            .for_each(|vizinhos| vizinhos.sort_by(|a, b| a.1.cmp(&b.1)));
+
+        grafo
+    }
+
+    fn adicionar(&mut self, aresta: ArestaCorrelacao, direcionada: bool) {
+        let indice = self.arestas.len();
+        self.adjacencias
+            .entry(aresta.de.clone())
+            .or_default()
+            .push((indice, aresta.para.clone()));
+        if !direcionada {
+            self.adjacencias
+                .entry(aresta.para.clone())
+                .or_default()
+                .push((indice, aresta.de.clone()));
+        }
+        self.arestas.push(aresta);
+    }
+
+    /// Menor caminho entre `origem` e `destino` (NF-e ou CT-e), ou `None` se não estiverem ligados.
+    ///
+    /// Entre uma NF-e e um CT-e, exige que o par conste das correlações exportadas.
+    pub fn explicar(&self, origem: &str, destino: &str) -> Option<Explicacao> {
+        // A busca parte da NF-e, no sentido em que as NF-es propagam pelos CT-es
+        let inverter = self.nfes.contains(destino) && !self.nfes.contains(origem);
+        let (inicio, fim) = if inverter {
+            (destino, origem)
+        } else {
+            (origem, destino)
+        };
+
+        if self.nfes.contains(inicio)
+            && !self.nfes.contains(fim)
+            && !self
+                .nfe_ctes
+                .get(inicio)
+                .is_some_and(|ctes| ctes.contains(fim))
+        {
+            return None;
+        }
+
+        let mut arestas: Vec<ArestaCorrelacao> = self
+            .menor_caminho(inicio, fim)?
+            .into_iter()
+            .map(|(indice, de, para)| ArestaCorrelacao {
+                de: de.to_string(),
+                para: para.to_string(),
+                ..self.arestas[indice].clone()
+            })
+            .collect();
+
+        if inverter {
+            arestas.reverse();
+            arestas
+                .iter_mut()
+                .for_each(|aresta| std::mem::swap(&mut aresta.de, &mut aresta.para));
+        }
+
+        Some(Explicacao {
+            origem: origem.to_string(),
+            destino: destino.to_string(),
+            arestas,
+        })
+    }
+
+    /// Busca em largura: retorna os passos `(índice da aresta, de, para)` de `inicio` a `fim`.
+    fn menor_caminho<'a>(
+        &'a self,
+        inicio: &'a str,
+        fim: &'a str,
+    ) -> Option<Vec<(usize, &'a str, &'a str)>> {
+        let mut anterior: HashMap<&str, (usize, &str)> = HashMap::new();
+        let mut visitados: HashSet<&str> = HashSet::from([inicio]);
+        let mut fila: VecDeque<&str> = VecDeque::from([inicio]);
+
+        while let Some(atual) = fila.pop_front() {
+            if atual == fim {
+                break;
+            }
+            if atual != inicio && self.nfes.contains(atual) {
+                continue;
+            }
+            for (indice, vizinho) in self.adjacencias.get(atual).into_iter().flatten() {
+                if visitados.insert(vizinho) {
+                    anterior.insert(vizinho, (*indice, atual));
+                    fila.push_back(vizinho);
+                }
+            }
+        }
+
+        let mut passos = Vec::new();
+        let mut atual = fim;
+        while atual != inicio {
+            let &(indice, de) = anterior.get(atual)?;
+            passos.push((indice, de, atual));
+            atual = de;
+        }
+        passos.reverse();
+
+        Some(passos)
+    }
+}
+
+/// Explica a correlação entre duas chaves (NF-e ou CT-e).
+///
+/// Monta o grafo a cada chamada; para várias consultas, use [`GrafoCorrelacoes`].
+pub fn explicar_correlacao(
+    correlacoes: &Correlacoes,
+    propagar: &[TipoRelacaoCte],
+    origem: &str,
+    destino: &str,
+) -> Option<Explicacao> {
+    GrafoCorrelacoes::new(correlacoes, propagar).explicar(origem, destino)
+}
+
+//----------------------------------------------------------------------------//
+//                                   Tests                                    //
+//----------------------------------------------------------------------------//
+
+/// Run tests with:
+/// cargo test -- --show-output tests_explicacao
+#[cfg(test)]
+mod tests_explicacao {
+    use super::*;
+    use crate::{
+        RelacaoCte,
+        core::fixtures::{self, CteXml, argumentos, chaves, docs_fiscais, item, por_chave},
+        xml_structs::cte::InfoCte,
+    };
+
+    fn cte(chave: &str, remetente_nfes: &[&str]) -> InfoCte {
+        let mut info = fixtures::cte(chave);
+        info.remetente_nfes = chaves(remetente_nfes);
+        info.arquivo = Some(format!("{chave}.xml"));
+        info
+    }
+
+    fn correlacoes() -> Correlacoes {
+        let nfe_info = por_chave([item("nfe1", 1, 100.0)]);
+
+        let cte_info = por_chave([
+            cte("cte1", &["nfe1"]),
+            cte("cte2", &[]),
+            cte("cte3", &[]),
+            cte("cte4", &[]),
+            cte("cte5", &["nfe1"]),
+        ]);
+
+        let relacoes_ctes = vec![
+            RelacaoCte::evento(
+                "cte1",
+                "cte2",
+                TipoRelacaoCte::Subcontratacao,
+                "evCTeSubcontratacao",
+            )
+            .com_arquivo(Some("evento-cte1.xml".to_string())),
+            RelacaoCte::xml("cte2", "cte3", TipoRelacaoCte::Complementar, "infCteComp")
+                .com_arquivo(Some("cte3.xml".to_string())),
+            RelacaoCte::xml("cte3", "cte4", TipoRelacaoCte::Substituicao, "infCteSub")
+                .com_arquivo(Some("cte4.xml".to_string())),
+        ];
+
+        let nfe_ctes = HashMap::from([(
+            "nfe1".to_string(),
+            HashSet::from(["cte1", "cte2", "cte3", "cte4", "cte5"].map(String::from)),
+        )]);
+
+        Correlacoes {
+            cte_info,
+            nfe_info,
+            nfe_ctes,
+            relacoes_ctes,
+            ..Default::default()
+        }
+    }
+
+    #[test]
+    /// `cargo test -- --show-output caminho_entre_nfe_e_cte`
+    fn caminho_entre_nfe_e_cte() {
+        let grafo = GrafoCorrelacoes::new(&correlacoes(), &TipoRelacaoCte::PROPAGACAO_PADRAO);
+
+        let explicacao = grafo.explicar("cte3", "nfe1").unwrap();
+        println!("{explicacao}");
+
+        assert_eq!(explicacao.documentos(), ["cte3", "cte2", "cte1", "nfe1"]);
+        assert_eq!(
+            explicacao.arestas[0].descricao,
+            "CTe cte3 complementa o CTe cte2"
+        );
+        assert_eq!(
+            explicacao.arestas[1].to_string(),
+            "CTe cte2 é a subcontratação do CTe cte1 \
+             (Evento do CTe original: evCTeSubcontratacao; arquivo: evento-cte1.xml)"
+        );
+        assert_eq!(explicacao.arestas[2].fonte, "XML do CTe: rem/infNFe/chave");
+
+        // O substituto recebe as NF-es do substituído, mas não o contrário
+        let substituto = grafo.explicar("nfe1", "cte4").unwrap();
+        assert_eq!(
+            substituto.documentos(),
+            ["nfe1", "cte1", "cte2", "cte3", "cte4"]
+        );
+
+        // NF-es não ligam CT-es: cte5 apenas compartilha a nfe1 com o cte1
+        assert!(grafo.explicar("cte5", "cte2").is_none());
+        assert_eq!(
+            grafo.explicar("cte5", "nfe1").unwrap().documentos(),
+            ["cte5", "nfe1"]
+        );
+    }
+
+    #[test]
+    /// `cargo test -- --show-output substituicao_propagada`
+    fn substituicao_propagada() {
+        let propagar = [TipoRelacaoCte::Substituicao];
+        let explicacao = explicar_correlacao(&correlacoes(), &propagar, "cte4", "cte3").unwrap();
+
+        assert_eq!(explicacao.documentos(), ["cte4", "cte3"]);
+        assert!(explicar_correlacao(&correlacoes(), &propagar, "cte4", "nfe1").is_none());
+    }
+
+    #[test]
+    /// `cargo test -- --show-output cte_cancelado_intermediario`
+    fn cte_cancelado_intermediario() {
+        // cte8, cancelado, complementa o cte7 e declara a nfe2
+        let mut cancelado = cte("cte8", &["nfe2"]);
+        cancelado.cancelado = Some("Sim".to_string());
+
+        let mut correlacoes = correlacoes();
+        correlacoes.cte_info.extend(por_chave([cte("cte7", &[])]));
+        correlacoes
+            .cte_info
+            .insert(KeyDoc::new("cte8", false), vec![cancelado]);
+        correlacoes
+            .nfe_info
+            .extend(por_chave([item("nfe2", 1, 100.0)]));
+        correlacoes.relacoes_ctes.push(RelacaoCte::xml(
+            "cte7",
+            "cte8",
+            TipoRelacaoCte::Complementar,
+            "infCteComp",
+        ));
+
+        let grafo = GrafoCorrelacoes::new(&correlacoes, &TipoRelacaoCte::PROPAGACAO_PADRAO);
+        assert!(grafo.explicar("cte7", "nfe2").is_none());
+        assert!(grafo.explicar("cte8", "nfe2").is_none());
+        assert!(grafo.explicar("cte7", "cte8").is_none());
+    }
+
+    #[test]
+    /// `cargo test -- --show-output cte_substituido_nao_exportado`
+    fn cte_substituido_nao_exportado() {
+        const CTE_A: &str = "35250134567890123456570060000002011000002014";
+        const CTE_B: &str = "35250134567890123456570060000002021000002022";
+        const NFE_1: &str = "35250156789012345678550010000020011000020015";
+
+        let mut docs = docs_fiscais(
+            &[
+                CteXml::new(CTE_A, &[NFE_1]),
+                CteXml::new(CTE_B, &[]).substitui(CTE_A),
+            ],
+            &[NFE_1],
+        );
+        let arguments = argumentos(&[]);
+        let correlacoes = docs.get_correlations(&arguments);
+        let propagar = &arguments.propagar_nfes;
+
+        // O substituído declara a NF-e, mas as colunas exportadas a ligam apenas ao substituto
+        assert!(explicar_correlacao(&correlacoes, propagar, NFE_1, CTE_A).is_none());
+        assert_eq!(
+            explicar_correlacao(&correlacoes, propagar, CTE_B, NFE_1)
+                .unwrap()
+                .documentos(),
+            [CTE_B, CTE_A, NFE_1]
+        );
+    }
+}
@@ -0,0 +1,204 @@
+//! Documentos fiscais de exemplo compartilhados pelos testes do módulo `core`.
+//!
+//! Os CT-es e as NF-es de [`CteXml`] e [`docs_fiscais`] são montados a partir dos XMLs de
+//! exemplo da raiz do projeto, trocando apenas as chaves e as referências, e passam pelo mesmo
+//! parse dos arquivos lidos.
+//!
+//! As demais funções criam as linhas das tabelas diretamente, com a chave e os campos mais
+//! usados preenchidos; cada teste completa os campos de seu interesse.
+
+use clap::Parser;
+use std::{collections::BTreeMap, path::Path};
+
+use crate::{
+    Arguments, DocsFiscais, GetKey, InfoEvento, KeyDoc, StructExtension,
+    xml_structs::{
+        cte::{CteProc, InfoCte},
+        nfe::{InfoNfe, NfeProc},
+        nfe_documento::InfoNfeDocumento,
+        nfe_evento::InfoNfeEvento,
+    },
+};
+
+const CTE_MODELO: &str = include_str!("../../35220998765432101234567894741048320396789012_CTe.xml");
+const NFE_MODELO: &str = include_str!("../../35250199999999999999550000000000021001034139_NFe.xml");
+
+/// Chave do CT-e de exemplo: repetida no `Id`, no QR Code, no protocolo e na NF-e de `infDoc`.
+const CHAVE_CTE_MODELO: &str = "12345678901234567890123456789012345678901234";
+/// Chave do protocolo da NF-e de exemplo.
+const CHAVE_NFE_MODELO: &str = "12345678901234567890123456789012345678904444";
+
+/// Argumentos da linha de comando com os valores padrão, acrescidos de `args`.
+pub fn argumentos(args: &[&str]) -> Arguments {
+    Arguments::parse_from(["read_xml"].iter().chain(args))
+}
+
+/// CT-e autorizado montado a partir do XML de exemplo.
+#[derive(Debug, Clone, Default)]
+pub struct CteXml {
+    chave: String,
+    nfes: Vec<String>,
+    anteriores: Vec<String>,
+    substituido: Option<String>,
+}
+
+impl CteXml {
+    /// CT-e `chave` que transporta as NF-es `nfes` (`infDoc/infNFe`).
+    pub fn new(chave: &str, nfes: &[&str]) -> Self {
+        Self {
+            chave: chave.to_string(),
+            nfes: nfes.iter().map(|nfe| nfe.to_string()).collect(),
+            ..Default::default()
+        }
+    }
+
+    /// Declara o CT-e `chave` como documento anterior (`docAnt`), como no redespacho.
+    pub fn anterior(mut self, chave: &str) -> Self {
+        self.anteriores.push(chave.to_string());
+        self
+    }
+
+    /// Declara o CT-e `original` como substituído por este (`infCteSub`).
+    pub fn substitui(mut self, original: &str) -> Self {
+        self.substituido = Some(original.to_string());
+        self
+    }
+
+    /// Texto XML do CT-e.
+    pub fn xml(&self) -> String {
+        let inf_doc: String = self
+            .nfes
+            .iter()
+            .map(|nfe| format!("<infNFe><chave>{nfe}</chave></infNFe>"))
+            .collect();
+
+        let doc_ant = if self.anteriores.is_empty() {
+            String::new()
+        } else {
+            let id_doc_ant: String = self
+                .anteriores
+                .iter()
+                .map(|cte| format!("<idDocAntEle><chCTe>{cte}</chCTe></idDocAntEle>"))
+                .collect();
+            format!(
+                "<docAnt><emiDocAnt><CNPJ>78901234567890</CNPJ><IE>890123456789</IE>\
+                 <UF>SP</UF><xNome>LOGISTICA VALE VERDE</xNome>\
+                 <idDocAnt>{id_doc_ant}</idDocAnt></emiDocAnt></docAnt>"
+            )
+        };
+
+        let inf_cte_sub = self
+            .substituido
+            .as_ref()
+            .map(|original| format!("<infCteSub><chCte>{original}</chCte></infCteSub>"))
+            .unwrap_or_default();
+
+        let xml = substituir_bloco(CTE_MODELO, "infDoc", &format!("<infDoc>{inf_doc}</infDoc>"));
+        let xml = substituir_bloco(&xml, "docAnt", &doc_ant);
+        xml.replace("</infCTeNorm>", &format!("{inf_cte_sub}</infCTeNorm>"))
+            .replace(CHAVE_CTE_MODELO, &self.chave)
+    }
+}
+
+/// Texto XML da NF-e de exemplo com a chave de acesso `chave`.
+pub fn nfe_xml(chave: &str) -> String {
+    NFE_MODELO
+        .replace(CHAVE_NFE_MODELO, chave)
+        .replace(CHAVE_CTE_MODELO, chave)
+}
+
+/// Lote com os CT-es e as NF-es informados, como após a leitura dos arquivos.
+pub fn docs_fiscais(ctes: &[CteXml], nfes: &[&str]) -> DocsFiscais {
+    let arguments = argumentos(&[]);
+    let mut docs = DocsFiscais::new();
+
+    for cte in ctes {
+        let cte_proc: CteProc = quick_xml::de::from_str(&cte.xml()).expect("CT-e de exemplo");
+        let arquivo = format!("{}-cte.xml", cte.chave);
+        docs.add_information(cte_proc.get_information(Path::new(&arquivo), &arguments));
+    }
+
+    for nfe in nfes {
+        let nfe_proc: NfeProc = quick_xml::de::from_str(&nfe_xml(nfe)).expect("NF-e de exemplo");
+        let arquivo = format!("{nfe}-nfe.xml");
+        docs.add_information(nfe_proc.get_information(Path::new(&arquivo), &arguments));
+    }
+
+    docs
+}
+
+/// Linha de CT-e com a chave `chave`.
+pub fn cte(chave: &str) -> InfoCte {
+    let mut info = InfoCte::default();
+    info.cte = Some(chave.to_string());
+    info
+}
+
+/// Item `n_item` da NF-e `nfe` com o valor do produto `v_prod`.
+pub fn item(nfe: &str, n_item: u32, v_prod: f64) -> InfoNfe {
+    let mut info = InfoNfe::default();
+    info.nfe = Some(nfe.to_string());
+    info.n_item = Some(n_item);
+    info.v_prod = Some(v_prod);
+    info
+}
+
+/// Linha da NF-e `chave` na tabela de documentos.
+pub fn documento(chave: &str) -> InfoNfeDocumento {
+    let mut documento = InfoNfeDocumento::default();
+    documento.nfe = Some(chave.to_string());
+    documento
+}
+
+/// Chaves de documentos como `String`.
+pub fn chaves(chaves: &[&str]) -> Vec<String> {
+    chaves.iter().map(|chave| chave.to_string()).collect()
+}
+
+/// Agrupa as linhas pela chave do documento válido, como em `cte_info` e `nfe_info`.
+pub fn por_chave<T: GetKey>(linhas: impl IntoIterator<Item = T>) -> BTreeMap<KeyDoc, Vec<T>> {
+    let mut grupos: BTreeMap<KeyDoc, Vec<T>> = BTreeMap::new();
+    for linha in linhas {
+        let chave = linha.get_chave().expect("linha com chave");
+        grupos
+            .entry(KeyDoc::new(chave, true))
+            .or_default()
+            .push(linha);
+    }
+    grupos
+}
+
+/// Evento `tp_evento` da NF-e `nfe`, homologado pela SEFAZ.
+pub fn evento_nfe(nfe: &str, tp_evento: &str, n_seq: u32, dh_evento: &str) -> InfoNfeEvento {
+    InfoNfeEvento {
+        nfe: Some(nfe.to_string()),
+        tp_evento: Some(tp_evento.to_string()),
+        n_seq_evento: Some(n_seq),
+        dh_evento: Some(dh_evento.to_string()),
+        situacao: Some("135 - Evento registrado e vinculado a NF-e".to_string()),
+        ..Default::default()
+    }
+}
+
+/// Linha da tabela "Eventos" para o evento `tp_evento` do documento `chave`, homologado
+/// pela SEFAZ e com um protocolo próprio.
+pub fn evento(chave: &str, tp_evento: &str, n_seq: u32, dh_evento: &str) -> InfoEvento {
+    InfoEvento {
+        chave: Some(chave.to_string()),
+        documento: Some("NFe".to_string()),
+        tp_evento: Some(tp_evento.to_string()),
+        n_seq_evento: Some(n_seq),
+        dh_evento: Some(dh_evento.to_string()),
+        protocolo: Some(format!("prot-{chave}-{tp_evento}-{n_seq}")),
+        situacao: Some("135 - Evento registrado e vinculado a NF-e".to_string()),
+        ..Default::default()
+    }
+}
+
+/// Troca o primeiro elemento `<tag>...</tag>` do modelo por `novo`.
+fn substituir_bloco(xml: &str, tag: &str, novo: &str) -> String {
+    let (abertura, fechamento) = (format!("<{tag}>"), format!("</{tag}>"));
+    let inicio = xml.find(&abertura).expect("elemento do modelo");
+    let fim = xml[inicio..].find(&fechamento).expect("elemento do modelo") + inicio;
+    format!("{}{novo}{}", &xml[..inicio], &xml[fim + fechamento.len()..])
+}
@@ -2,12 +2,18 @@
 //!
 //! Modela as relações entre CT-es (subcontratações, redespachos, complementares)
 //! e mapeia a relação bipartida CT-e <-> NF-e utilizando um algoritmo de ponto fixo.
+//!
+//! Cada relação entre CT-es preserva o seu tipo ([`TipoRelacaoCte`]) e a sua fonte
+//! ([`FonteRelacao`]), de modo que apenas os tipos escolhidos em `--propagar-nfes`
+//! propagam as NF-es entre os CT-es da cadeia.
 
 use crate::xml_structs::{cte::InfoCte, nfe::InfoNfe};
+use clap::ValueEnum;
 use rayon::prelude::*;
 use std::{
     cmp::Ordering,
     collections::{BTreeMap, HashMap, HashSet},
+    fmt,
 };
 
 /// Identificador de documento fiscal para correlação indexada por validade/cancelamento.
@@ -29,6 +35,181 @@ impl KeyDoc {
     }
 }
 
+/// Tipo de relação entre dois CT-es, do ponto de vista do CT-e derivado.
+///
+/// Ex.: em uma relação `Redespacho`, o CT-e derivado é o redespacho do CT-e original.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, PartialOrd, Ord, Hash, ValueEnum)]
+pub enum TipoRelacaoCte {
+    /// CT-e complementar de valores (`infCteComp` ou evento de CT-e complementar).
+    Complementar,
+    /// CT-e de redespacho (evento de CT-e de redespacho).
+    Redespacho,
+    /// CT-e de redespacho intermediário (evento de CT-e de redespacho intermediário).
+    RedespachoIntermediario,
+    /// CT-e de subcontratação (evento de CT-e de subcontratação).
+    Subcontratacao,
+    /// CT-e de substituição (`infCteSub` ou evento de CT-e substituído).
+    Substituicao,
+    /// CT-e de anulação (`infCteAnu`).
+    Anulacao,
+    /// CT-e vinculado a um CT-e multimodal (`infServVinc` ou evento de CT-e multimodal).
+    Multimodal,
+    /// CT-e que informa o original como documento anterior de transporte (`docAnt`).
+    Anterior,
+}
+
+impl TipoRelacaoCte {
+    /// Tipos que propagam as NF-es por padrão (`--propagar-nfes`).
+    ///
+    /// Substituição e anulação ficam de fora: o CT-e substituto assume as NF-es do
+    /// substituído (ver [`GraphExtension::replace_nfes`]) e a anulação não transporta carga.
+    pub const PROPAGACAO_PADRAO: [Self; 6] = [
+        Self::Complementar,
+        Self::Redespacho,
+        Self::RedespachoIntermediario,
+        Self::Subcontratacao,
+        Self::Multimodal,
+        Self::Anterior,
+    ];
+
+    pub fn descricao(&self) -> &'static str {
+        match self {
+            Self::Complementar => "Complementar",
+            Self::Redespacho => "Redespacho",
+            Self::RedespachoIntermediario => "Redespacho Intermediário",
+            Self::Subcontratacao => "Subcontratação",
+            Self::Substituicao => "Substituição",
+            Self::Anulacao => "Anulação",
+            Self::Multimodal => "Vinculado a Multimodal",
+            Self::Anterior => "Documento Anterior",
+        }
+    }
+}
+
+/// Local em que a referência entre dois CT-es foi declarada.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, PartialOrd, Ord, Hash)]
+pub enum FonteRelacao {
+    /// Campo do XML do CT-e derivado (ex.: `infCteComp`), que referencia o original.
+    Xml(&'static str),
+    /// Evento registrado no CT-e original (ex.: `evCTeRedespacho`), que referencia o derivado.
+    Evento(&'static str),
+}
+
+impl fmt::Display for FonteRelacao {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            Self::Xml(campo) => write!(f, "XML do CTe derivado: {campo}"),
+            Self::Evento(evento) => write!(f, "Evento do CTe original: {evento}"),
+        }
+    }
+}
+
+/// Relação tipada entre dois CT-es: `derivado` é o CT-e `tipo` de `original`.
+#[derive(Debug, Clone, PartialEq, Eq, PartialOrd, Ord, Hash)]
+pub struct RelacaoCte {
+    pub original: String,
+    pub derivado: String,
+    pub tipo: TipoRelacaoCte,
+    pub fonte: FonteRelacao,
+    /// Arquivo XML (CT-e ou evento) em que a relação foi lida, se conhecido.
+    pub arquivo: Option<String>,
+}
+
+impl RelacaoCte {
+    /// Relação declarada no XML do CT-e `derivado`.
+    pub fn xml(original: &str, derivado: &str, tipo: TipoRelacaoCte, campo: &'static str) -> Self {
+        Self {
+            original: original.to_string(),
+            derivado: derivado.to_string(),
+            tipo,
+            fonte: FonteRelacao::Xml(campo),
+            arquivo: None,
+        }
+    }
+
+    /// Relação declarada em evento do CT-e `original`.
+    pub fn evento(
+        original: &str,
+        derivado: &str,
+        tipo: TipoRelacaoCte,
+        evento: &'static str,
+    ) -> Self {
+        Self {
+            original: original.to_string(),
+            derivado: derivado.to_string(),
+            tipo,
+            fonte: FonteRelacao::Evento(evento),
+            arquivo: None,
+        }
+    }
+
+    /// Registra o arquivo XML em que a relação foi lida.
+    pub fn com_arquivo(mut self, arquivo: Option<String>) -> Self {
+        self.arquivo = arquivo;
+        self
+    }
+
+    /// Compara as relações desconsiderando o arquivo de origem.
+    ///
+    /// O mesmo evento pode ter sido baixado em mais de um arquivo.
+    pub fn mesma_relacao(&self, other: &Self) -> bool {
+        (&self.original, &self.derivado, self.tipo, self.fonte)
+            == (&other.original, &other.derivado, other.tipo, other.fonte)
+    }
+
+    /// CT-e em cujo XML ou evento a referência foi declarada.
+    pub fn declarante(&self) -> &str {
+        match self.fonte {
+            FonteRelacao::Xml(_) => &self.derivado,
+            FonteRelacao::Evento(_) => &self.original,
+        }
+    }
+
+    /// CT-e referenciado pelo declarante.
+    pub fn referenciado(&self) -> &str {
+        match self.fonte {
+            FonteRelacao::Xml(_) => &self.original,
+            FonteRelacao::Evento(_) => &self.derivado,
+        }
+    }
+
+    /// Verifica se o CT-e `cte` da relação pode ligar-se ao outro CT-e.
+    ///
+    /// Basta que o CT-e esteja no lote e não tenha sido cancelado: o CT-e substituído ou
+    /// anulado, embora inválido (ver [`adicionar_substituicoes_cte`]), continua ligado aos
+    /// seus redespachos, subcontratações e complementos e às substituições encadeadas.
+    ///
+    /// [`adicionar_substituicoes_cte`]: crate::adicionar_substituicoes_cte
+    pub fn cte_disponivel(&self, cte: &str, cte_info: &BTreeMap<KeyDoc, Vec<InfoCte>>) -> bool {
+        [true, false].into_iter().any(|valido| {
+            cte_info
+                .get(&KeyDoc::new(cte, valido))
+                .is_some_and(|infos| infos.iter().any(|info| !info.is_canceled()))
+        })
+    }
+
+    /// Descrição legível da relação, para relatórios e papéis de trabalho.
+    pub fn descrever(&self) -> String {
+        let (d, o) = (&self.derivado, &self.original);
+        match self.tipo {
+            TipoRelacaoCte::Complementar => format!("CTe {d} complementa o CTe {o}"),
+            TipoRelacaoCte::Redespacho => format!("CTe {d} é o redespacho do CTe {o}"),
+            TipoRelacaoCte::RedespachoIntermediario => {
+                format!("CTe {d} é o redespacho intermediário do CTe {o}")
+            }
+            TipoRelacaoCte::Subcontratacao => format!("CTe {d} é a subcontratação do CTe {o}"),
+            TipoRelacaoCte::Substituicao => format!("CTe {d} substitui o CTe {o}"),
+            TipoRelacaoCte::Anulacao => format!("CTe {d} anula o CTe {o}"),
+            TipoRelacaoCte::Multimodal => {
+                format!("CTe {d} está vinculado ao CTe multimodal {o}")
+            }
+            TipoRelacaoCte::Anterior => {
+                format!("CTe {d} informa o CTe {o} como documento anterior")
+            }
+        }
+    }
+}
+
 /// Estrutura consolidada de correlações cruzadas de documentos fiscais.
 #[derive(Debug, Default, Clone)]
 pub struct Correlacoes {
@@ -36,6 +217,8 @@ pub struct Correlacoes {
     pub nfe_info: BTreeMap<KeyDoc, Vec<InfoNfe>>,
     pub cte_nfes: HashMap<String, HashSet<String>>,
     pub nfe_ctes: HashMap<String, HashSet<String>>,
+    /// Relações declaradas entre CT-es, sem duplicidades e sem fecho transitivo.
+    pub relacoes_ctes: Vec<RelacaoCte>,
 }
 
 /// # Estrutura Disjoint-Set Union (DSU / Union-Find)
@@ -99,7 +282,7 @@ pub struct Correlacoes {
 /// Parent:  [  2,   2,   2,   3,   4 ]  <-- Consulta instantânea O(1)!
 /// ```
 #[derive(Debug, Clone)]
-struct DisjointSet {
+pub(crate) struct DisjointSet {
     /// Aponta para o índice do elemento pai/representante do conjunto.
     parent: Vec<usize>,
     /// Altura aproximada da árvore do conjunto (usado na união por rank).
@@ -109,7 +292,7 @@ struct DisjointSet {
 impl DisjointSet {
     /// Cria uma nova floresta de conjuntos disjuntos onde cada elemento de 0..size é seu próprio líder.
     #[inline]
-    fn new(size: usize) -> Self {
+    pub(crate) fn new(size: usize) -> Self {
         Self {
             parent: (0..size).collect(),
             rank: vec![0; size],
@@ -123,7 +306,7 @@ impl DisjointSet {
     ///
     /// # Complexidade
     /// Amortizada O(alfa(N)), onde alfa é a Função Inversa de Ackermann (na prática, alfa(N) <= 4, ou seja, quase O(1)).
-    fn find(&mut self, i: usize) -> usize {
+    pub(crate) fn find(&mut self, i: usize) -> usize {
         let mut root = i;
         while root != self.parent[root] {
             root = self.parent[root];
@@ -145,7 +328,7 @@ impl DisjointSet {
     /// Utiliza a heurística de **União por Rank (Union by Rank)**, acoplando a árvore de menor
     /// profundidade sob a raiz da árvore mais profunda, mantendo a estrutura equilibrada.
     #[inline]
-    fn union(&mut self, i: usize, j: usize) {
+    pub(crate) fn union(&mut self, i: usize, j: usize) {
         let root_i = self.find(i);
         let root_j = self.find(j);
 
@@ -215,6 +398,17 @@ pub trait GraphExtension {
     /// * `cte_ctes`: Grafo previamente expandido contendo as correlações entre CT-es.
     fn expand_nfes(&mut self, cte_ctes: &Self);
 
+    /// Transfere as NF-es de cada CT-e substituído para o seu substituto.
+    ///
+    /// Aplicado após [`GraphExtension::expand_nfes`] quando a substituição não propaga NF-es:
+    /// o substituto (e os CT-es de sua cadeia em `cte_ctes`) herda as NF-es do original.
+    /// Substituições encadeadas são resolvidas até o último substituto, independentemente
+    /// da ordem dos pares. O original, já inválido, mantém as suas NF-es para consulta.
+    ///
+    /// # Parâmetros
+    /// * `substituicoes`: pares `(original, substituto)`.
+    fn replace_nfes(&mut self, substituicoes: &[(&str, &str)], cte_ctes: &Self);
+
     /// Filtra as NF-es mantendo apenas aquelas confirmadas como ativas/válidas no lote.
     fn filtrar_nfes_validos(&self, nfe_info: &BTreeMap<KeyDoc, Vec<InfoNfe>>) -> Self;
 
@@ -427,6 +621,57 @@ impl GraphExtension for HashMap<String, HashSet<String>> {
         }
     }
 
+    fn replace_nfes(&mut self, substituicoes: &[(&str, &str)], cte_ctes: &Self) {
+        let mut substitutos: HashMap<&str, Vec<&str>> = HashMap::new();
+        for &(original, substituto) in substituicoes {
+            substitutos.entry(original).or_default().push(substituto);
+        }
+
+        // Substitutos finais de cada original: em A -> B -> C, as NF-es de A e de B
+        // vão para C, qualquer que seja a ordem dos pares.
+        fn finais<'a>(
+            substitutos: &HashMap<&'a str, Vec<&'a str>>,
+            original: &'a str,
+        ) -> Vec<&'a str> {
+            let mut visitados = HashSet::from([original]);
+            let mut pendentes = vec![original];
+            let mut finais = Vec::new();
+            while let Some(cte) = pendentes.pop() {
+                match substitutos.get(cte) {
+                    Some(proximos) => pendentes.extend(
+                        proximos
+                            .iter()
+                            .copied()
+                            .filter(|&proximo| visitados.insert(proximo)),
+                    ),
+                    None => finais.push(cte),
+                }
+            }
+            finais
+        }
+
+        // As transferências são apuradas antes de aplicadas, sobre as NF-es originais.
+        let transferencias: Vec<(String, HashSet<String>)> = substitutos
+            .keys()
+            .filter_map(|&original| Some((original, self.get(original)?)))
+            .flat_map(|(original, nfes)| {
+                finais(&substitutos, original)
+                    .into_iter()
+                    .flat_map(|substituto| {
+                        let cadeia = cte_ctes.get(substituto).into_iter().flatten();
+                        cadeia.map(String::as_str).chain([substituto])
+                    })
+                    .filter(|&cte| cte != original)
+                    .map(|cte| (cte.to_string(), nfes.clone()))
+                    .collect::<Vec<_>>()
+            })
+            .collect();
+
+        for (cte, nfes) in transferencias {
+            self.entry(cte).or_default().extend(nfes);
+        }
+    }
+
     fn filtrar_nfes_validos(&self, nfe_info: &BTreeMap<KeyDoc, Vec<InfoNfe>>) -> Self {
         self.par_iter()
             .map(|(cte, nfes)| {
@@ -981,6 +1226,91 @@ mod tests_complexos_grafo {
         // Total de nós registrados no mapa final (2 + 3 + 5 + 10 = 20 nós ativos)
         assert_eq!(grafo.len(), 20);
     }
+
+    /**
+        cte_1 --(complementar)--> cte_2
+        cte_3 substitui cte_1
+
+        O complementar herda as NFes do original; o substituto também as assume, e o
+        original (já inválido) as mantém para consulta.
+
+        `cargo test -- --show-output substituicao_substitui_nfes`
+    */
+    #[test]
+    fn substituicao_substitui_nfes() {
+        let relacoes = [
+            RelacaoCte::xml("cte_1", "cte_2", TipoRelacaoCte::Complementar, "infCteComp"),
+            RelacaoCte::evento(
+                "cte_1",
+                "cte_3",
+                TipoRelacaoCte::Substituicao,
+                "evCTeSubstituido",
+            ),
+        ];
+
+        assert_eq!(relacoes[0].declarante(), "cte_2");
+        assert_eq!(relacoes[1].referenciado(), "cte_3");
+        assert_eq!(relacoes[1].descrever(), "CTe cte_3 substitui o CTe cte_1");
+
+        let propagar = TipoRelacaoCte::PROPAGACAO_PADRAO;
+        let mut cte_ctes: HashMap<String, HashSet<String>> = HashMap::new();
+        for relacao in relacoes.iter().filter(|r| propagar.contains(&r.tipo)) {
+            cte_ctes
+                .entry(relacao.declarante().to_string())
+                .or_default()
+                .insert(relacao.referenciado().to_string());
+        }
+
+        let mut cte_nfes =
+            HashMap::from([("cte_1".to_string(), HashSet::from(["nfe_a".to_string()]))]);
+
+        cte_ctes.expand_ctes(false);
+        cte_nfes.expand_nfes(&cte_ctes);
+        cte_nfes.replace_nfes(&[("cte_1", "cte_3")], &cte_ctes);
+
+        println!("cte_nfes: {cte_nfes:?}");
+
+        let nfes = HashSet::from(["nfe_a".to_string()]);
+        assert_eq!(
+            cte_nfes,
+            HashMap::from([
+                ("cte_1".to_string(), nfes.clone()),
+                ("cte_2".to_string(), nfes.clone()),
+                ("cte_3".to_string(), nfes),
+            ])
+        );
+    }
+
+    /**
+        cte_a substituído por cte_b, que por sua vez é substituído por cte_c
+
+        As NFes de cte_a e de cte_b chegam a cte_c em qualquer ordem dos pares.
+
+        `cargo test -- --show-output substituicoes_encadeadas`
+    */
+    #[test]
+    fn substituicoes_encadeadas() {
+        let cte_ctes: HashMap<String, HashSet<String>> = HashMap::new();
+        let nfes = |chaves: &[&str]| -> HashSet<String> {
+            chaves.iter().map(|chave| chave.to_string()).collect()
+        };
+
+        for pares in [
+            [("cte_a", "cte_b"), ("cte_b", "cte_c")],
+            [("cte_b", "cte_c"), ("cte_a", "cte_b")],
+        ] {
+            let mut cte_nfes = HashMap::from([
+                ("cte_a".to_string(), nfes(&["nfe_1"])),
+                ("cte_b".to_string(), nfes(&["nfe_2"])),
+            ]);
+            cte_nfes.replace_nfes(&pares, &cte_ctes);
+
+            println!("pares: {pares:?}\ncte_nfes: {cte_nfes:?}");
+
+            assert_eq!(cte_nfes["cte_c"], nfes(&["nfe_1", "nfe_2"]));
+            assert_eq!(cte_nfes["cte_a"], nfes(&["nfe_1"]));
+        }
+    }
 }
 
 /// Run tests with:
@@ -1190,3 +1520,76 @@ mod tests_graphos_comparativos {
         }
     }
 }
+
+/// Run tests with:
+/// cargo test -- --show-output tests_correlacoes_xml
+#[cfg(test)]
+mod tests_correlacoes_xml {
+    use super::*;
+    use crate::core::fixtures::{CteXml, argumentos, docs_fiscais};
+
+    const CTE_A: &str = "35250134567890123456570060000001011000001016";
+    const CTE_B: &str = "35250134567890123456570060000001021000001024";
+    const CTE_C: &str = "35250134567890123456570060000001031000001032";
+    const CTE_D: &str = "35250178901234567890570010000002011000002013";
+    const NFE_1: &str = "35250156789012345678550010000010011000010011";
+    const NFE_2: &str = "35250156789012345678550010000010021000010029";
+
+    fn ctes_da_nfe(correlacoes: &Correlacoes, nfe: &str) -> HashSet<String> {
+        correlacoes.nfe_ctes.get(nfe).cloned().unwrap_or_default()
+    }
+
+    /**
+        cte_b substitui cte_a; cte_d informa cte_a como documento anterior (redespacho)
+
+        O CTe substituído, inválido mas não cancelado, continua ligado ao redespacho.
+
+        `cargo test -- --show-output redespacho_de_cte_substituido`
+    */
+    #[test]
+    fn redespacho_de_cte_substituido() {
+        let mut docs = docs_fiscais(
+            &[
+                CteXml::new(CTE_A, &[NFE_1]),
+                CteXml::new(CTE_B, &[NFE_1]).substitui(CTE_A),
+                CteXml::new(CTE_D, &[]).anterior(CTE_A),
+            ],
+            &[NFE_1],
+        );
+
+        let correlacoes = docs.get_correlations(&argumentos(&[]));
+        println!("nfe_ctes: {:?}", correlacoes.nfe_ctes);
+
+        assert_eq!(
+            ctes_da_nfe(&correlacoes, NFE_1),
+            HashSet::from([CTE_B.to_string(), CTE_D.to_string()])
+        );
+    }
+
+    /**
+        cte_b substitui cte_a e é substituído por cte_c
+
+        As NFes de cte_a e de cte_b chegam a cte_c, qualquer que seja a ordem dos arquivos.
+
+        `cargo test -- --show-output substituicoes_encadeadas_no_lote`
+    */
+    #[test]
+    fn substituicoes_encadeadas_no_lote() {
+        let ctes = [
+            CteXml::new(CTE_A, &[NFE_1]),
+            CteXml::new(CTE_B, &[NFE_2]).substitui(CTE_A),
+            CteXml::new(CTE_C, &[]).substitui(CTE_B),
+        ];
+
+        for ordem in [[0, 1, 2], [2, 1, 0]] {
+            let mut docs = docs_fiscais(&ordem.map(|i| ctes[i].clone()), &[NFE_1, NFE_2]);
+
+            let correlacoes = docs.get_correlations(&argumentos(&[]));
+            println!("ordem: {ordem:?}\nnfe_ctes: {:?}", correlacoes.nfe_ctes);
+
+            let substituto = HashSet::from([CTE_C.to_string()]);
+            assert_eq!(ctes_da_nfe(&correlacoes, NFE_1), substituto);
+            assert_eq!(ctes_da_nfe(&correlacoes, NFE_2), substituto);
+        }
+    }
+}
@@ -0,0 +1,302 @@
+//! # Histórico de Eventos por Documento
+//!
+//! Além do cancelamento, NF-es e CT-es recebem eventos de toda ordem: carta de correção,
+//! manifestação do destinatário, EPEC, prestação do serviço em desacordo, comprovante e
+//! insucesso de entrega, GTV, MDF-e autorizado etc. Este módulo reúne todos eles na tabela
+//! "Eventos" (uma linha por evento, em ordem cronológica por chave) e resume o evento mais
+//! recente de cada documento, entre os homologados pela SEFAZ, na coluna "Último Evento".
+
+use rayon::prelude::*;
+use serde::{Deserialize, Serialize};
+use std::collections::HashMap;
+use struct_iterable::Iterable;
+
+use crate::{
+    GetKey, InfoExtension, data_hora,
+    xml_structs::{
+        cte_evento::InfoCteEvento, nfe_evento::InfoNfeEvento, ret_evento::is_situacao_homologada,
+    },
+};
+
+/// Tipos de evento da NF-e (`tpEvento`), conforme os Manuais de Orientação do Contribuinte.
+const EVENTOS_NFE: [(&str, &str); 19] = [
+    ("110110", "Carta de Correção"),
+    ("110111", "Cancelamento"),
+    ("110112", "Cancelamento por Substituição"),
+    ("110130", "Comprovante de Entrega da NF-e"),
+    ("110131", "Cancelamento do Comprovante de Entrega da NF-e"),
+    ("110140", "EPEC"),
+    ("110192", "Insucesso na Entrega da NF-e"),
+    ("110193", "Cancelamento do Insucesso na Entrega da NF-e"),
+    ("210200", "Confirmação da Operação"),
+    ("210210", "Ciência da Operação"),
+    ("210220", "Desconhecimento da Operação"),
+    ("210240", "Operação não Realizada"),
+    ("610500", "Registro de Passagem"),
+    ("610600", "CT-e Autorizado"),
+    ("610601", "CT-e Cancelado"),
+    ("610610", "MDF-e Autorizado"),
+    ("610611", "MDF-e Cancelado"),
+    ("610614", "MDF-e Autorizado com CT-e"),
+    ("610615", "Cancelamento de MDF-e Autorizado com CT-e"),
+];
+
+/// Tipos de evento do CT-e (`tpEvento`), conforme o Manual de Orientação do Contribuinte.
+const EVENTOS_CTE: [(&str, &str); 21] = [
+    ("110110", "Carta de Correção"),
+    ("110111", "Cancelamento"),
+    ("110113", "EPEC"),
+    ("110160", "Registro Multimodal"),
+    ("110170", "Informações da GTV"),
+    ("110180", "Comprovante de Entrega do CT-e"),
+    ("110181", "Cancelamento do Comprovante de Entrega do CT-e"),
+    ("110190", "Insucesso na Entrega do CT-e"),
+    ("110191", "Cancelamento do Insucesso na Entrega do CT-e"),
+    ("240130", "Autorizado CT-e Complementar"),
+    ("240131", "Cancelado CT-e Complementar"),
+    ("240140", "CT-e de Substituição"),
+    ("240150", "CT-e de Anulação"),
+    ("240160", "Vinculado a CT-e Multimodal"),
+    ("240170", "Liberação de Prazo de Cancelamento"),
+    ("310610", "MDF-e Autorizado"),
+    ("310611", "MDF-e Cancelado"),
+    ("310620", "Registro de Passagem"),
+    ("510620", "Registro de Passagem Automático"),
+    ("610110", "Prestação do Serviço em Desacordo"),
+    (
+        "610111",
+        "Cancelamento da Prestação do Serviço em Desacordo",
+    ),
+];
+
+/// Linha da tabela "Eventos": um evento de NF-e ou CT-e.
+#[derive(Debug, Default, Serialize, Deserialize, Clone, Iterable)]
+pub struct InfoEvento {
+    #[serde(rename = "Chave do Documento Fiscal", default)]
+    pub chave: Option<String>,
+
+    /// "NFe" ou "CTe".
+    #[serde(rename = "Tipo de Documento", default)]
+    pub documento: Option<String>,
+
+    #[serde(rename = "Código do Evento", default)]
+    pub tp_evento: Option<String>,
+
+    /// Descrição padronizada pelo código do evento ou, se desconhecido, o `descEvento` do XML.
+    #[serde(rename = "Descrição do Evento", default)]
+    pub descricao: Option<String>,
+
+    #[serde(rename = "Nº Sequencial do Evento", default)]
+    pub n_seq_evento: Option<u32>,
+
+    #[serde(rename = "Data e Hora do Evento", default)]
+    pub dh_evento: Option<String>,
+
+    #[serde(rename = "Protocolo do Evento", default)]
+    pub protocolo: Option<String>,
+
+    /// Retorno da SEFAZ: `cStat - xMotivo`.
+    #[serde(rename = "Situação do Registro", default)]
+    pub situacao: Option<String>,
+
+    #[serde(rename = "Detalhe do Evento", default)]
+    pub detalhe: Option<String>,
+}
+
+impl InfoExtension for InfoEvento {}
+
+impl GetKey for InfoEvento {
+    fn get_chave(&self) -> Option<String> {
+        self.chave.clone()
+    }
+}
+
+impl InfoEvento {
+    /// Verifica se o evento foi registrado pela SEFAZ (`cStat` 135 ou 136).
+    pub fn is_homologado(&self) -> bool {
+        is_situacao_homologada(self.situacao.as_deref())
+    }
+
+    /// Resumo para a coluna "Último Evento": `AAAA-MM-DD: código - descrição`.
+    pub fn resumir(&self) -> String {
+        let data = self.dh_evento.as_deref().and_then(|dh| dh.get(..10));
+        let evento = [self.tp_evento.as_deref(), self.descricao.as_deref()]
+            .into_iter()
+            .flatten()
+            .collect::<Vec<_>>()
+            .join(" - ");
+        match data {
+            Some(data) => format!("{data}: {evento}"),
+            None => evento,
+        }
+    }
+}
+
+impl From<&InfoNfeEvento> for InfoEvento {
+    fn from(evento: &InfoNfeEvento) -> Self {
+        Self {
+            chave: evento.nfe.clone(),
+            documento: Some("NFe".to_string()),
+            tp_evento: evento.tp_evento.clone(),
+            descricao: descrever_evento(&evento.tp_evento, &evento.desc_evento, &EVENTOS_NFE),
+            n_seq_evento: evento.n_seq_evento,
+            dh_evento: evento.dh_evento.clone(),
+            protocolo: evento.protocolo.clone(),
+            situacao: evento.situacao.clone(),
+            detalhe: evento.detalhe.clone(),
+        }
+    }
+}
+
+impl From<&InfoCteEvento> for InfoEvento {
+    fn from(evento: &InfoCteEvento) -> Self {
+        Self {
+            chave: evento.cte.clone(),
+            documento: Some("CTe".to_string()),
+            tp_evento: evento.tp_evento.clone(),
+            descricao: descrever_evento(&evento.tp_evento, &evento.desc_evento, &EVENTOS_CTE),
+            n_seq_evento: evento.n_seq_evento,
+            dh_evento: evento.dh_evento.clone(),
+            protocolo: evento.protocolo.clone(),
+            situacao: evento.situacao.clone(),
+            detalhe: evento.detalhe.clone(),
+        }
+    }
+}
+
+fn descrever_evento(
+    tp_evento: &Option<String>,
+    desc_evento: &Option<String>,
+    tabela: &[(&str, &str)],
+) -> Option<String> {
+    tp_evento
+        .as_deref()
+        .and_then(|codigo| tabela.iter().find(|(cod, _)| *cod == codigo))
+        .map(|(_, descricao)| descricao.to_string())
+        .or_else(|| desc_evento.clone())
+}
+
+/// Tabela "Eventos": todos os eventos de NF-es e CT-es, sem duplicidades.
+///
+/// Ordenada por chave e, dentro de cada documento, por data/hora (convertida para UTC) e
+/// número sequencial. O mesmo evento baixado em mais de um arquivo aparece uma única vez.
+pub fn get_historico_eventos(
+    eventos_nfe: &[InfoNfeEvento],
+    eventos_cte: &[InfoCteEvento],
+) -> Vec<InfoEvento> {
+    let mut eventos: Vec<InfoEvento> = eventos_nfe
+        .par_iter()
+        .map(InfoEvento::from)
+        .chain(eventos_cte.par_iter().map(InfoEvento::from))
+        .filter(|evento| evento.chave.is_some())
+        .collect();
+
+    eventos.par_sort_by_cached_key(|evento| {
+        (
+            evento.chave.clone(),
+            evento.dh_evento.as_deref().and_then(data_hora),
+            evento.n_seq_evento,
+            evento.tp_evento.clone(),
+        )
+    });
+    eventos.dedup_by(|a, b| {
+        (&a.chave, &a.tp_evento, a.n_seq_evento, &a.protocolo)
+            == (&b.chave, &b.tp_evento, b.n_seq_evento, &b.protocolo)
+    });
+    eventos
+}
+
+/// Resumo do evento homologado mais recente de cada documento (coluna "Último Evento").
+///
+/// Eventos rejeitados ou sem retorno da SEFAZ são ignorados.
+/// Requer `eventos` na ordem de [`get_historico_eventos`].
+pub fn get_ultimos_eventos(eventos: &[InfoEvento]) -> HashMap<&str, String> {
+    eventos
+        .iter()
+        .filter(|evento| evento.is_homologado())
+        .filter_map(|evento| {
+            evento
+                .chave
+                .as_deref()
+                .map(|chave| (chave, evento.resumir()))
+        })
+        .collect()
+}
+
+//----------------------------------------------------------------------------//
+//                                   Tests                                    //
+//----------------------------------------------------------------------------//
+
+/// Run tests with:
+/// cargo test -- --show-output tests_historico_eventos
+#[cfg(test)]
+mod tests_historico_eventos {
+    use super::*;
+
+    fn evento_nfe(tp_evento: &str, n_seq: u32, dh_evento: &str) -> InfoNfeEvento {
+        InfoNfeEvento {
+            nfe: Some("nfe1".to_string()),
+            tp_evento: Some(tp_evento.to_string()),
+            desc_evento: Some("Descrição do XML".to_string()),
+            n_seq_evento: Some(n_seq),
+            dh_evento: Some(dh_evento.to_string()),
+            protocolo: Some(format!("prot-{tp_evento}-{n_seq}")),
+            situacao: Some("135 - Evento registrado e vinculado a NF-e".to_string()),
+            ..Default::default()
+        }
+    }
+
+    #[test]
+    /// `cargo test -- --show-output linha_do_tempo`
+    fn linha_do_tempo() {
+        let cce = evento_nfe("110110", 1, "2024-03-05T10:00:00-03:00");
+        let mut rejeitado = evento_nfe("110110", 3, "2024-03-07T09:00:00-03:00");
+        rejeitado.situacao = Some("573 - Rejeição: Duplicidade de Evento".to_string());
+
+        let eventos_nfe = vec![
+            evento_nfe("210210", 1, "2024-03-02T08:00:00-03:00"),
+            evento_nfe("110110", 2, "2024-03-06T09:00:00-03:00"),
+            // 10:00 UTC: anterior às 09:00 de Brasília (12:00 UTC) do mesmo dia
+            evento_nfe("210200", 1, "2024-03-06T10:00:00+00:00"),
+            rejeitado,
+            cce.clone(),
+            cce, // mesmo evento em outro arquivo
+        ];
+        let eventos_cte = vec![InfoCteEvento {
+            cte: Some("cte1".to_string()),
+            tp_evento: Some("999999".to_string()),
+            desc_evento: Some("Evento Novo".to_string()),
+            n_seq_evento: Some(1),
+            situacao: Some("135 - Evento registrado e vinculado ao CT-e".to_string()),
+            ..Default::default()
+        }];
+
+        let eventos = get_historico_eventos(&eventos_nfe, &eventos_cte);
+        println!("eventos: {eventos:#?}");
+
+        let sequencia: Vec<(&str, Option<u32>)> = eventos
+            .iter()
+            .map(|e| (e.tp_evento.as_deref().unwrap(), e.n_seq_evento))
+            .collect();
+        assert_eq!(
+            sequencia,
+            [
+                ("999999", Some(1)),
+                ("210210", Some(1)),
+                ("110110", Some(1)),
+                ("210200", Some(1)),
+                ("110110", Some(2)),
+                ("110110", Some(3)),
+            ]
+        );
+
+        // Código desconhecido: mantém a descrição do XML
+        assert_eq!(eventos[0].descricao.as_deref(), Some("Evento Novo"));
+        assert_eq!(eventos[1].descricao.as_deref(), Some("Ciência da Operação"));
+
+        // O evento rejeitado não é o último evento da NF-e
+        let ultimos = get_ultimos_eventos(&eventos);
+        assert_eq!(ultimos["nfe1"], "2024-03-06: 110110 - Carta de Correção");
+        assert_eq!(ultimos["cte1"], "999999 - Evento Novo");
+    }
+}
@@ -0,0 +1,305 @@
+//! # Inconsistências de Eventos
+//!
+//! Os eventos são vinculados aos documentos pela chave de acesso: sem a NF-e ou o CT-e
+//! no lote, [`adicionar_eventos_nfe`](crate::adicionar_eventos_nfe) e
+//! [`adicionar_eventos_cte`](crate::adicionar_eventos_cte) os ignoram em silêncio.
+//! Este módulo percorre a tabela "Eventos" e reúne na tabela "Eventos — inconsistências":
+//! - eventos órfãos, cujo documento não está no lote;
+//! - sequência irregular de `nSeqEvento` (por documento e tipo de evento): número em
+//!   duplicidade, lacuna ou data/hora anterior à do número precedente;
+//! - eventos com data anterior à emissão do documento;
+//! - eventos sem registro homologado pela SEFAZ (`cStat` do `retEvento` diferente de 135 e 136).
+//!
+//! Uma lacuna pode indicar apenas que o evento intermediário não foi baixado.
+
+use chrono::NaiveDate;
+use rayon::prelude::*;
+use serde::{Deserialize, Serialize};
+use std::collections::HashMap;
+use struct_iterable::Iterable;
+
+use crate::{GetKey, InfoEvento, InfoExtension, data_hora, get_naive_date_from_yyyy_mm_dd};
+
+const ORFAO: &str = "Evento Órfão";
+const SEQUENCIA: &str = "Sequência Irregular";
+const ANTERIOR_EMISSAO: &str = "Anterior à Emissão";
+const NAO_HOMOLOGADO: &str = "Não Homologado";
+
+/// Linha da tabela "Eventos — inconsistências": um evento e a inconsistência encontrada.
+#[derive(Debug, Default, Serialize, Deserialize, Clone, Iterable)]
+pub struct InfoInconsistenciaEvento {
+    #[serde(rename = "Chave do Documento Fiscal", default)]
+    pub chave: Option<String>,
+
+    /// "NFe" ou "CTe".
+    #[serde(rename = "Tipo de Documento", default)]
+    pub documento: Option<String>,
+
+    #[serde(rename = "Código do Evento", default)]
+    pub tp_evento: Option<String>,
+
+    #[serde(rename = "Descrição do Evento", default)]
+    pub descricao: Option<String>,
+
+    #[serde(rename = "Nº Sequencial do Evento", default)]
+    pub n_seq_evento: Option<u32>,
+
+    #[serde(rename = "Data e Hora do Evento", default)]
+    pub dh_evento: Option<String>,
+
+    #[serde(rename = "Protocolo do Evento", default)]
+    pub protocolo: Option<String>,
+
+    #[serde(rename = "Situação do Registro", default)]
+    pub situacao: Option<String>,
+
+    /// Evento Órfão, Sequência Irregular, Anterior à Emissão ou Não Homologado.
+    #[serde(rename = "Tipo de Inconsistência", default)]
+    pub tipo: Option<String>,
+
+    #[serde(rename = "Detalhe da Inconsistência", default)]
+    pub detalhe: Option<String>,
+}
+
+impl InfoExtension for InfoInconsistenciaEvento {}
+
+impl GetKey for InfoInconsistenciaEvento {
+    fn get_chave(&self) -> Option<String> {
+        self.chave.clone()
+    }
+}
+
+impl InfoInconsistenciaEvento {
+    fn new(evento: &InfoEvento, tipo: &str, detalhe: String) -> Self {
+        Self {
+            chave: evento.chave.clone(),
+            documento: evento.documento.clone(),
+            tp_evento: evento.tp_evento.clone(),
+            descricao: evento.descricao.clone(),
+            n_seq_evento: evento.n_seq_evento,
+            dh_evento: evento.dh_evento.clone(),
+            protocolo: evento.protocolo.clone(),
+            situacao: evento.situacao.clone(),
+            tipo: Some(tipo.to_string()),
+            detalhe: Some(detalhe),
+        }
+    }
+}
+
+/// Verifica a sequência `nSeqEvento` dos eventos homologados de um documento e tipo.
+fn verificar_sequencia(eventos: &[&InfoEvento]) -> Vec<InfoInconsistenciaEvento> {
+    let mut sequencia: Vec<(u32, &InfoEvento)> = eventos
+        .iter()
+        .filter_map(|evento| evento.n_seq_evento.map(|n_seq| (n_seq, *evento)))
+        .collect();
+    sequencia.sort_by_key(|(n_seq, _)| *n_seq);
+
+    let mut inconsistencias = Vec::new();
+    let mut anterior: Option<(u32, &InfoEvento)> = None;
+
+    for (n_seq, evento) in sequencia {
+        let mut registrar = |detalhe| {
+            inconsistencias.push(InfoInconsistenciaEvento::new(evento, SEQUENCIA, detalhe))
+        };
+
+        match anterior {
+            None if n_seq != 1 => registrar(format!("Sequência iniciada no nSeqEvento {n_seq}")),
+            Some((n_ant, _)) if n_seq == n_ant => {
+                registrar(format!("nSeqEvento {n_seq} em duplicidade"))
+            }
+            Some((n_ant, _)) if n_seq > n_ant + 1 => registrar(format!(
+                "Lacuna na sequência: nSeqEvento {n_seq} após o {n_ant}"
+            )),
+            _ => (),
+        }
+
+        if let Some((n_ant, evento_ant)) = anterior
+            && n_seq > n_ant
+            && let (Some(dh), Some(dh_ant)) = (
+                evento.dh_evento.as_deref().and_then(data_hora),
+                evento_ant.dh_evento.as_deref().and_then(data_hora),
+            )
+            && dh < dh_ant
+        {
+            registrar(format!("Data e hora anteriores às do nSeqEvento {n_ant}"));
+        }
+
+        anterior = Some((n_seq, evento));
+    }
+
+    inconsistencias
+}
+
+/// Tabela "Eventos — inconsistências".
+///
+/// `emissoes` associa a chave de cada NF-e e CT-e do lote à sua data de emissão.
+/// A tabela segue a ordem de `eventos`
+/// (ver [`get_historico_eventos`](crate::get_historico_eventos)).
+pub fn get_inconsistencias_eventos(
+    eventos: &[InfoEvento],
+    emissoes: &HashMap<&str, Option<NaiveDate>>,
+) -> Vec<InfoInconsistenciaEvento> {
+    let mut inconsistencias: Vec<InfoInconsistenciaEvento> = eventos
+        .par_iter()
+        .flat_map_iter(|evento| {
+            let mut linhas = Vec::new();
+            let chave = evento.chave.as_deref().unwrap_or_default();
+
+            match emissoes.get(chave) {
+                None => linhas.push(InfoInconsistenciaEvento::new(
+                    evento,
+                    ORFAO,
+                    format!(
+                        "{} ausente do lote",
+                        evento.documento.as_deref().unwrap_or("Documento")
+                    ),
+                )),
+                Some(Some(emissao)) => {
+                    if let Some(data) = get_naive_date_from_yyyy_mm_dd(&evento.dh_evento)
+                        && data < *emissao
+                    {
+                        linhas.push(InfoInconsistenciaEvento::new(
+                            evento,
+                            ANTERIOR_EMISSAO,
+                            format!("Evento em {data}, emissão em {emissao}"),
+                        ));
+                    }
+                }
+                Some(None) => (),
+            }
+
+            if !evento.is_homologado() {
+                let detalhe = match evento.situacao.as_deref() {
+                    Some(situacao) => format!("Retorno da SEFAZ: {situacao}"),
+                    None => "Evento sem retorno (retEvento) da SEFAZ".to_string(),
+                };
+                linhas.push(InfoInconsistenciaEvento::new(
+                    evento,
+                    NAO_HOMOLOGADO,
+                    detalhe,
+                ));
+            }
+
+            linhas
+        })
+        .collect();
+
+    // Eventos homologados agrupados por documento e tipo de evento
+    let mut grupos: HashMap<(&str, &str), Vec<&InfoEvento>> = HashMap::new();
+    for evento in eventos.iter().filter(|evento| evento.is_homologado()) {
+        if let (Some(chave), Some(tp_evento)) = (&evento.chave, &evento.tp_evento) {
+            grupos.entry((chave, tp_evento)).or_default().push(evento);
+        }
+    }
+    inconsistencias.par_extend(
+        grupos
+            .par_iter()
+            .flat_map_iter(|(_, eventos)| verificar_sequencia(eventos)),
+    );
+
+    inconsistencias.par_sort_by(|a, b| {
+        (
+            &a.chave,
+            &a.dh_evento,
+            a.n_seq_evento,
+            &a.tp_evento,
+            &a.tipo,
+        )
+            .cmp(&(
+                &b.chave,
+                &b.dh_evento,
+                b.n_seq_evento,
+                &b.tp_evento,
+                &b.tipo,
+            ))
+    });
+    inconsistencias
+}
+
+//----------------------------------------------------------------------------//
+//                                   Tests                                    //
+//----------------------------------------------------------------------------//
+
+/// Run tests with:
+/// cargo test -- --show-output tests_inconsistencias_eventos
+#[cfg(test)]
+mod tests_inconsistencias_eventos {
+    use super::*;
+    use crate::core::fixtures::evento;
+
+    fn tipos(inconsistencias: &[InfoInconsistenciaEvento], chave: &str) -> Vec<String> {
+        inconsistencias
+            .iter()
+            .filter(|linha| linha.chave.as_deref() == Some(chave))
+            .map(|linha| {
+                format!(
+                    "{}: {}",
+                    linha.tipo.as_deref().unwrap_or_default(),
+                    linha.detalhe.as_deref().unwrap_or_default()
+                )
+            })
+            .collect()
+    }
+
+    #[test]
+    /// `cargo test -- --show-output sequencia_orfaos_e_homologacao`
+    fn sequencia_orfaos_e_homologacao() {
+        let mut rejeitado = evento("nfe1", "110110", 4, "2024-03-09T10:00:00-03:00");
+        rejeitado.situacao = Some("573 - Rejeição: Duplicidade de Evento".to_string());
+
+        let mut repetido = evento("nfe1", "110110", 2, "2024-03-07T10:00:00-03:00");
+        repetido.protocolo = Some("outro".to_string());
+
+        let eventos = vec![
+            // nfe1: CC-e 1, 2, 2 (repetido), 5 (lacuna) com data anterior à do 2
+            evento("nfe1", "110110", 1, "2024-03-05T10:00:00-03:00"),
+            evento("nfe1", "110110", 5, "2024-03-05T11:00:00-03:00"),
+            evento("nfe1", "110110", 2, "2024-03-06T10:00:00-03:00"),
+            repetido,
+            rejeitado,
+            // nfe2: evento anterior à emissão
+            evento("nfe2", "210210", 1, "2024-02-28T23:00:00-03:00"),
+            // nfe9: fora do lote
+            evento("nfe9", "110111", 1, "2024-03-01T10:00:00-03:00"),
+        ];
+
+        let emissoes: HashMap<&str, Option<NaiveDate>> = HashMap::from([
+            ("nfe1", NaiveDate::from_ymd_opt(2024, 3, 1)),
+            ("nfe2", NaiveDate::from_ymd_opt(2024, 3, 1)),
+        ]);
+
+        let inconsistencias = get_inconsistencias_eventos(&eventos, &emissoes);
+        println!("inconsistencias: {inconsistencias:#?}");
+
+        assert_eq!(
+            tipos(&inconsistencias, "nfe1"),
+            [
+                "Sequência Irregular: Lacuna na sequência: nSeqEvento 5 após o 2",
+                "Sequência Irregular: Data e hora anteriores às do nSeqEvento 2",
+                "Sequência Irregular: nSeqEvento 2 em duplicidade",
+                "Não Homologado: Retorno da SEFAZ: 573 - Rejeição: Duplicidade de Evento",
+            ]
+        );
+        assert_eq!(
+            tipos(&inconsistencias, "nfe2"),
+            ["Anterior à Emissão: Evento em 2024-02-28, emissão em 2024-03-01"]
+        );
+        assert_eq!(
+            tipos(&inconsistencias, "nfe9"),
+            ["Evento Órfão: NFe ausente do lote"]
+        );
+    }
+
+    #[test]
+    /// `cargo test -- --show-output sequencia_regular`
+    fn sequencia_regular() {
+        let eventos = vec![
+            evento("cte1", "110110", 1, "2024-03-05T10:00:00-03:00"),
+            evento("cte1", "110110", 2, "2024-03-06T10:00:00-03:00"),
+            evento("cte1", "110180", 1, "2024-03-07T10:00:00-03:00"),
+        ];
+        let emissoes = HashMap::from([("cte1", NaiveDate::from_ymd_opt(2024, 3, 5))]);
+
+        assert!(get_inconsistencias_eventos(&eventos, &emissoes).is_empty());
+    }
+}
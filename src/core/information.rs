@@ -18,7 +18,7 @@ use crate::{
         cte::{CteProc, InfoCte},
         cte_evento::{InfoCteEvento, ProcEventoCte},
         efinanceira::{EFinanceira, InfoEFinanceira},
-        nfe::{InfoNfe, NfeProc},
+        nfe::{NfeProc, TabelasNfe},
         nfe_evento::{InfoNfeEvento, ProcEventoNfe},
     },
 };
@@ -27,7 +27,7 @@ use crate::{
 #[derive(Debug, Default, Serialize, Deserialize, Clone)]
 pub enum Information {
     Cte(Box<InfoCte>),
-    Nfe(Vec<InfoNfe>),
+    Nfe(Box<TabelasNfe>),
     EventoCte(Box<InfoCteEvento>),
     EventoNfe(Box<InfoNfeEvento>),
     CancelamentoCte(Box<InfoCteCancel>),
@@ -73,7 +73,13 @@ impl Information {
     pub fn add_info_to_docs_fiscais(&self, docs_fiscais: &mut DocsFiscais) {
         match self {
             Self::Cte(info_cte) => docs_fiscais.ctes.push(*info_cte.clone()),
-            Self::Nfe(info_nfe) => docs_fiscais.nfes.extend(info_nfe.clone()),
+            Self::Nfe(tabelas) => {
+                docs_fiscais.nfes.extend(tabelas.itens.clone());
+                docs_fiscais.nfes_documentos.push(tabelas.documento.clone());
+                docs_fiscais.pagamentos.extend(tabelas.pagamentos.clone());
+                docs_fiscais.duplicatas.extend(tabelas.duplicatas.clone());
+                docs_fiscais.volumes.extend(tabelas.volumes.clone());
+            }
             Self::EventoCte(info_cte_evento) => {
                 docs_fiscais.eventos_cte.push(*info_cte_evento.clone())
             }
@@ -0,0 +1,242 @@
+//! # Manifestação do Destinatário
+//!
+//! O destinatário de uma NF-e registra a sua posição sobre a operação por eventos:
+//! Ciência da Operação (210210), que não é conclusiva, e as manifestações conclusivas
+//! Confirmação (210200), Desconhecimento (210220) e Operação não Realizada (210240).
+//!
+//! O estado final de cada NF-e é a manifestação conclusiva mais recente ou, na sua
+//! ausência, a ciência, considerados apenas os eventos homologados pela SEFAZ.
+//! NF-es com "Operação não Realizada" que estejam correlacionadas a CT-es recebem um
+//! alerta: houve frete para uma operação que o destinatário nega.
+
+use chrono::NaiveDateTime;
+use rayon::prelude::*;
+use std::collections::HashMap;
+
+use crate::{
+    cnpj_pertence_a, data_hora, descrever_codigo,
+    xml_structs::{nfe_documento::InfoNfeDocumento, nfe_evento::InfoNfeEvento},
+};
+
+const CIENCIA: &str = "210210";
+const NAO_REALIZADA: &str = "210240";
+
+/// Eventos de manifestação do destinatário (`tpEvento`).
+const MANIFESTACOES: [(&str, &str); 4] = [
+    ("210200", "Confirmação da Operação"),
+    ("210210", "Ciência da Operação"),
+    ("210220", "Desconhecimento da Operação"),
+    ("210240", "Operação não Realizada"),
+];
+
+/// Eventos de manifestação de uma NF-e.
+#[derive(Debug, Default)]
+struct Manifestacoes<'a> {
+    /// Primeira Ciência da Operação.
+    ciencia: Option<&'a InfoNfeEvento>,
+    /// Manifestação conclusiva mais recente.
+    conclusiva: Option<&'a InfoNfeEvento>,
+}
+
+impl<'a> Manifestacoes<'a> {
+    fn registrar(&mut self, evento: &'a InfoNfeEvento) {
+        if evento.tp_evento.as_deref() == Some(CIENCIA) {
+            if self
+                .ciencia
+                .is_none_or(|atual| ordem(evento) < ordem(atual))
+            {
+                self.ciencia = Some(evento);
+            }
+        } else if self
+            .conclusiva
+            .is_none_or(|atual| ordem(evento) > ordem(atual))
+        {
+            self.conclusiva = Some(evento);
+        }
+    }
+
+    fn estado(&self) -> Option<&'a InfoNfeEvento> {
+        self.conclusiva.or(self.ciencia)
+    }
+}
+
+/// Ordem cronológica dos eventos de uma mesma NF-e, com a data e hora convertidas para UTC.
+fn ordem(evento: &InfoNfeEvento) -> (Option<NaiveDateTime>, Option<u32>) {
+    (
+        evento.dh_evento.as_deref().and_then(data_hora),
+        evento.n_seq_evento,
+    )
+}
+
+fn is_manifestacao(evento: &InfoNfeEvento) -> bool {
+    evento
+        .tp_evento
+        .as_deref()
+        .is_some_and(|tp| MANIFESTACOES.iter().any(|(cod, _)| *cod == tp))
+}
+
+/// Preenche as colunas de manifestação do destinatário de cada NF-e.
+///
+/// Eventos rejeitados ou sem retorno da SEFAZ são ignorados. As colunas são preenchidas
+/// para todo o lote; a restrição à empresa auditada fica a cargo de [`restringir_manifestacao`].
+///
+/// Requer a coluna de CTes já preenchida para o alerta de "Operação não Realizada".
+pub fn adicionar_manifestacoes(documentos: &mut [InfoNfeDocumento], eventos_nfe: &[InfoNfeEvento]) {
+    let mut manifestacoes: HashMap<&str, Manifestacoes> = HashMap::new();
+    for evento in eventos_nfe
+        .iter()
+        .filter(|evento| is_manifestacao(evento) && evento.is_homologado())
+    {
+        if let Some(nfe) = evento.nfe.as_deref() {
+            manifestacoes.entry(nfe).or_default().registrar(evento);
+        }
+    }
+
+    documentos.par_iter_mut().for_each(|documento| {
+        let Some(manifestacao) = documento
+            .nfe
+            .as_deref()
+            .and_then(|nfe| manifestacoes.get(nfe))
+        else {
+            return;
+        };
+
+        let estado = manifestacao.estado();
+        documento.manifestacao =
+            estado.and_then(|evento| descrever_codigo(&evento.tp_evento, &MANIFESTACOES));
+        documento.dh_manifestacao = estado.and_then(|evento| evento.dh_evento.clone());
+        documento.dh_ciencia = manifestacao
+            .ciencia
+            .and_then(|evento| evento.dh_evento.clone());
+
+        let nao_realizada = estado.is_some_and(|e| e.tp_evento.as_deref() == Some(NAO_REALIZADA));
+        documento.alerta_manifestacao = (nao_realizada && !documento.ctes.is_empty()).then(|| {
+            format!(
+                "Operação não Realizada, mas correlacionada a {} CTe(s)",
+                documento.ctes.len()
+            )
+        });
+    });
+}
+
+/// Limpa as colunas de manifestação das NF-es que não são destinadas à empresa de CNPJ base.
+///
+/// Apenas o destinatário manifesta a operação: para os demais participantes os eventos
+/// não dizem respeito à empresa auditada.
+pub fn restringir_manifestacao(documento: &mut InfoNfeDocumento, cnpj_base: &str) {
+    if !cnpj_pertence_a(&documento.destinatario_cnpj, cnpj_base) {
+        documento.manifestacao = None;
+        documento.dh_manifestacao = None;
+        documento.dh_ciencia = None;
+        documento.alerta_manifestacao = None;
+    }
+}
+
+//----------------------------------------------------------------------------//
+//                                   Tests                                    //
+//----------------------------------------------------------------------------//
+
+/// Run tests with:
+/// cargo test -- --show-output tests_manifestacao
+#[cfg(test)]
+mod tests_manifestacao {
+    use super::*;
+    use crate::core::fixtures::{self, documento};
+
+    fn evento(nfe: &str, tp_evento: &str, dh_evento: &str) -> InfoNfeEvento {
+        fixtures::evento_nfe(nfe, tp_evento, 1, dh_evento)
+    }
+
+    #[test]
+    /// `cargo test -- --show-output estado_final_e_alerta`
+    fn estado_final_e_alerta() {
+        let mut rejeitado = evento("nfe2", "210220", "2024-05-04T10:00:00-03:00");
+        rejeitado.situacao = Some("596 - Rejeição: Evento apresentado fora do prazo".to_string());
+
+        let eventos_nfe = vec![
+            evento("nfe1", "210210", "2024-05-02T10:00:00-03:00"),
+            evento("nfe1", "210200", "2024-05-10T10:00:00-03:00"),
+            // 12:00 UTC: anterior à confirmação das 10:00 de Brasília (13:00 UTC)
+            evento("nfe1", "210220", "2024-05-10T12:00:00+00:00"),
+            evento("nfe1", "210210", "2024-05-01T10:00:00-03:00"),
+            evento("nfe2", "210210", "2024-05-03T10:00:00-03:00"),
+            rejeitado,
+            evento("nfe3", "210240", "2024-05-04T10:00:00-03:00"),
+            evento("nfe3", "110110", "2024-05-05T10:00:00-03:00"),
+        ];
+        let mut documentos: Vec<InfoNfeDocumento> = ["nfe1", "nfe2", "nfe3", "nfe4"]
+            .into_iter()
+            .map(documento)
+            .collect();
+        documentos[2].ctes = vec!["cte1".to_string()];
+
+        adicionar_manifestacoes(&mut documentos, &eventos_nfe);
+        println!("documentos: {documentos:#?}");
+
+        assert_eq!(
+            documentos[0].manifestacao.as_deref(),
+            Some("210200 - Confirmação da Operação")
+        );
+        assert_eq!(
+            documentos[0].dh_ciencia.as_deref(),
+            Some("2024-05-01T10:00:00-03:00")
+        );
+        assert_eq!(
+            documentos[1].manifestacao.as_deref(),
+            Some("210210 - Ciência da Operação")
+        );
+        assert_eq!(
+            documentos[2].alerta_manifestacao.as_deref(),
+            Some("Operação não Realizada, mas correlacionada a 1 CTe(s)")
+        );
+        assert_eq!(documentos[3].manifestacao, None);
+        assert!(documentos[0].alerta_manifestacao.is_none());
+    }
+
+    #[test]
+    /// `cargo test -- --show-output apenas_nfes_destinadas_a_empresa`
+    fn apenas_nfes_destinadas_a_empresa() {
+        let eventos_nfe = vec![
+            evento("nfe1", "210200", "2024-05-10T10:00:00-03:00"),
+            evento("nfe2", "210200", "2024-05-10T10:00:00-03:00"),
+        ];
+        let mut documentos: Vec<InfoNfeDocumento> =
+            [("nfe1", "12345678000199"), ("nfe2", "98765432000110")]
+                .into_iter()
+                .map(|(nfe, destinatario)| {
+                    let mut documento = documento(nfe);
+                    documento.destinatario_cnpj = Some(destinatario.to_string());
+                    documento
+                })
+                .collect();
+
+        adicionar_manifestacoes(&mut documentos, &eventos_nfe);
+
+        // As colunas são calculadas para todo o lote, independentemente da empresa
+        assert!(
+            documentos
+                .iter()
+                .all(|documento| documento.manifestacao.is_some())
+        );
+
+        let mut empresa = documentos.clone();
+        empresa
+            .iter_mut()
+            .for_each(|documento| restringir_manifestacao(documento, "12345678"));
+        assert_eq!(
+            empresa[0].manifestacao.as_deref(),
+            Some("210200 - Confirmação da Operação")
+        );
+        assert_eq!(empresa[1].manifestacao, None);
+
+        // Na perspectiva da outra empresa, apenas a NF-e destinada a ela é mantida
+        documentos
+            .iter_mut()
+            .for_each(|documento| restringir_manifestacao(documento, "98765432"));
+        assert_eq!(documentos[0].manifestacao, None);
+        assert_eq!(
+            documentos[1].dh_manifestacao.as_deref(),
+            Some("2024-05-10T10:00:00-03:00")
+        );
+    }
+}
@@ -1,11 +1,43 @@
 mod aggregations;
+mod cadeias;
+mod cancelamentos;
+mod correcoes;
+mod credito;
 mod docs_fiscais;
+mod entregas;
 mod event;
+mod explicacao;
+#[cfg(test)]
+mod fixtures;
 mod graph;
+mod historico;
+mod inconsistencias;
 mod information;
+mod manifestacao;
+mod papel;
+mod perspectiva;
+mod rateio;
+mod referencias;
+mod relacionamentos;
+mod sugestoes;
 
 pub use aggregations::*;
+pub use cadeias::*;
+pub use cancelamentos::*;
+pub use correcoes::*;
+pub use credito::*;
 pub use docs_fiscais::*;
+pub use entregas::*;
 pub use event::*;
+pub use explicacao::*;
 pub use graph::*;
+pub use historico::*;
+pub use inconsistencias::*;
 pub use information::*;
+pub use manifestacao::*;
+pub use papel::*;
+pub use perspectiva::*;
+pub use rateio::*;
+pub use referencias::*;
+pub use relacionamentos::*;
+pub use sugestoes::*;
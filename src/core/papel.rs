@@ -0,0 +1,367 @@
+//! # Correlação de CT-es com Documentos em Papel
+//!
+//! CT-es mais antigos declaram a mercadoria transportada sem chave de acesso:
+//!
+//! * `infDoc/infNF`: Nota Fiscal modelo 01/1A ou de produtor (modelo 04), com série,
+//!   número, data de emissão e valor;
+//! * `infDoc/infOutros`: outros documentos (declaração, CF-e, NFC-e etc.), com número,
+//!   data de emissão e valor.
+//!
+//! Sem chave, essas referências não entram em [`InfoCte::get_correlated_nfes`]. Este módulo
+//! procura, entre as NF-es válidas do lote, a nota emitida pelo remetente do CT-e com a
+//! mesma série e o mesmo número. Havendo mais de uma candidata, a data de emissão e depois
+//! o valor servem de desempate. A chave encontrada é gravada em [`InfoCte::nfes_papel`] e
+//! passa a participar das correlações como as chaves declaradas no XML.
+//!
+//! As referências sem correspondência única compõem a tabela "Documentos em Papel".
+
+use chrono::NaiveDate;
+use claudiofsr_lib::StrExtension;
+use rayon::prelude::*;
+use rust_xlsxwriter::serialize_option_datetime_to_excel;
+use serde::{Deserialize, Serialize};
+use std::collections::HashMap;
+use struct_iterable::Iterable;
+
+use crate::{
+    GetKey, InfoExtension, descrever_codigo, serialize_vec_string,
+    xml_structs::{cte::InfoCte, nfe_documento::InfoNfeDocumento},
+};
+
+/// Tipo de outro documento transportado (`infOutros/tpDoc`).
+const TIPO_OUTROS: [(&str, &str); 5] = [
+    ("00", "Declaração"),
+    ("10", "Dutoviário"),
+    ("59", "CF-e SAT"),
+    ("65", "NFC-e"),
+    ("99", "Outros"),
+];
+
+/// Diferença máxima de valor aceita no desempate entre NF-es candidatas.
+const TOLERANCIA_VALOR: f64 = 0.01;
+
+/// Referência a documento sem chave de acesso declarada em `infDoc` do CT-e.
+#[derive(Debug, Default, Clone, PartialEq)]
+pub struct DocumentoPapel {
+    /// Grupo do XML: "infNF" ou "infOutros".
+    pub grupo: &'static str,
+    /// Modelo da NF (`mod`): "01" (modelo 01/1A) ou "04" (NF de produtor).
+    pub modelo: Option<String>,
+    /// Tipo de outro documento (`tpDoc`).
+    pub tipo: Option<String>,
+    /// Descrição de outro documento (`descOutros`).
+    pub descricao: Option<String>,
+    pub serie: Option<String>,
+    pub numero: Option<String>,
+    pub data_emissao: Option<NaiveDate>,
+    /// `vNF` da NF ou `vDocFisc` de outro documento.
+    pub valor: Option<f64>,
+}
+
+impl DocumentoPapel {
+    /// Descrição do documento para a coluna "Tipo de Documento".
+    pub fn descrever(&self) -> String {
+        match (self.grupo, &self.modelo) {
+            ("infNF", Some(modelo)) => format!("NF modelo {modelo}"),
+            ("infNF", None) => "NF".to_string(),
+            _ => {
+                let tipo = descrever_codigo(&self.tipo, &TIPO_OUTROS);
+                [tipo, self.descricao.clone()]
+                    .into_iter()
+                    .flatten()
+                    .collect::<Vec<_>>()
+                    .join(": ")
+            }
+        }
+    }
+
+    /// Número do documento sem zeros à esquerda.
+    fn get_numero(&self) -> Option<u32> {
+        self.numero.as_ref()?.remove_non_digits().parse().ok()
+    }
+}
+
+/// Linha da tabela "Documentos em Papel": referência sem NF-e correspondente única.
+#[derive(Debug, Default, Serialize, Deserialize, Clone, Iterable)]
+pub struct InfoDocumentoPapel {
+    #[serde(rename = "Chave do CTe", default)]
+    pub cte: Option<String>,
+
+    #[serde(rename = "Nº do CTe", default)]
+    pub numero_cte: Option<u32>,
+
+    #[serde(rename = "CNPJ do Remetente", default)]
+    pub remetente_cnpj: Option<String>,
+
+    #[serde(rename = "CPF do Remetente", default)]
+    pub remetente_cpf: Option<String>,
+
+    /// "infNF" ou "infOutros".
+    #[serde(rename = "Grupo do XML", default)]
+    pub grupo: Option<String>,
+
+    #[serde(rename = "Tipo de Documento", default)]
+    pub tipo: Option<String>,
+
+    #[serde(rename = "Série do Documento", default)]
+    pub serie: Option<String>,
+
+    #[serde(rename = "Nº do Documento", default)]
+    pub numero: Option<String>,
+
+    #[serde(
+        rename = "Data de Emissão",
+        serialize_with = "serialize_option_datetime_to_excel",
+        default
+    )]
+    pub data_emissao: Option<NaiveDate>,
+
+    #[serde(rename = "Valor do Documento", default)]
+    pub valor: Option<f64>,
+
+    /// "Não Encontrado" ou "Ambíguo" (mais de uma NF-e após os desempates).
+    #[serde(rename = "Situação da Correlação", default)]
+    pub situacao: Option<String>,
+
+    /// NF-es do remetente com a mesma série e número, quando ambíguo.
+    #[serde(
+        rename = "NFes Candidatas",
+        serialize_with = "serialize_vec_string",
+        default
+    )]
+    pub candidatas: Vec<String>,
+}
+
+impl InfoExtension for InfoDocumentoPapel {}
+
+impl GetKey for InfoDocumentoPapel {
+    fn get_chave(&self) -> Option<String> {
+        self.cte.clone()
+    }
+}
+
+/// Série sem zeros à esquerda: "001" e "1" designam a mesma série.
+fn normalizar_serie(serie: &str) -> &str {
+    let serie = serie.trim().trim_start_matches('0');
+    if serie.is_empty() { "0" } else { serie }
+}
+
+/// CNPJ ou CPF apenas com dígitos.
+pub(crate) fn get_documento(cnpj: &Option<String>, cpf: &Option<String>) -> Option<String> {
+    cnpj.as_ref()
+        .or(cpf.as_ref())
+        .map(|documento| documento.remove_non_digits())
+        .filter(|digitos| !digitos.is_empty())
+}
+
+/// Seleciona as NF-es do remetente que correspondem à referência.
+///
+/// A série só restringe quando declarada nos dois documentos (`infOutros` não tem série).
+/// Data de emissão e valor apenas desempatam: são aplicados se restar mais de uma
+/// candidata e se ao menos uma delas atender ao critério.
+fn get_candidatas<'a>(
+    documento: &DocumentoPapel,
+    nfes: &[&'a InfoNfeDocumento],
+) -> Vec<&'a InfoNfeDocumento> {
+    let mut candidatas: Vec<&InfoNfeDocumento> = nfes
+        .iter()
+        .filter(|nfe| match (&documento.serie, &nfe.serie) {
+            (Some(a), Some(b)) => normalizar_serie(a) == normalizar_serie(b),
+            _ => true,
+        })
+        .copied()
+        .collect();
+
+    let desempates: [&dyn Fn(&InfoNfeDocumento) -> bool; 2] = [
+        &|nfe| documento.data_emissao.is_some() && nfe.data_emissao == documento.data_emissao,
+        &|nfe| {
+            documento
+                .valor
+                .zip(nfe.valor_total_nfe)
+                .is_some_and(|(a, b)| (a - b).abs() <= TOLERANCIA_VALOR)
+        },
+    ];
+
+    for criterio in desempates {
+        if candidatas.len() > 1 && candidatas.iter().any(|nfe| criterio(nfe)) {
+            candidatas.retain(|nfe| criterio(nfe));
+        }
+    }
+
+    candidatas
+}
+
+/// Correlaciona as referências em papel dos CT-es com as NF-es válidas do lote.
+///
+/// As chaves encontradas são gravadas em [`InfoCte::nfes_papel`].
+/// Retorna as referências sem correspondência única, ordenadas por CT-e.
+pub fn correlacionar_documentos_papel(
+    ctes: &mut [InfoCte],
+    documentos: &[InfoNfeDocumento],
+) -> Vec<InfoDocumentoPapel> {
+    // (CNPJ/CPF do emitente, número) -> NF-es válidas
+    let mut indice: HashMap<(String, u32), Vec<&InfoNfeDocumento>> = HashMap::new();
+    for nfe in documentos.iter().filter(|nfe| nfe.is_valid()) {
+        if let Some(emitente) = get_documento(&nfe.emitente_cnpj, &nfe.emitente_cpf)
+            && let Some(numero) = nfe.numero_da_nota
+        {
+            indice.entry((emitente, numero)).or_default().push(nfe);
+        }
+    }
+
+    let mut pendentes: Vec<InfoDocumentoPapel> = ctes
+        .par_iter_mut()
+        .filter(|info| !info.documentos_papel.is_empty())
+        .flat_map_iter(|info| {
+            let remetente = get_documento(&info.remetente_cnpj, &info.remetente_cpf);
+            let mut pendentes = Vec::new();
+
+            for documento in &info.documentos_papel {
+                let nfes = remetente
+                    .clone()
+                    .zip(documento.get_numero())
+                    .and_then(|chave| indice.get(&chave))
+                    .map(Vec::as_slice)
+                    .unwrap_or_default();
+                let candidatas = get_candidatas(documento, nfes);
+
+                if let [nfe] = candidatas.as_slice() {
+                    if let Some(chave) = &nfe.nfe
+                        && !info.nfes_papel.contains(chave)
+                    {
+                        info.nfes_papel.push(chave.clone());
+                    }
+                    continue;
+                }
+
+                let situacao = if candidatas.is_empty() {
+                    "Não Encontrado"
+                } else {
+                    "Ambíguo"
+                };
+                pendentes.push(InfoDocumentoPapel {
+                    cte: info.cte.clone(),
+                    numero_cte: info.numero_da_nota,
+                    remetente_cnpj: info.remetente_cnpj.clone(),
+                    remetente_cpf: info.remetente_cpf.clone(),
+                    grupo: Some(documento.grupo.to_string()),
+                    tipo: Some(documento.descrever()),
+                    serie: documento.serie.clone(),
+                    numero: documento.numero.clone(),
+                    data_emissao: documento.data_emissao,
+                    valor: documento.valor,
+                    situacao: Some(situacao.to_string()),
+                    candidatas: candidatas
+                        .iter()
+                        .filter_map(|nfe| nfe.nfe.clone())
+                        .collect(),
+                });
+            }
+
+            info.nfes_papel.sort();
+            pendentes
+        })
+        .collect();
+
+    pendentes.par_sort_by(|a, b| (&a.cte, &a.numero).cmp(&(&b.cte, &b.numero)));
+    pendentes
+}
+
+//----------------------------------------------------------------------------//
+//                                   Tests                                    //
+//----------------------------------------------------------------------------//
+
+/// Run tests with:
+/// cargo test -- --show-output tests_documentos_papel
+#[cfg(test)]
+mod tests_documentos_papel {
+    use super::*;
+    use crate::core::fixtures::{cte, documento};
+
+    fn nfe(chave: &str, serie: &str, numero: u32, dia: u32, valor: f64) -> InfoNfeDocumento {
+        let mut documento = documento(chave);
+        documento.emitente_cnpj = Some("12.345.678/0001-90".to_string());
+        documento.serie = Some(serie.to_string());
+        documento.numero_da_nota = Some(numero);
+        documento.data_emissao = NaiveDate::from_ymd_opt(2015, 6, dia);
+        documento.valor_total_nfe = Some(valor);
+        documento
+    }
+
+    fn nf(serie: &str, numero: &str, dia: u32, valor: f64) -> DocumentoPapel {
+        DocumentoPapel {
+            grupo: "infNF",
+            modelo: Some("01".to_string()),
+            serie: Some(serie.to_string()),
+            numero: Some(numero.to_string()),
+            data_emissao: NaiveDate::from_ymd_opt(2015, 6, dia),
+            valor: Some(valor),
+            ..Default::default()
+        }
+    }
+
+    #[test]
+    /// `cargo test -- --show-output correlacionar_por_serie_e_numero`
+    fn correlacionar_por_serie_e_numero() {
+        let documentos = vec![
+            nfe("nfe1", "1", 123, 1, 100.0),
+            nfe("nfe2", "2", 123, 1, 200.0),
+            // Mesma série e número: desempate pela data e depois pelo valor
+            nfe("nfe3", "1", 456, 2, 300.0),
+            nfe("nfe4", "1", 456, 3, 400.0),
+            nfe("nfe5", "1", 456, 3, 500.0),
+            nfe("nfe6", "1", 789, 4, 600.0),
+            nfe("nfe7", "1", 789, 4, 600.0),
+        ];
+
+        let mut cte = cte("cte1");
+        cte.remetente_cnpj = Some("12.345.678/0001-90".to_string());
+        cte.documentos_papel = vec![
+            nf("001", "000123", 1, 100.0),
+            nf("1", "456", 3, 500.0),
+            nf("1", "789", 4, 600.0),
+            nf("1", "999", 5, 700.0),
+        ];
+        let outros = DocumentoPapel {
+            grupo: "infOutros",
+            tipo: Some("00".to_string()),
+            numero: Some("123".to_string()),
+            ..Default::default()
+        };
+        cte.documentos_papel.push(outros.clone());
+
+        let mut ctes = vec![cte];
+        let pendentes = correlacionar_documentos_papel(&mut ctes, &documentos);
+        println!("pendentes: {pendentes:#?}");
+
+        assert_eq!(ctes[0].nfes_papel, ["nfe1", "nfe5"]);
+
+        let situacoes: Vec<(Option<&str>, Option<&str>, &[String])> = pendentes
+            .iter()
+            .map(|p| {
+                (
+                    p.numero.as_deref(),
+                    p.situacao.as_deref(),
+                    p.candidatas.as_slice(),
+                )
+            })
+            .collect();
+        assert_eq!(
+            situacoes,
+            [
+                (
+                    Some("123"),
+                    Some("Ambíguo"),
+                    &["nfe1".to_string(), "nfe2".to_string()][..]
+                ),
+                (
+                    Some("789"),
+                    Some("Ambíguo"),
+                    &["nfe6".to_string(), "nfe7".to_string()][..]
+                ),
+                (Some("999"), Some("Não Encontrado"), &[][..]),
+            ]
+        );
+        assert_eq!(outros.descrever(), "00 - Declaração");
+    }
+}
@@ -0,0 +1,391 @@
+//! # Perspectiva da Empresa Auditada
+//!
+//! Classifica NF-es e CT-es em relação à empresa de CNPJ base informado (`--cnpj-base`):
+//! o papel que a empresa exerce no documento e a direção da operação.
+//!
+//! * NF-e: a empresa é emitente e/ou destinatária; a direção decorre do `tpNF`
+//!   lido do ponto de vista da empresa.
+//! * CT-e: a empresa pode figurar como emitente, tomador, remetente, expedidor,
+//!   recebedor ou destinatário.
+//!
+//! Documentos em que a empresa não figura em nenhum papel são classificados como
+//! "Não Relacionado" e exportados separadamente.
+
+use claudiofsr_lib::StrExtension;
+use std::fmt;
+
+use crate::xml_structs::{cte::InfoCte, nfe::InfoNfe, nfe_documento::InfoNfeDocumento};
+
+/// Direção da operação do ponto de vista da empresa auditada.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash)]
+pub enum Direcao {
+    Entrada,
+    Saida,
+    NaoRelacionado,
+}
+
+impl Direcao {
+    /// Todas as direções, na ordem de exportação.
+    pub const ALL: [Direcao; 3] = [Direcao::Entrada, Direcao::Saida, Direcao::NaoRelacionado];
+
+    /// Descrição exibida nas colunas das planilhas.
+    pub const fn descricao(self) -> &'static str {
+        match self {
+            Direcao::Entrada => "Entrada",
+            Direcao::Saida => "Saída",
+            Direcao::NaoRelacionado => "Não Relacionado",
+        }
+    }
+
+    /// Sufixo acrescentado aos nomes dos arquivos de saída.
+    pub const fn sufixo(self) -> &'static str {
+        match self {
+            Direcao::Entrada => "entradas",
+            Direcao::Saida => "saidas",
+            Direcao::NaoRelacionado => "nao_relacionados",
+        }
+    }
+
+    /// Reconstrói a direção a partir da descrição gravada no documento.
+    pub fn from_descricao(descricao: Option<&str>) -> Option<Self> {
+        Self::ALL
+            .into_iter()
+            .find(|direcao| descricao == Some(direcao.descricao()))
+    }
+}
+
+impl fmt::Display for Direcao {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        write!(f, "{}", self.descricao())
+    }
+}
+
+/// Papéis que a empresa auditada pode exercer em um CT-e.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum PapelCte {
+    Emitente,
+    Tomador,
+    Remetente,
+    Expedidor,
+    Recebedor,
+    Destinatario,
+}
+
+impl PapelCte {
+    pub const fn descricao(self) -> &'static str {
+        match self {
+            PapelCte::Emitente => "Emitente",
+            PapelCte::Tomador => "Tomador",
+            PapelCte::Remetente => "Remetente",
+            PapelCte::Expedidor => "Expedidor",
+            PapelCte::Recebedor => "Recebedor",
+            PapelCte::Destinatario => "Destinatário",
+        }
+    }
+}
+
+/// Extrai o CNPJ base (8 dígitos) de um CNPJ completo, formatado ou não.
+///
+/// CPFs e CNPJs incompletos não possuem CNPJ base.
+pub fn get_cnpj_base(cnpj: &Option<String>) -> Option<String> {
+    let digits = cnpj.as_deref()?.remove_non_digits();
+    (digits.len() == 14).then(|| digits[..8].to_string())
+}
+
+/// Verifica se o CNPJ (formatado ou não) pertence à empresa de CNPJ base (8 dígitos).
+pub fn cnpj_pertence_a(cnpj: &Option<String>, cnpj_base: &str) -> bool {
+    get_cnpj_base(cnpj).is_some_and(|base| base == cnpj_base)
+}
+
+/// Classifica uma NF-e a partir dos papéis da empresa e do tipo de operação (`tpNF`).
+///
+/// O `tpNF` é declarado pelo emitente. Quando a empresa é apenas destinatária, a direção
+/// se inverte: a saída do fornecedor é uma entrada na empresa, e a nota de entrada emitida
+/// por terceiro (p. ex., na aquisição de produtor rural) é uma saída da empresa.
+///
+/// Retorna o papel da empresa ("Emitente", "Destinatário" ou ambos) e a direção.
+pub fn classificar_nfe(
+    emitente: bool,
+    destinatario: bool,
+    tipo_operacao: Option<&str>,
+) -> (Option<String>, Direcao) {
+    // tpNF: "0 - Entrada" ou "1 - Saída". Na ausência, presume-se saída do emitente.
+    let nota_de_entrada = tipo_operacao.is_some_and(|tipo| tipo.starts_with('0'));
+
+    let (papel, direcao) = match (emitente, destinatario) {
+        (true, _) => {
+            let papel = if destinatario {
+                "Emitente e Destinatário"
+            } else {
+                "Emitente"
+            };
+            let direcao = if nota_de_entrada {
+                Direcao::Entrada
+            } else {
+                Direcao::Saida
+            };
+            (papel, direcao)
+        }
+        (false, true) => {
+            let direcao = if nota_de_entrada {
+                Direcao::Saida
+            } else {
+                Direcao::Entrada
+            };
+            ("Destinatário", direcao)
+        }
+        (false, false) => return (None, Direcao::NaoRelacionado),
+    };
+
+    (Some(papel.to_string()), direcao)
+}
+
+/// Classifica um CT-e a partir dos papéis exercidos pela empresa.
+///
+/// Prioridade da direção:
+///
+/// 1. Remetente ou expedidor: a carga sai da empresa ([`Direcao::Saida`]).
+/// 2. Destinatário ou recebedor: a carga entra na empresa ([`Direcao::Entrada`]).
+/// 3. Emitente: prestação de serviço de transporte pela empresa ([`Direcao::Saida`]).
+/// 4. Apenas tomador (terceiro em relação à carga): aquisição do serviço ([`Direcao::Entrada`]).
+///
+/// Retorna os papéis (separados por vírgula) e a direção.
+pub fn classificar_cte(papeis: &[PapelCte]) -> (Option<String>, Direcao) {
+    use PapelCte::*;
+
+    let possui = |alvos: &[PapelCte]| papeis.iter().any(|papel| alvos.contains(papel));
+
+    let direcao = if possui(&[Remetente, Expedidor]) {
+        Direcao::Saida
+    } else if possui(&[Destinatario, Recebedor]) {
+        Direcao::Entrada
+    } else if possui(&[Emitente]) {
+        Direcao::Saida
+    } else if possui(&[Tomador]) {
+        Direcao::Entrada
+    } else {
+        return (None, Direcao::NaoRelacionado);
+    };
+
+    let papel = papeis
+        .iter()
+        .map(|papel| papel.descricao())
+        .collect::<Vec<_>>()
+        .join(", ");
+
+    (Some(papel), direcao)
+}
+
+/// Documentos que recebem o papel e a direção da empresa auditada.
+pub trait Perspectiva {
+    /// Papel e direção do documento em relação à empresa de CNPJ base (8 dígitos).
+    fn classificar(&self, cnpj_base: &str) -> (Option<String>, Direcao);
+
+    /// Grava o papel e a direção nas colunas do documento.
+    fn set_perspectiva(&mut self, papel: Option<String>, direcao: Direcao);
+
+    /// Direção atribuída por [`Perspectiva::aplicar_perspectiva`].
+    fn get_direcao(&self) -> Option<Direcao>;
+
+    /// Preenche as colunas de papel e direção a partir do CNPJ base.
+    fn aplicar_perspectiva(&mut self, cnpj_base: &str) {
+        let (papel, direcao) = self.classificar(cnpj_base);
+        self.set_perspectiva(papel, direcao);
+    }
+
+    /// Verifica se a empresa exerce algum papel no documento.
+    fn participa(&self, cnpj_base: &str) -> bool {
+        self.classificar(cnpj_base).1 != Direcao::NaoRelacionado
+    }
+}
+
+impl Perspectiva for InfoNfe {
+    fn classificar(&self, cnpj_base: &str) -> (Option<String>, Direcao) {
+        classificar_nfe(
+            cnpj_pertence_a(&self.emitente_cnpj, cnpj_base),
+            cnpj_pertence_a(&self.destinatario_cnpj, cnpj_base),
+            self.tipo_operacao.as_deref(),
+        )
+    }
+
+    fn set_perspectiva(&mut self, papel: Option<String>, direcao: Direcao) {
+        self.papel_empresa = papel;
+        self.direcao = Some(direcao.to_string());
+    }
+
+    fn get_direcao(&self) -> Option<Direcao> {
+        Direcao::from_descricao(self.direcao.as_deref())
+    }
+}
+
+impl Perspectiva for InfoNfeDocumento {
+    fn classificar(&self, cnpj_base: &str) -> (Option<String>, Direcao) {
+        classificar_nfe(
+            cnpj_pertence_a(&self.emitente_cnpj, cnpj_base),
+            cnpj_pertence_a(&self.destinatario_cnpj, cnpj_base),
+            self.tipo_operacao.as_deref(),
+        )
+    }
+
+    fn set_perspectiva(&mut self, papel: Option<String>, direcao: Direcao) {
+        self.papel_empresa = papel;
+        self.direcao = Some(direcao.to_string());
+    }
+
+    fn get_direcao(&self) -> Option<Direcao> {
+        Direcao::from_descricao(self.direcao.as_deref())
+    }
+}
+
+impl Perspectiva for InfoCte {
+    fn classificar(&self, cnpj_base: &str) -> (Option<String>, Direcao) {
+        let papeis: Vec<PapelCte> = [
+            (
+                PapelCte::Emitente,
+                cnpj_pertence_a(&self.emitente_cnpj, cnpj_base),
+            ),
+            (PapelCte::Tomador, self.tomador_pertence_a(cnpj_base)),
+            (
+                PapelCte::Remetente,
+                cnpj_pertence_a(&self.remetente_cnpj, cnpj_base),
+            ),
+            (
+                PapelCte::Expedidor,
+                cnpj_pertence_a(&self.expedidor_cnpj, cnpj_base),
+            ),
+            (
+                PapelCte::Recebedor,
+                cnpj_pertence_a(&self.recebedor_cnpj, cnpj_base),
+            ),
+            (
+                PapelCte::Destinatario,
+                cnpj_pertence_a(&self.destinatario_cnpj, cnpj_base),
+            ),
+        ]
+        .into_iter()
+        .filter_map(|(papel, pertence)| pertence.then_some(papel))
+        .collect();
+
+        classificar_cte(&papeis)
+    }
+
+    fn set_perspectiva(&mut self, papel: Option<String>, direcao: Direcao) {
+        self.papel_empresa = papel;
+        self.direcao = Some(direcao.to_string());
+    }
+
+    fn get_direcao(&self) -> Option<Direcao> {
+        Direcao::from_descricao(self.direcao.as_deref())
+    }
+}
+
+//----------------------------------------------------------------------------//
+//                                   Tests                                    //
+//----------------------------------------------------------------------------//
+
+/// Run tests with:
+/// cargo test -- --show-output tests_perspectiva
+#[cfg(test)]
+mod tests_perspectiva {
+    use super::*;
+    use crate::DocsFiscais;
+
+    const EMPRESA: &str = "12345678";
+
+    #[test]
+    /// `cargo test -- --show-output perspectiva_da_nfe`
+    fn perspectiva_da_nfe() {
+        let saida = Some("1 - Saída");
+        let entrada = Some("0 - Entrada");
+
+        assert_eq!(
+            classificar_nfe(true, false, saida),
+            (Some("Emitente".to_string()), Direcao::Saida)
+        );
+        assert_eq!(
+            classificar_nfe(true, false, entrada),
+            (Some("Emitente".to_string()), Direcao::Entrada)
+        );
+        assert_eq!(
+            classificar_nfe(false, true, saida),
+            (Some("Destinatário".to_string()), Direcao::Entrada)
+        );
+        assert_eq!(
+            classificar_nfe(false, true, entrada),
+            (Some("Destinatário".to_string()), Direcao::Saida)
+        );
+        assert_eq!(
+            classificar_nfe(false, false, saida),
+            (None, Direcao::NaoRelacionado)
+        );
+    }
+
+    #[test]
+    /// `cargo test -- --show-output perspectiva_do_cte`
+    fn perspectiva_do_cte() {
+        let mut info = InfoCte::default();
+        info.cte = Some("cte1".to_string());
+        info.emitente_cnpj = Some("11.111.111/0001-11".to_string());
+        info.remetente_cnpj = Some("98.765.432/0001-10".to_string());
+        info.destinatario_cnpj = Some("12.345.678/0002-80".to_string());
+        info.tomador_codigo = Some(3); // destinatário
+
+        info.aplicar_perspectiva(EMPRESA);
+        println!(
+            "papel: {:?} ; direcao: {:?}",
+            info.papel_empresa, info.direcao
+        );
+        assert_eq!(info.papel_empresa.as_deref(), Some("Tomador, Destinatário"));
+        assert_eq!(info.get_direcao(), Some(Direcao::Entrada));
+
+        // Remetente prevalece sobre destinatário (transferência entre estabelecimentos)
+        info.remetente_cnpj = Some("12.345.678/0001-99".to_string());
+        info.aplicar_perspectiva(EMPRESA);
+        assert_eq!(info.get_direcao(), Some(Direcao::Saida));
+
+        // CPF com os mesmos 8 primeiros dígitos não pertence à empresa
+        assert!(!cnpj_pertence_a(
+            &Some("123.456.789-01".to_string()),
+            EMPRESA
+        ));
+
+        info.aplicar_perspectiva("99999999");
+        assert_eq!(info.papel_empresa, None);
+        assert_eq!(info.get_direcao(), Some(Direcao::NaoRelacionado));
+    }
+
+    #[test]
+    /// `cargo test -- --show-output documentos_por_empresa`
+    fn documentos_por_empresa() {
+        let cte = |chave: &str, remetente: &str, destinatario: &str| {
+            let mut info = InfoCte::default();
+            info.cte = Some(chave.to_string());
+            info.remetente_cnpj = Some(remetente.to_string());
+            info.destinatario_cnpj = Some(destinatario.to_string());
+            info.tomador_codigo = Some(0); // remetente
+            info
+        };
+
+        let docs_fiscais = DocsFiscais {
+            ctes: vec![
+                cte("cte1", "12.345.678/0001-99", "98.765.432/0001-10"),
+                cte("cte2", "98.765.432/0001-10", "11.111.111/0001-11"),
+            ],
+            ..Default::default()
+        };
+
+        // Cada CTe conta uma vez para o seu tomador (remetente)
+        assert_eq!(docs_fiscais.get_cnpj_bases(1), ["12345678", "98765432"]);
+        assert!(docs_fiscais.get_cnpj_bases(2).is_empty());
+
+        // 98.765.432 participa dos dois CTes: destinatário no primeiro, remetente no segundo
+        let docs = docs_fiscais.filtrar_empresa("98765432");
+        println!("ctes: {:#?}", docs.ctes);
+        assert_eq!(docs.ctes.len(), 2);
+        assert_eq!(docs.ctes[0].get_direcao(), Some(Direcao::Entrada));
+        assert_eq!(docs.ctes[1].get_direcao(), Some(Direcao::Saida));
+
+        assert_eq!(docs_fiscais.filtrar_empresa("12345678").ctes.len(), 1);
+        assert!(!docs_fiscais.ctes[1].participa("12345678"));
+    }
+}
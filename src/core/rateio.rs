@@ -0,0 +1,222 @@
+//! # Rateio do Frete de CT-e sobre Itens de NF-e
+//!
+//! Distribui o valor da prestação (`vTPrest`) de cada CT-e entre todos os itens das
+//! NF-es correlacionadas. Diferente de "Valor Total de CTes", que repete o frete integral
+//! em cada linha de item, a soma do frete rateado coincide com o valor dos CT-es.
+
+use clap::ValueEnum;
+use claudiofsr_lib::RoundFloat;
+use itertools::Itertools;
+use rayon::prelude::*;
+use serde::{Deserialize, Serialize};
+use std::collections::{HashMap, HashSet};
+
+use crate::{Correlacoes, GroupBy, KeyDoc};
+
+/// Identificação de um item de NF-e: (chave da NF-e, nº do item).
+pub type ItemNfe = (String, u32);
+
+/// Critério de proporcionalidade do rateio do frete.
+#[derive(Default, ValueEnum, Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize)]
+pub enum CriterioRateio {
+    /// Proporcional ao valor do produto (`vProd`) de cada item.
+    #[default]
+    Valor,
+
+    /// Proporcional ao peso de cada NF-e (`pesoB`, ou `pesoL` na ausência do bruto).
+    ///
+    /// Dentro de uma mesma NF-e, o peso é distribuído entre os itens pelo valor do produto.
+    /// Se alguma NF-e correlacionada ao CT-e não informar peso, o CT-e é rateado por valor.
+    Peso,
+}
+
+/// Calcula o frete rateado de cada item de NF-e, somando as parcelas de todos os CT-es.
+///
+/// `pesos` associa a chave da NF-e ao seu peso em kg (usado apenas no critério [`CriterioRateio::Peso`]).
+pub fn get_rateio_frete(
+    correlacoes: &Correlacoes,
+    pesos: &HashMap<&str, f64>,
+    criterio: CriterioRateio,
+) -> HashMap<ItemNfe, f64> {
+    let parcelas: Vec<(ItemNfe, f64)> = correlacoes
+        .cte_nfes
+        .par_iter() // rayon: parallel iterator
+        .flat_map(|(cte, nfes)| {
+            let valor_frete = correlacoes
+                .cte_info
+                .get(&KeyDoc::new(cte, true))
+                .and_then(|infos| {
+                    infos
+                        .iter()
+                        .map(|info| info.valor_total)
+                        .sum::<Option<f64>>()
+                });
+
+            match valor_frete {
+                Some(valor) if valor > 0.0 => ratear_cte(valor, nfes, correlacoes, pesos, criterio),
+                _ => Vec::new(),
+            }
+        })
+        .collect();
+
+    parcelas
+        .group_by_key()
+        .into_iter()
+        .map(|(item, valor)| (item, valor.round_float(2)))
+        .collect()
+}
+
+/// Distribui o valor de um único CT-e entre os itens das NF-es correlacionadas.
+///
+/// As parcelas são arredondadas em centavos e a diferença de arredondamento
+/// é atribuída ao item de maior parcela, de modo que a soma seja igual a `valor_frete`.
+fn ratear_cte(
+    valor_frete: f64,
+    nfes: &HashSet<String>,
+    correlacoes: &Correlacoes,
+    pesos: &HashMap<&str, f64>,
+    criterio: CriterioRateio,
+) -> Vec<(ItemNfe, f64)> {
+    let usar_peso = criterio == CriterioRateio::Peso
+        && nfes
+            .iter()
+            .all(|nfe| pesos.get(nfe.as_str()).is_some_and(|&peso| peso > 0.0));
+
+    // Base de rateio de cada item, em ordem determinística (chave da NF-e, nº do item)
+    let bases: Vec<(ItemNfe, f64)> = nfes
+        .iter()
+        .flat_map(|nfe| {
+            let infos = correlacoes
+                .nfe_info
+                .get(&KeyDoc::new(nfe, true))
+                .map(Vec::as_slice)
+                .unwrap_or_default();
+
+            let valor_nfe: f64 = infos.iter().filter_map(|info| info.v_prod).sum();
+            let peso_nfe = pesos.get(nfe.as_str()).copied().unwrap_or_default();
+
+            infos.iter().filter_map(move |info| {
+                let n_item = info.n_item?;
+                let v_prod = info.v_prod.unwrap_or_default();
+                let base = match (usar_peso, valor_nfe > 0.0) {
+                    (false, _) => v_prod,
+                    (true, true) => peso_nfe * v_prod / valor_nfe,
+                    (true, false) => 0.0,
+                };
+                Some(((nfe.clone(), n_item), base))
+            })
+        })
+        .sorted_by(|a, b| a.0.cmp(&b.0))
+        .collect();
+
+    let base_total: f64 = bases.iter().map(|(_, base)| base).sum();
+    if base_total <= 0.0 {
+        return Vec::new();
+    }
+
+    let mut parcelas: Vec<(ItemNfe, f64)> = bases
+        .into_iter()
+        .map(|(item, base)| (item, (valor_frete * base / base_total).round_float(2)))
+        .collect();
+
+    let diferenca = (valor_frete - parcelas.iter().map(|(_, v)| v).sum::<f64>()).round_float(2);
+    if diferenca != 0.0
+        && let Some(maior) = parcelas.iter_mut().max_by(|a, b| a.1.total_cmp(&b.1))
+    {
+        maior.1 = (maior.1 + diferenca).round_float(2);
+    }
+
+    parcelas
+}
+
+//----------------------------------------------------------------------------//
+//                                   Tests                                    //
+//----------------------------------------------------------------------------//
+
+/// Run tests with:
+/// cargo test -- --show-output tests_rateio
+#[cfg(test)]
+mod tests_rateio {
+    use super::*;
+    use crate::core::fixtures::{chaves, cte, item, por_chave};
+
+    fn correlacoes() -> Correlacoes {
+        let mut cte = cte("cte1");
+        cte.valor_total = Some(100.0);
+
+        Correlacoes {
+            cte_info: por_chave([cte]),
+            nfe_info: por_chave([
+                item("nfe1", 1, 100.0),
+                item("nfe1", 2, 200.0),
+                item("nfe2", 1, 300.0),
+            ]),
+            cte_nfes: HashMap::from([(
+                "cte1".to_string(),
+                HashSet::from_iter(chaves(&["nfe1", "nfe2"])),
+            )]),
+            ..Default::default()
+        }
+    }
+
+    #[test]
+    /// `cargo test -- --show-output rateio_por_valor`
+    fn rateio_por_valor() {
+        let rateio = get_rateio_frete(&correlacoes(), &HashMap::new(), CriterioRateio::Valor);
+        println!("rateio: {rateio:?}");
+
+        // Proporcional a 100, 200 e 300
+        assert_eq!(rateio[&("nfe1".to_string(), 1)], 16.67);
+        assert_eq!(rateio[&("nfe1".to_string(), 2)], 33.33);
+        assert_eq!(rateio[&("nfe2".to_string(), 1)], 50.0);
+        assert_eq!(rateio.values().sum::<f64>().round_float(2), 100.0);
+    }
+
+    #[test]
+    /// `cargo test -- --show-output rateio_por_peso`
+    fn rateio_por_peso() {
+        let correlacoes = correlacoes();
+
+        // nfe1: 300 kg ; nfe2: 100 kg
+        let pesos = HashMap::from([("nfe1", 300.0), ("nfe2", 100.0)]);
+        let rateio = get_rateio_frete(&correlacoes, &pesos, CriterioRateio::Peso);
+        println!("rateio: {rateio:?}");
+        assert_eq!(rateio[&("nfe1".to_string(), 1)], 25.0);
+        assert_eq!(rateio[&("nfe1".to_string(), 2)], 50.0);
+        assert_eq!(rateio[&("nfe2".to_string(), 1)], 25.0);
+
+        // NF-e sem peso: o CT-e é rateado por valor
+        let pesos = HashMap::from([("nfe1", 300.0)]);
+        let rateio = get_rateio_frete(&correlacoes, &pesos, CriterioRateio::Peso);
+        assert_eq!(rateio[&("nfe2".to_string(), 1)], 50.0);
+    }
+
+    #[test]
+    /// `cargo test -- --show-output rateio_com_arredondamento`
+    fn rateio_com_arredondamento() {
+        let nfes = HashSet::from(["nfe1".to_string()]);
+        let mut correlacoes = correlacoes();
+        correlacoes.nfe_info.insert(
+            KeyDoc::new("nfe1", true),
+            vec![
+                item("nfe1", 1, 10.0),
+                item("nfe1", 2, 10.0),
+                item("nfe1", 3, 10.0),
+            ],
+        );
+
+        // 100 / 3 = 33.33 (x3): o centavo restante vai para a maior parcela
+        let parcelas = ratear_cte(
+            100.0,
+            &nfes,
+            &correlacoes,
+            &HashMap::new(),
+            CriterioRateio::Valor,
+        );
+        println!("parcelas: {parcelas:?}");
+        let valores: Vec<f64> = parcelas.iter().map(|(_, valor)| *valor).collect();
+        assert_eq!(valores.iter().sum::<f64>().round_float(2), 100.0);
+        assert_eq!(valores.iter().filter(|&&v| v == 33.33).count(), 2);
+        assert!(valores.contains(&33.34));
+    }
+}
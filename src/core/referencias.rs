@@ -0,0 +1,271 @@
+//! # Referências entre NF-es (`NFref/refNFe`)
+//!
+//! Devoluções (`finNFe = 4`), NF-es complementares (`finNFe = 2`) e de ajuste (`finNFe = 3`)
+//! referenciam a NF-e original em `ide/NFref/refNFe`. Este módulo inverte essas referências
+//! e preenche, em cada linha de [`InfoNfeDocumento`]:
+//!
+//! * as NF-es que a referenciam ("Referenciada pelas NFes");
+//! * o valor líquido após as devoluções que a referenciam; a devolução que referencia
+//!   mais de uma NF-e original não é abatida (não há como ratear o seu valor) e fica
+//!   registrada no alerta do valor líquido;
+//! * nas devoluções, os CT-es correlacionados às NF-es originais, que fundamentam o
+//!   estorno do crédito sobre o frete da operação desfeita.
+//!
+//! A tabela "NFes x NFes" traz uma linha por referência declarada.
+
+use claudiofsr_lib::RoundFloat;
+use rayon::prelude::*;
+use serde::{Deserialize, Serialize};
+use std::collections::{BTreeSet, HashMap, HashSet};
+use struct_iterable::Iterable;
+
+use crate::{InfoExtension, xml_structs::nfe_documento::InfoNfeDocumento};
+
+/// Linha da tabela "NFes x NFes": uma referência `refNFe` declarada.
+#[derive(Debug, Default, Serialize, Deserialize, Clone, Iterable)]
+pub struct InfoNfeNfe {
+    #[serde(rename = "NFe Original", default)]
+    pub original: Option<String>,
+
+    /// NF-e que declara a referência em `NFref`.
+    #[serde(rename = "NFe Referenciadora", default)]
+    pub referenciadora: Option<String>,
+
+    /// Finalidade de emissão da NF-e referenciadora (`finNFe`).
+    #[serde(rename = "Finalidade da NFe Referenciadora", default)]
+    pub finalidade: Option<String>,
+
+    #[serde(rename = "Valor da NFe Referenciadora", default)]
+    pub valor: Option<f64>,
+
+    /// "Válido", "Cancelado" ou "Não Encontrado" no lote de arquivos.
+    #[serde(rename = "Situação da NFe Original", default)]
+    pub situacao_original: Option<String>,
+
+    #[serde(rename = "Situação da NFe Referenciadora", default)]
+    pub situacao_referenciadora: Option<String>,
+}
+
+impl InfoExtension for InfoNfeNfe {}
+
+impl InfoNfeNfe {
+    /// Verifica se a NF-e original ou a referenciadora pertence ao conjunto de chaves.
+    pub fn envolve(&self, nfes: &HashSet<&str>) -> bool {
+        [&self.original, &self.referenciadora]
+            .into_iter()
+            .flatten()
+            .any(|nfe| nfes.contains(nfe.as_str()))
+    }
+}
+
+fn situacao(documento: Option<&InfoNfeDocumento>) -> &'static str {
+    match documento {
+        Some(documento) if documento.is_valid() => "Válido",
+        Some(_) => "Cancelado",
+        None => "Não Encontrado",
+    }
+}
+
+/// Preenche as colunas de referências entre NF-es e retorna a tabela "NFes x NFes".
+///
+/// `nfe_ctes`: CT-es correlacionados a cada NF-e (ver `Correlacoes::nfe_ctes`).
+pub fn get_referencias_nfes(
+    documentos: &mut [InfoNfeDocumento],
+    nfe_ctes: &HashMap<String, HashSet<String>>,
+) -> Vec<InfoNfeNfe> {
+    let por_chave: HashMap<&str, &InfoNfeDocumento> = documentos
+        .iter()
+        .filter_map(|documento| documento.nfe.as_deref().map(|nfe| (nfe, documento)))
+        .collect();
+
+    let mut linhas: Vec<InfoNfeNfe> = documentos
+        .iter()
+        .flat_map(|documento| {
+            documento
+                .nfes_referenciadas
+                .iter()
+                .map(move |original| (documento, original))
+        })
+        .filter(|(documento, original)| documento.nfe.as_ref() != Some(original))
+        .map(|(documento, original)| InfoNfeNfe {
+            original: Some(original.clone()),
+            referenciadora: documento.nfe.clone(),
+            finalidade: documento.finalidade.clone(),
+            valor: documento.valor_total_nfe,
+            situacao_original: Some(situacao(por_chave.get(original.as_str()).copied()).into()),
+            situacao_referenciadora: Some(situacao(Some(documento)).into()),
+        })
+        .collect();
+    linhas.par_sort_by(|a, b| {
+        (&a.original, &a.referenciadora).cmp(&(&b.original, &b.referenciadora))
+    });
+    linhas.dedup_by(|a, b| a.original == b.original && a.referenciadora == b.referenciadora);
+
+    // Quantidade de NF-es originais declaradas por cada referenciadora
+    let mut originais: HashMap<&str, usize> = HashMap::new();
+    for referenciadora in linhas
+        .iter()
+        .filter_map(|linha| linha.referenciadora.as_deref())
+    {
+        *originais.entry(referenciadora).or_default() += 1;
+    }
+
+    let mut referenciada_por: HashMap<String, Vec<String>> = HashMap::new();
+    let mut devolucoes: HashMap<String, f64> = HashMap::new();
+    let mut ambiguas: HashMap<String, Vec<String>> = HashMap::new();
+
+    // Apenas referências declaradas por NF-es válidas
+    for linha in linhas
+        .iter()
+        .filter(|linha| linha.situacao_referenciadora.as_deref() == Some("Válido"))
+    {
+        let (Some(original), Some(referenciadora)) = (&linha.original, &linha.referenciadora)
+        else {
+            continue;
+        };
+        referenciada_por
+            .entry(original.clone())
+            .or_default()
+            .push(referenciadora.clone());
+        if por_chave
+            .get(referenciadora.as_str())
+            .is_some_and(|documento| documento.is_devolucao())
+        {
+            match originais
+                .get(referenciadora.as_str())
+                .copied()
+                .unwrap_or_default()
+            {
+                1 => {
+                    *devolucoes.entry(original.clone()).or_default() +=
+                        linha.valor.unwrap_or_default()
+                }
+                quantidade => ambiguas.entry(original.clone()).or_default().push(format!(
+                    "Devolução {referenciadora} referencia {quantidade} NFes: valor não abatido"
+                )),
+            }
+        }
+    }
+
+    documentos
+        .par_iter_mut()
+        .filter(|documento| documento.is_valid())
+        .for_each(|documento| {
+            let Some(nfe) = documento.nfe.as_deref() else {
+                return;
+            };
+
+            documento.referenciada_por = referenciada_por.get(nfe).cloned().unwrap_or_default();
+
+            let devolvido = devolucoes.get(nfe).copied().unwrap_or_default();
+            documento.valor_liquido = documento
+                .valor_total_nfe
+                .map(|valor| (valor - devolvido).round_float(2));
+            documento.alerta_valor_liquido = ambiguas.get(nfe).map(|alertas| alertas.join("; "));
+
+            if documento.is_devolucao() {
+                let ctes: BTreeSet<&String> = documento
+                    .nfes_referenciadas
+                    .iter()
+                    .filter_map(|original| nfe_ctes.get(original))
+                    .flatten()
+                    .collect();
+                documento.ctes_nfe_original = ctes.into_iter().cloned().collect();
+            }
+        });
+
+    linhas
+}
+
+//----------------------------------------------------------------------------//
+//                                   Tests                                    //
+//----------------------------------------------------------------------------//
+
+/// Run tests with:
+/// cargo test -- --show-output tests_referencias_nfes
+#[cfg(test)]
+mod tests_referencias_nfes {
+    use super::*;
+    use crate::core::fixtures::{self, chaves};
+
+    fn documento(chave: &str, finalidade: &str, valor: f64, refs: &[&str]) -> InfoNfeDocumento {
+        let mut documento = fixtures::documento(chave);
+        documento.finalidade = Some(finalidade.to_string());
+        documento.valor_total_nfe = Some(valor);
+        documento.nfes_referenciadas = chaves(refs);
+        documento
+    }
+
+    #[test]
+    /// `cargo test -- --show-output devolucoes_e_complementares`
+    fn devolucoes_e_complementares() {
+        let mut cancelada = documento("nfe5", "4 - Devolução de mercadoria", 50.0, &["nfe1"]);
+        cancelada.cancelado = Some("Cancelado".to_string());
+
+        let mut documentos = vec![
+            documento("nfe1", "1 - NF-e normal", 1000.0, &[]),
+            documento("nfe2", "4 - Devolução de mercadoria", 300.0, &["nfe1"]),
+            documento("nfe3", "2 - NF-e complementar", 100.0, &["nfe1"]),
+            documento(
+                "nfe4",
+                "4 - Devolução de mercadoria",
+                200.0,
+                &["nfe1", "nfe9"],
+            ),
+            cancelada,
+        ];
+        let nfe_ctes = HashMap::from([(
+            "nfe1".to_string(),
+            HashSet::from(["cte1".to_string(), "cte2".to_string()]),
+        )]);
+
+        let linhas = get_referencias_nfes(&mut documentos, &nfe_ctes);
+        println!("linhas: {linhas:#?}");
+
+        assert_eq!(linhas.len(), 5);
+        let nfe9 = linhas
+            .iter()
+            .find(|linha| linha.original.as_deref() == Some("nfe9"))
+            .unwrap();
+        assert_eq!(nfe9.situacao_original.as_deref(), Some("Não Encontrado"));
+
+        // A devolução cancelada não reduz o valor nem aparece como referenciadora
+        assert_eq!(documentos[0].referenciada_por, ["nfe2", "nfe3", "nfe4"]);
+        // nfe4 também referencia nfe9: o seu valor não é abatido de nfe1
+        assert_eq!(documentos[0].valor_liquido, Some(700.0));
+        assert_eq!(
+            documentos[0].alerta_valor_liquido.as_deref(),
+            Some("Devolução nfe4 referencia 2 NFes: valor não abatido")
+        );
+        assert_eq!(documentos[1].ctes_nfe_original, ["cte1", "cte2"]);
+        assert!(documentos[2].ctes_nfe_original.is_empty());
+        assert_eq!(documentos[3].ctes_nfe_original, ["cte1", "cte2"]);
+    }
+
+    #[test]
+    /// `cargo test -- --show-output devolucao_com_duas_originais`
+    fn devolucao_com_duas_originais() {
+        let mut documentos = vec![
+            documento("nfe1", "1 - NF-e normal", 1000.0, &[]),
+            documento("nfe2", "1 - NF-e normal", 500.0, &[]),
+            documento(
+                "nfe3",
+                "4 - Devolução de mercadoria",
+                400.0,
+                &["nfe1", "nfe2"],
+            ),
+            documento("nfe4", "4 - Devolução de mercadoria", 100.0, &["nfe2"]),
+        ];
+
+        get_referencias_nfes(&mut documentos, &HashMap::new());
+
+        // A devolução de duas originais não é abatida de nenhuma delas
+        let alerta = "Devolução nfe3 referencia 2 NFes: valor não abatido";
+        assert_eq!(documentos[0].valor_liquido, Some(1000.0));
+        assert_eq!(documentos[0].alerta_valor_liquido.as_deref(), Some(alerta));
+        assert_eq!(documentos[1].valor_liquido, Some(400.0));
+        assert_eq!(documentos[1].alerta_valor_liquido.as_deref(), Some(alerta));
+        assert_eq!(documentos[1].referenciada_por, ["nfe3", "nfe4"]);
+        assert!(documentos[3].alerta_valor_liquido.is_none());
+    }
+}
@@ -0,0 +1,342 @@
+//! # Tabelas Normalizadas de Relacionamentos
+//!
+//! As colunas multivaloradas das tabelas de CT-es e NF-es (`"[chave1, chave2, ...]"`)
+//! não permitem filtros nem junções em planilhas. Este módulo gera uma linha por relacionamento:
+//!
+//! * CTe x NFe: cada par correlacionado, indicando se o vínculo foi declarado no próprio CT-e
+//!   ou herdado de outro CT-e da mesma cadeia (ver [`GraphExtension::expand_nfes`]);
+//! * CTe x CTe: cada relação declarada no XML ou em eventos, com o tipo e a fonte da relação;
+//! * CTe x NCM: composição da carga de cada CT-e por NCM e descrição dos itens das NF-es,
+//!   com valores e porcentagens em colunas numéricas.
+//!
+//! [`GraphExtension::expand_nfes`]: crate::GraphExtension::expand_nfes
+
+use rayon::prelude::*;
+use serde::{Deserialize, Serialize};
+use std::collections::HashSet;
+use struct_iterable::Iterable;
+
+use crate::{
+    Correlacoes, GetKey, InfoExtension, KeyDoc, TipoRelacaoCte, get_itens_por_ncm_descricao,
+    xml_structs::cte::InfoCte,
+};
+
+/// Linha da tabela "CTes x NFes": um par CT-e/NF-e correlacionado.
+#[derive(Debug, Default, Serialize, Deserialize, Clone, Iterable)]
+pub struct InfoCteNfe {
+    #[serde(rename = "Chave do CTe", default)]
+    pub cte: Option<String>,
+
+    #[serde(rename = "Chave da NFe", default)]
+    pub nfe: Option<String>,
+
+    /// "Declarado no CTe", "Documento em Papel do CTe" ou "Herdado de CTe Relacionado".
+    #[serde(rename = "Origem do Vínculo", default)]
+    pub origem: Option<String>,
+}
+
+/// Linha da tabela "CTes x CTes": uma relação declarada entre dois CT-es.
+#[derive(Debug, Default, Serialize, Deserialize, Clone, Iterable)]
+pub struct InfoCteCte {
+    #[serde(rename = "CTe Original", default)]
+    pub original: Option<String>,
+
+    #[serde(rename = "CTe Derivado", default)]
+    pub derivado: Option<String>,
+
+    /// Papel do CT-e derivado em relação ao original (ver [`TipoRelacaoCte`]).
+    #[serde(rename = "Tipo de Relação", default)]
+    pub tipo_relacao: Option<String>,
+
+    #[serde(rename = "Descrição da Relação", default)]
+    pub descricao: Option<String>,
+
+    /// Campo do XML ou evento em que a relação foi declarada.
+    #[serde(rename = "Fonte da Relação", default)]
+    pub fonte: Option<String>,
+
+    /// "Válido", "Cancelado", "Substituído ou Anulado" ou "Não Encontrado" no lote de arquivos.
+    #[serde(rename = "Situação do CTe Original", default)]
+    pub situacao_original: Option<String>,
+
+    #[serde(rename = "Situação do CTe Derivado", default)]
+    pub situacao_derivado: Option<String>,
+
+    /// Se a relação propaga as NF-es entre os CT-es (`--propagar-nfes`).
+    #[serde(rename = "Propaga NFes", default)]
+    pub propaga_nfes: Option<String>,
+}
+
+impl InfoCteCte {
+    /// Verifica se o CT-e original ou o derivado pertence ao conjunto de chaves.
+    pub fn envolve(&self, ctes: &HashSet<&str>) -> bool {
+        [&self.original, &self.derivado]
+            .into_iter()
+            .flatten()
+            .any(|cte| ctes.contains(cte.as_str()))
+    }
+}
+
+/// Linha da tabela "CTes x NCMs": parcela da carga de um CT-e por NCM e descrição.
+#[derive(Debug, Default, Serialize, Deserialize, Clone, Iterable)]
+pub struct InfoCteNcm {
+    #[serde(rename = "Chave do CTe", default)]
+    pub cte: Option<String>,
+
+    #[serde(rename = "NCM (Nomenclatura Comum do Mercosul)", default)]
+    pub ncm: Option<String>,
+
+    #[serde(rename = "Descrição", default)]
+    pub descricao: Option<String>,
+
+    /// Soma do valor dos itens (vProd) das NF-es correlacionadas.
+    #[serde(rename = "Valor dos Itens", default)]
+    pub valor: f64,
+
+    /// Porcentagem sobre o valor total dos itens das NF-es do CT-e.
+    #[serde(rename = "Porcentagem do Valor", default)]
+    pub porcentagem: f64,
+}
+
+impl InfoExtension for InfoCteNfe {}
+impl InfoExtension for InfoCteCte {}
+impl InfoExtension for InfoCteNcm {}
+
+impl GetKey for InfoCteNfe {
+    fn get_chave(&self) -> Option<String> {
+        self.cte.clone()
+    }
+}
+
+impl GetKey for InfoCteNcm {
+    fn get_chave(&self) -> Option<String> {
+        self.cte.clone()
+    }
+}
+
+/// Uma linha por par CT-e/NF-e em `correlacoes.cte_nfes` (apenas CT-es válidos).
+pub fn get_ctes_nfes(ctes: &[InfoCte], correlacoes: &Correlacoes) -> Vec<InfoCteNfe> {
+    ctes.par_iter()
+        .filter(|info| info.is_valid())
+        .flat_map_iter(|info| {
+            let declaradas: HashSet<String> = info.get_correlated_nfes().into_iter().collect();
+            let mut nfes: Vec<&String> = info
+                .cte
+                .as_ref()
+                .and_then(|cte| correlacoes.cte_nfes.get(cte))
+                .into_iter()
+                .flatten()
+                .collect();
+            nfes.sort();
+
+            nfes.into_iter()
+                .map(|nfe| {
+                    let origem = if declaradas.contains(nfe) {
+                        if info.nfes_papel.contains(nfe)
+                            && !info.remetente_nfes.contains(nfe)
+                            && !info.nfes_vinculados.contains(nfe)
+                        {
+                            "Documento em Papel do CTe"
+                        } else {
+                            "Declarado no CTe"
+                        }
+                    } else {
+                        "Herdado de CTe Relacionado"
+                    };
+                    InfoCteNfe {
+                        cte: info.cte.clone(),
+                        nfe: Some(nfe.clone()),
+                        origem: Some(origem.to_string()),
+                    }
+                })
+                .collect::<Vec<_>>()
+        })
+        .collect()
+}
+
+/// Situação de um CT-e no lote: "Válido", "Cancelado", "Substituído ou Anulado" ou "Não Encontrado".
+fn situacao_cte(cte: &str, correlacoes: &Correlacoes) -> &'static str {
+    if correlacoes.cte_info.contains_key(&KeyDoc::new(cte, true)) {
+        "Válido"
+    } else if let Some(infos) = correlacoes.cte_info.get(&KeyDoc::new(cte, false)) {
+        if infos.iter().any(InfoCte::is_canceled) {
+            "Cancelado"
+        } else {
+            "Substituído ou Anulado"
+        }
+    } else {
+        "Não Encontrado"
+    }
+}
+
+/// Uma linha por relação declarada em `correlacoes.relacoes_ctes`.
+///
+/// Ao contrário de `cte_nfes`, não há fecho transitivo: cada linha é uma referência declarada.
+pub fn get_ctes_ctes(correlacoes: &Correlacoes, propagar: &[TipoRelacaoCte]) -> Vec<InfoCteCte> {
+    correlacoes
+        .relacoes_ctes
+        .par_iter()
+        .map(|relacao| {
+            let propaga = propagar.contains(&relacao.tipo);
+            InfoCteCte {
+                original: Some(relacao.original.clone()),
+                derivado: Some(relacao.derivado.clone()),
+                tipo_relacao: Some(relacao.tipo.descricao().to_string()),
+                descricao: Some(relacao.descrever()),
+                fonte: Some(relacao.fonte.to_string()),
+                situacao_original: Some(situacao_cte(&relacao.original, correlacoes).to_string()),
+                situacao_derivado: Some(situacao_cte(&relacao.derivado, correlacoes).to_string()),
+                propaga_nfes: Some(if propaga { "Sim" } else { "Não" }.to_string()),
+            }
+        })
+        .collect()
+}
+
+/// Composição da carga de cada CT-e válido por NCM e descrição, em ordem decrescente de valor.
+///
+/// Mesmo agrupamento da coluna `ncm_descricao` de [`InfoCte`], porém sem o limite de `--itens`.
+pub fn get_ctes_ncms(ctes: &[InfoCte], correlacoes: &Correlacoes) -> Vec<InfoCteNcm> {
+    ctes.par_iter()
+        .filter(|info| info.is_valid())
+        .flat_map_iter(|info| {
+            let Some(nfes) = info
+                .cte
+                .as_ref()
+                .and_then(|cte| correlacoes.cte_nfes.get(cte))
+            else {
+                return Vec::new();
+            };
+
+            get_itens_por_ncm_descricao(nfes, &correlacoes.nfe_info)
+                .into_iter()
+                .map(|item| InfoCteNcm {
+                    cte: info.cte.clone(),
+                    ncm: Some(item.keys.ncm.to_string()),
+                    descricao: Some(item.keys.descricao.to_string()),
+                    valor: item.valor,
+                    porcentagem: item.pct,
+                })
+                .collect()
+        })
+        .collect()
+}
+
+//----------------------------------------------------------------------------//
+//                                   Tests                                    //
+//----------------------------------------------------------------------------//
+
+/// Run tests with:
+/// cargo test -- --show-output tests_relacionamentos
+#[cfg(test)]
+mod tests_relacionamentos {
+    use super::*;
+    use crate::{
+        RelacaoCte,
+        core::fixtures::{chaves, cte, item, por_chave},
+        xml_structs::nfe::InfoNfe,
+    };
+    use std::collections::HashMap;
+
+    fn nfe(chave: &str, n_item: u32, ncm: &str, descricao: &str, v_prod: f64) -> InfoNfe {
+        let mut info = item(chave, n_item, v_prod);
+        info.ncm = Some(ncm.to_string());
+        info.descricao = Some(descricao.to_string());
+        info
+    }
+
+    fn correlacoes() -> (Vec<InfoCte>, Correlacoes) {
+        let mut cte1 = cte("cte1");
+        cte1.remetente_nfes = chaves(&["nfe1"]);
+        let ctes = vec![cte1, cte("cte2")];
+
+        let cte_info = por_chave(ctes.clone());
+        let nfe_info = por_chave([
+            nfe("nfe1", 1, "1001", "Soja", 300.0),
+            nfe("nfe1", 2, "1002", "Milho", 100.0),
+            nfe("nfe2", 1, "1001", "Soja", 600.0),
+        ]);
+
+        // nfe2 chega ao cte1 por meio do cte2 (fecho da cadeia de CT-es)
+        let nfes = HashSet::from_iter(chaves(&["nfe1", "nfe2"]));
+        let cte_nfes = HashMap::from([
+            ("cte1".to_string(), nfes.clone()),
+            ("cte2".to_string(), nfes),
+        ]);
+
+        let relacoes_ctes = vec![
+            RelacaoCte::evento(
+                "cte1",
+                "cte2",
+                TipoRelacaoCte::Redespacho,
+                "evCTeRedespacho",
+            ),
+            RelacaoCte::xml("cte0", "cte2", TipoRelacaoCte::Substituicao, "infCteSub"),
+        ];
+
+        let correlacoes = Correlacoes {
+            cte_info,
+            nfe_info,
+            cte_nfes,
+            relacoes_ctes,
+            ..Default::default()
+        };
+
+        (ctes, correlacoes)
+    }
+
+    #[test]
+    /// `cargo test -- --show-output tabelas_de_relacionamentos`
+    fn tabelas_de_relacionamentos() {
+        let (ctes, correlacoes) = correlacoes();
+
+        let ctes_nfes = get_ctes_nfes(&ctes, &correlacoes);
+        let linhas: Vec<(&str, &str, &str)> = ctes_nfes
+            .iter()
+            .map(|l| {
+                (
+                    l.cte.as_deref().unwrap(),
+                    l.nfe.as_deref().unwrap(),
+                    l.origem.as_deref().unwrap(),
+                )
+            })
+            .collect();
+        assert_eq!(
+            linhas,
+            [
+                ("cte1", "nfe1", "Declarado no CTe"),
+                ("cte1", "nfe2", "Herdado de CTe Relacionado"),
+                ("cte2", "nfe1", "Herdado de CTe Relacionado"),
+                ("cte2", "nfe2", "Herdado de CTe Relacionado"),
+            ]
+        );
+
+        let ctes_ctes = get_ctes_ctes(&correlacoes, &TipoRelacaoCte::PROPAGACAO_PADRAO);
+        println!("ctes_ctes: {ctes_ctes:#?}");
+        let redespacho = &ctes_ctes[0];
+        assert_eq!(redespacho.tipo_relacao.as_deref(), Some("Redespacho"));
+        assert_eq!(
+            redespacho.descricao.as_deref(),
+            Some("CTe cte2 é o redespacho do CTe cte1")
+        );
+        assert_eq!(redespacho.propaga_nfes.as_deref(), Some("Sim"));
+
+        let substituicao = &ctes_ctes[1];
+        assert_eq!(
+            substituicao.situacao_original.as_deref(),
+            Some("Não Encontrado")
+        );
+        assert_eq!(substituicao.situacao_derivado.as_deref(), Some("Válido"));
+        assert_eq!(substituicao.propaga_nfes.as_deref(), Some("Não"));
+        assert!(substituicao.envolve(&HashSet::from(["cte2"])));
+        assert!(!substituicao.envolve(&HashSet::from(["cte1"])));
+
+        let ctes_ncms = get_ctes_ncms(&ctes, &correlacoes);
+        println!("ctes_ncms: {ctes_ncms:#?}");
+        let cte1: Vec<(&str, f64, f64)> = ctes_ncms
+            .iter()
+            .filter(|l| l.cte.as_deref() == Some("cte1"))
+            .map(|l| (l.ncm.as_deref().unwrap(), l.valor, l.porcentagem))
+            .collect();
+        assert_eq!(cte1, [("1001", 900.0, 90.0), ("1002", 100.0, 10.0)]);
+    }
+}
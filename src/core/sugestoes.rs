@@ -0,0 +1,331 @@
+//! # Vínculos Sugeridos entre CT-es e NF-es (`--sugerir-vinculos`)
+//!
+//! Alguns CT-es não declaram nenhuma chave de NF-e (nem em papel) e não herdam NF-es de
+//! outros CT-es da cadeia. Para esses CT-es, este módulo propõe vínculos a partir de
+//! evidências circunstanciais, cada uma com o seu peso na confiança (0 a 100):
+//!
+//! | Evidência                                                | Peso   |
+//! |----------------------------------------------------------|-------:|
+//! | Remetente do CT-e = emitente da NF-e                     |     30 |
+//! | Destinatário do CT-e = destinatário da NF-e              |     30 |
+//! | Transportadora da NF-e (`transporta`) = emitente do CT-e |     20 |
+//! | Datas de emissão próximas (dentro de `--janela-dias`)    | até 10 |
+//! | Valor da carga próximo do valor da NF-e (`vNF`)          | até 10 |
+//!
+//! Só são avaliadas NF-es válidas em que ao menos uma das partes (remetente ou destinatário)
+//! coincide. Os vínculos sugeridos não entram em `cte_nfes`: compõem a tabela própria
+//! "Vínculos Sugeridos", para conferência manual.
+
+use chrono::NaiveDate;
+use claudiofsr_lib::RoundFloat;
+use rayon::prelude::*;
+use serde::{Deserialize, Serialize};
+use std::collections::{BTreeMap, HashMap};
+use struct_iterable::Iterable;
+
+use crate::{
+    Correlacoes, GetKey, InfoExtension, get_documento, serialize_vec_string,
+    xml_structs::{cte::InfoCte, nfe_documento::InfoNfeDocumento},
+};
+
+const PESO_REMETENTE: f64 = 30.0;
+const PESO_DESTINATARIO: f64 = 30.0;
+const PESO_TRANSPORTADORA: f64 = 20.0;
+const PESO_DATA: f64 = 10.0;
+const PESO_VALOR: f64 = 10.0;
+
+/// Linha da tabela "Vínculos Sugeridos": NF-e proposta para um CT-e sem NF-es.
+#[derive(Debug, Default, Serialize, Deserialize, Clone, Iterable)]
+pub struct InfoVinculoSugerido {
+    #[serde(rename = "Chave do CTe", default)]
+    pub cte: Option<String>,
+
+    #[serde(rename = "Chave da NFe", default)]
+    pub nfe: Option<String>,
+
+    /// Soma dos pesos das evidências, de 0 a 100.
+    #[serde(rename = "Confiança (%)", default)]
+    pub confianca: f64,
+
+    #[serde(
+        rename = "Evidências",
+        serialize_with = "serialize_vec_string",
+        default
+    )]
+    pub evidencias: Vec<String>,
+
+    /// Diferença absoluta entre as datas de emissão do CT-e e da NF-e.
+    #[serde(rename = "Diferença de Dias", default)]
+    pub diferenca_dias: Option<i64>,
+
+    #[serde(rename = "Valor da Carga", default)]
+    pub valor_carga: Option<f64>,
+
+    #[serde(rename = "Valor da NFe", default)]
+    pub valor_nfe: Option<f64>,
+
+    /// Diferença entre o valor da carga e o valor da NF-e, relativa ao valor da NF-e.
+    #[serde(rename = "Diferença de Valor (%)", default)]
+    pub diferenca_valor_pct: Option<f64>,
+}
+
+impl InfoExtension for InfoVinculoSugerido {}
+
+impl GetKey for InfoVinculoSugerido {
+    fn get_chave(&self) -> Option<String> {
+        self.cte.clone()
+    }
+}
+
+/// Partes do CT-e usadas na comparação, apenas com dígitos.
+struct PartesCte {
+    emitente: Option<String>,
+    remetente: Option<String>,
+    destinatario: Option<String>,
+    data_emissao: Option<NaiveDate>,
+    valor_carga: Option<f64>,
+}
+
+impl PartesCte {
+    fn new(info: &InfoCte) -> Self {
+        Self {
+            emitente: get_documento(&info.emitente_cnpj, &info.emitente_cpf),
+            remetente: get_documento(&info.remetente_cnpj, &info.remetente_cpf),
+            destinatario: get_documento(&info.destinatario_cnpj, &info.destinatario_cpf),
+            data_emissao: info.data_emissao,
+            valor_carga: info.valor_carga,
+        }
+    }
+
+    /// Pontua a NF-e; `None` se nenhuma parte coincide ou se as datas excedem a janela.
+    fn avaliar(&self, nfe: &InfoNfeDocumento, janela_dias: u32) -> Option<InfoVinculoSugerido> {
+        let iguais = |a: &Option<String>, b: Option<String>| a.is_some() && *a == b;
+
+        let remetente = iguais(
+            &self.remetente,
+            get_documento(&nfe.emitente_cnpj, &nfe.emitente_cpf),
+        );
+        let destinatario = iguais(
+            &self.destinatario,
+            get_documento(&nfe.destinatario_cnpj, &nfe.destinatario_cpf),
+        );
+        if !remetente && !destinatario {
+            return None;
+        }
+        let transportadora = iguais(
+            &self.emitente,
+            get_documento(&nfe.transportadora_cnpj, &nfe.transportadora_cpf),
+        );
+
+        let diferenca_dias = self
+            .data_emissao
+            .zip(nfe.data_emissao)
+            .map(|(cte, nfe)| (cte - nfe).num_days().abs());
+        if diferenca_dias.is_some_and(|dias| dias > i64::from(janela_dias)) {
+            return None;
+        }
+
+        let diferenca_valor = self
+            .valor_carga
+            .zip(nfe.valor_total_nfe)
+            .filter(|&(_, valor_nfe)| valor_nfe > 0.0)
+            .map(|(carga, valor_nfe)| (carga - valor_nfe).abs() / valor_nfe);
+
+        let mut confianca = 0.0;
+        let mut evidencias = Vec::new();
+        let partes = [
+            (remetente, PESO_REMETENTE, "Remetente = Emitente da NFe"),
+            (
+                destinatario,
+                PESO_DESTINATARIO,
+                "Destinatário = Destinatário da NFe",
+            ),
+            (
+                transportadora,
+                PESO_TRANSPORTADORA,
+                "Transportadora da NFe = Emitente do CTe",
+            ),
+        ];
+        for (coincide, peso, evidencia) in partes {
+            if coincide {
+                confianca += peso;
+                evidencias.push(evidencia.to_string());
+            }
+        }
+
+        if let Some(dias) = diferenca_dias {
+            confianca += PESO_DATA * (1.0 - dias as f64 / f64::from(janela_dias + 1));
+            evidencias.push(format!("Emissão com {dias} dia(s) de diferença"));
+        }
+
+        if let Some(diferenca) = diferenca_valor
+            && diferenca < 1.0
+        {
+            confianca += PESO_VALOR * (1.0 - diferenca);
+            evidencias.push(format!(
+                "Valor da carga com {:.2}% de diferença",
+                diferenca * 100.0
+            ));
+        }
+
+        Some(InfoVinculoSugerido {
+            nfe: nfe.nfe.clone(),
+            confianca: confianca.round_float(2),
+            evidencias,
+            diferenca_dias,
+            valor_carga: self.valor_carga,
+            valor_nfe: nfe.valor_total_nfe,
+            diferenca_valor_pct: diferenca_valor.map(|d| (d * 100.0).round_float(2)),
+            ..Default::default()
+        })
+    }
+}
+
+/// Propõe NF-es para os CT-es válidos sem nenhuma NF-e correlacionada.
+///
+/// Retorna as sugestões com confiança mínima de `confianca_minima` (fração de 0 a 1),
+/// ordenadas por CT-e e confiança decrescente.
+pub fn get_vinculos_sugeridos(
+    ctes: &[InfoCte],
+    documentos: &[InfoNfeDocumento],
+    correlacoes: &Correlacoes,
+    janela_dias: u32,
+    confianca_minima: f64,
+) -> Vec<InfoVinculoSugerido> {
+    // Confiança mínima na mesma escala de 0 a 100 da coluna "Confiança (%)"
+    let confianca_minima = (confianca_minima * 100.0).round_float(2);
+
+    // Índices por emitente e por destinatário das NF-es válidas
+    let mut por_emitente: HashMap<String, Vec<&InfoNfeDocumento>> = HashMap::new();
+    let mut por_destinatario: HashMap<String, Vec<&InfoNfeDocumento>> = HashMap::new();
+    for nfe in documentos
+        .iter()
+        .filter(|nfe| nfe.is_valid() && nfe.nfe.is_some())
+    {
+        if let Some(emitente) = get_documento(&nfe.emitente_cnpj, &nfe.emitente_cpf) {
+            por_emitente.entry(emitente).or_default().push(nfe);
+        }
+        if let Some(destinatario) = get_documento(&nfe.destinatario_cnpj, &nfe.destinatario_cpf) {
+            por_destinatario.entry(destinatario).or_default().push(nfe);
+        }
+    }
+
+    let sem_nfes = |info: &&InfoCte| {
+        info.is_valid()
+            && info.get_correlated_nfes().is_empty()
+            && info
+                .cte
+                .as_ref()
+                .is_some_and(|cte| correlacoes.cte_nfes.get(cte).is_none_or(|n| n.is_empty()))
+    };
+
+    let mut sugestoes: Vec<InfoVinculoSugerido> = ctes
+        .par_iter()
+        .filter(sem_nfes)
+        .flat_map_iter(|info| {
+            let partes = PartesCte::new(info);
+
+            // Chave da NFe -> NFe, sem repetir as encontradas pelos dois índices
+            let candidatas: BTreeMap<&str, &InfoNfeDocumento> = [
+                partes.remetente.as_ref().and_then(|r| por_emitente.get(r)),
+                partes
+                    .destinatario
+                    .as_ref()
+                    .and_then(|d| por_destinatario.get(d)),
+            ]
+            .into_iter()
+            .flatten()
+            .flatten()
+            .filter_map(|nfe| nfe.nfe.as_deref().map(|chave| (chave, *nfe)))
+            .collect();
+
+            candidatas
+                .into_values()
+                .filter_map(|nfe| partes.avaliar(nfe, janela_dias))
+                .filter(|sugestao| sugestao.confianca >= confianca_minima)
+                .map(|sugestao| InfoVinculoSugerido {
+                    cte: info.cte.clone(),
+                    ..sugestao
+                })
+                .collect::<Vec<_>>()
+        })
+        .collect();
+
+    sugestoes.par_sort_by(|a, b| {
+        a.cte
+            .cmp(&b.cte)
+            .then(b.confianca.total_cmp(&a.confianca))
+            .then(a.nfe.cmp(&b.nfe))
+    });
+    sugestoes
+}
+
+//----------------------------------------------------------------------------//
+//                                   Tests                                    //
+//----------------------------------------------------------------------------//
+
+/// Run tests with:
+/// cargo test -- --show-output tests_vinculos_sugeridos
+#[cfg(test)]
+mod tests_vinculos_sugeridos {
+    use super::*;
+    use crate::core::fixtures::{self, chaves, documento};
+
+    const TRANSPORTADORA: &str = "11.111.111/0001-11";
+    const REMETENTE: &str = "22.222.222/0001-22";
+    const DESTINATARIO: &str = "33.333.333/0001-33";
+    const OUTRO: &str = "44.444.444/0001-44";
+
+    fn nfe(chave: &str, emitente: &str, destinatario: &str, dia: u32) -> InfoNfeDocumento {
+        let mut documento = documento(chave);
+        documento.emitente_cnpj = Some(emitente.to_string());
+        documento.destinatario_cnpj = Some(destinatario.to_string());
+        documento.transportadora_cnpj = Some(TRANSPORTADORA.to_string());
+        documento.data_emissao = NaiveDate::from_ymd_opt(2025, 5, dia);
+        documento.valor_total_nfe = Some(1000.0);
+        documento
+    }
+
+    #[test]
+    /// `cargo test -- --show-output sugerir_vinculos`
+    fn sugerir_vinculos() {
+        let cte = |chave: &str, nfes: &[&str]| {
+            let mut info = fixtures::cte(chave);
+            info.emitente_cnpj = Some(TRANSPORTADORA.to_string());
+            info.remetente_cnpj = Some(REMETENTE.to_string());
+            info.destinatario_cnpj = Some(DESTINATARIO.to_string());
+            info.remetente_nfes = chaves(nfes);
+            info.data_emissao = NaiveDate::from_ymd_opt(2025, 5, 10);
+            info.valor_carga = Some(1000.0);
+            info
+        };
+
+        // cte2 declara NFe: não recebe sugestões
+        let ctes = vec![cte("cte1", &[]), cte("cte2", &["nfe1"])];
+        let documentos = vec![
+            nfe("nfe1", REMETENTE, DESTINATARIO, 10),
+            nfe("nfe2", REMETENTE, OUTRO, 8),
+            nfe("nfe3", OUTRO, OUTRO, 10),
+            nfe("nfe4", REMETENTE, DESTINATARIO, 20),
+        ];
+
+        let sugestoes = get_vinculos_sugeridos(&ctes, &documentos, &Correlacoes::default(), 5, 0.5);
+        println!("sugestoes: {sugestoes:#?}");
+
+        let resumo: Vec<(Option<&str>, Option<&str>, f64)> = sugestoes
+            .iter()
+            .map(|s| (s.cte.as_deref(), s.nfe.as_deref(), s.confianca))
+            .collect();
+
+        // nfe1: 30 + 30 + 20 + 10 + 10; nfe2: 30 + 20 + 10 * (1 - 2/6) + 10
+        // nfe3 não tem partes em comum; nfe4 está fora da janela de 5 dias
+        assert_eq!(
+            resumo,
+            [
+                (Some("cte1"), Some("nfe1"), 100.0),
+                (Some("cte1"), Some("nfe2"), 66.67),
+            ]
+        );
+        assert_eq!(sugestoes[1].diferenca_dias, Some(2));
+    }
+}
@@ -57,93 +57,151 @@ where
     P: AsRef<Path> + std::marker::Copy + std::fmt::Debug,
     T: Serialize + Deserialize<'de> + InfoExtension + Iterable + Sync,
 {
-    let mut logs = Vec::new();
-
     if lines.is_empty() {
-        return Ok(logs);
+        return Ok(Vec::new());
     }
 
-    let output_path = output_file.as_ref();
-    let file_name = output_path.to_string_lossy();
-
-    logs.push(format!("Generating Excel file: {}", file_name));
-
-    let mut workbook = Workbook::new();
-    let properties = get_properties()?;
-    workbook.set_properties(&properties);
-
-    let number_of_rows = lines.len();
-    let number_of_columns = T::get_headers().len();
-    let number_of_sheets = number_of_rows.div_ceil(MAX_NUMBER_OF_ROWS);
-
-    logs.push(format!(
-        "Info: Dataset '{}' contains {} rows and {} columns. Partitioning into {} worksheet chunk(s)...",
-        sheet_name, number_of_rows, number_of_columns, number_of_sheets
-    ));
-
-    if memory_mode.is_parallel() {
-        logs.push(
-            "Info: Starting concurrent worksheet generation across thread pool...".to_string(),
-        );
-
-        // Coleta os logs específicos das threads paralelas de forma segura
-        let worksheets = lines
-            .par_chunks(MAX_NUMBER_OF_ROWS)
-            .enumerate()
-            .map(
-                |(index, chunk)| -> XmlParserResult<(Worksheet, Vec<String>)> {
-                    let name = determine_sheet_name(sheet_name, index);
-                    let mut thread_logs = Vec::new();
-                    thread_logs.push(format!("Info: Thread working on worksheet '{}'...", name));
-
-                    let mut worksheet = Worksheet::new();
-                    worksheet.set_name(&name)?;
-                    populate_worksheet(&mut worksheet, chunk)?;
-                    Ok((worksheet, thread_logs))
-                },
-            )
-            .collect::<Result<Vec<(Worksheet, Vec<String>)>, _>>()?;
+    let mut workbook = XlsxWorkbook::new(memory_mode)?;
+    workbook.add_sheets(lines, sheet_name)?;
+    workbook.save(output_file)
+}
+
+/// Pasta de trabalho XLSX composta por uma ou mais tabelas, cada qual em suas próprias abas.
+///
+/// Usada diretamente para reunir CT-es, NF-es, e-Financeiras e tabelas derivadas
+/// em um único arquivo (`--planilha-unica`).
+pub struct XlsxWorkbook {
+    workbook: Workbook,
+    memory_mode: ExcelMemoryMode,
+    number_of_worksheets: usize,
+    logs: Vec<String>,
+}
+
+impl XlsxWorkbook {
+    /// Cria uma pasta de trabalho vazia com as propriedades de metadados do documento.
+    pub fn new(memory_mode: ExcelMemoryMode) -> XmlParserResult<Self> {
+        let mut workbook = Workbook::new();
+        let properties = get_properties()?;
+        workbook.set_properties(&properties);
+
+        Ok(Self {
+            workbook,
+            memory_mode,
+            number_of_worksheets: 0,
+            logs: Vec::new(),
+        })
+    }
 
-        for (worksheet, thread_logs) in worksheets {
-            logs.extend(thread_logs);
-            workbook.push_worksheet(worksheet);
+    /// Acrescenta as linhas em abas nomeadas a partir de `sheet_name`.
+    ///
+    /// Tabelas com mais de 1.000.000 de linhas são particionadas em abas numeradas.
+    /// Tabelas vazias não geram abas.
+    pub fn add_sheets<'de, T>(&mut self, lines: &[T], sheet_name: &str) -> XmlParserResult<()>
+    where
+        T: Serialize + Deserialize<'de> + InfoExtension + Iterable + Sync,
+    {
+        if lines.is_empty() {
+            return Ok(());
         }
-    } else {
+
+        let memory_mode = self.memory_mode;
+        let logs = &mut self.logs;
+
+        let number_of_rows = lines.len();
+        let number_of_columns = T::get_headers().len();
+        let number_of_sheets = number_of_rows.div_ceil(MAX_NUMBER_OF_ROWS);
+
         logs.push(format!(
-            "Info: Starting sequential worksheet generation (memory mode: {:?})...",
-            memory_mode
+            "Info: Dataset '{}' contains {} rows and {} columns. Partitioning into {} worksheet chunk(s)...",
+            sheet_name, number_of_rows, number_of_columns, number_of_sheets
         ));
 
-        let chunks = lines.chunks(MAX_NUMBER_OF_ROWS);
-        for (index, chunk) in chunks.enumerate() {
-            let name = determine_sheet_name(sheet_name, index);
+        if memory_mode.is_parallel() {
+            logs.push(
+                "Info: Starting concurrent worksheet generation across thread pool...".to_string(),
+            );
+
+            // Coleta os logs específicos das threads paralelas de forma segura
+            let worksheets = lines
+                .par_chunks(MAX_NUMBER_OF_ROWS)
+                .enumerate()
+                .map(
+                    |(index, chunk)| -> XmlParserResult<(Worksheet, Vec<String>)> {
+                        let name = determine_sheet_name(sheet_name, index);
+                        let mut thread_logs = Vec::new();
+                        thread_logs
+                            .push(format!("Info: Thread working on worksheet '{}'...", name));
+
+                        let mut worksheet = Worksheet::new();
+                        worksheet.set_name(&name)?;
+                        populate_worksheet(&mut worksheet, chunk)?;
+                        Ok((worksheet, thread_logs))
+                    },
+                )
+                .collect::<Result<Vec<(Worksheet, Vec<String>)>, _>>()?;
+
+            for (worksheet, thread_logs) in worksheets {
+                logs.extend(thread_logs);
+                self.workbook.push_worksheet(worksheet);
+            }
+        } else {
             logs.push(format!(
-                "Info: Writing worksheet '{}' sequentially...",
-                name
+                "Info: Starting sequential worksheet generation (memory mode: {:?})...",
+                memory_mode
             ));
 
-            let worksheet = memory_mode.add_worksheet_to_workbook(&mut workbook);
-            worksheet.set_name(&name)?;
-            populate_worksheet(worksheet, chunk)?;
+            let chunks = lines.chunks(MAX_NUMBER_OF_ROWS);
+            for (index, chunk) in chunks.enumerate() {
+                let name = determine_sheet_name(sheet_name, index);
+                logs.push(format!(
+                    "Info: Writing worksheet '{}' sequentially...",
+                    name
+                ));
+
+                let worksheet = memory_mode.add_worksheet_to_workbook(&mut self.workbook);
+                worksheet.set_name(&name)?;
+                populate_worksheet(worksheet, chunk)?;
+            }
         }
+
+        self.number_of_worksheets += number_of_sheets;
+
+        Ok(())
     }
 
-    logs.push("Info: Writing workbook data to disk...".to_string());
+    /// Grava a pasta de trabalho em disco e retorna os logs acumulados.
+    ///
+    /// Uma pasta de trabalho sem abas não gera arquivo.
+    pub fn save<P>(mut self, output_file: P) -> XmlParserResult<Vec<String>>
+    where
+        P: AsRef<Path>,
+    {
+        if self.number_of_worksheets == 0 {
+            return Ok(Vec::new());
+        }
+
+        let output_path = output_file.as_ref();
+        let file_name = output_path.to_string_lossy();
 
-    // Salva o resultado final no disco
-    workbook
-        .save(output_file)
-        .map_err(|xlsx_error| XmlParserError::ExcelWrite {
-            source: xlsx_error,
-            path: output_path.to_path_buf(),
-        })?;
+        let mut logs = vec![format!("Generating Excel file: {}", file_name)];
+        logs.append(&mut self.logs);
+        logs.push("Info: Writing workbook data to disk...".to_string());
 
-    logs.push(format!(
-        "Success: Excel document saved to '{}'\n",
-        file_name
-    ));
+        // Salva o resultado final no disco
+        self.workbook
+            .save(output_path)
+            .map_err(|xlsx_error| XmlParserError::ExcelWrite {
+                source: xlsx_error,
+                path: output_path.to_path_buf(),
+            })?;
 
-    Ok(logs)
+        logs.push(format!(
+            "Success: Excel document saved to '{}'\n",
+            file_name
+        ));
+
+        Ok(logs)
+    }
 }
 
 /// Helper para nomear planilhas segmentadas com base no índice.
@@ -352,7 +410,11 @@ where
         get_length_of_column_values(line, &mut max_length);
     }
 
-    for (index, len) in max_length {
+    // Campos não exportados (`#[serde(skip)]`) ficam após as colunas e são ignorados
+    for (index, len) in max_length
+        .into_iter()
+        .filter(|(index, _)| *index < column_names.len())
+    {
         let width = width_max.min(len);
         worksheet.set_column_width(index as u16, (width as f64) * adjustment)?;
     }
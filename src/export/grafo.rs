@@ -0,0 +1,534 @@
+//! # Exportação do Grafo de Correlações (DOT e GraphML)
+//!
+//! Apresenta as cadeias de subcontratação e redespacho como grafo: CT-es e NF-es são nós
+//! e as referências declaradas (NF-es transportadas e relações entre CT-es) são arestas.
+//!
+//! * DOT: para renderização com GraphViz (`dot -Tsvg grafo.dot -o grafo.svg`);
+//! * GraphML: para ferramentas de análise de redes (Gephi, yEd, Cytoscape).
+//!
+//! Documentos referenciados mas ausentes do lote são desenhados com contorno tracejado.
+//! O grafo pode ser exportado inteiro, restrito a uma cadeia de frete (`--grafo-cadeia`)
+//! ou à vizinhança de uma chave até N saltos (`--grafo-chave` e `--grafo-saltos`).
+
+use std::{
+    collections::{BTreeMap, HashSet, VecDeque},
+    fmt::Write as _,
+    fs,
+    path::{Path, PathBuf},
+};
+
+use crate::{
+    Arguments, DocsFiscais, TipoRelacaoCte, XmlParserError, XmlParserResult, get_componentes,
+};
+
+const CTE: &str = "CTe";
+const NFE: &str = "NFe";
+
+const VALIDO: &str = "Válido";
+const CANCELADO: &str = "Cancelado";
+const SUBSTITUIDO: &str = "Substituído ou Anulado";
+const NAO_ENCONTRADO: &str = "Não Encontrado";
+
+/// Documento fiscal (CT-e ou NF-e) lido no lote ou apenas referenciado.
+#[derive(Debug, Clone, PartialEq)]
+pub struct NoGrafo {
+    pub chave: String,
+    /// "CTe" ou "NFe".
+    pub tipo: &'static str,
+    pub numero: Option<u32>,
+    pub emitente: Option<String>,
+    pub valor: Option<f64>,
+    /// "Válido", "Cancelado", "Substituído ou Anulado" ou "Não Encontrado".
+    pub situacao: &'static str,
+    pub cadeia: Option<String>,
+}
+
+impl NoGrafo {
+    fn nao_encontrado(chave: &str, tipo: &'static str) -> Self {
+        Self {
+            chave: chave.to_string(),
+            tipo,
+            numero: None,
+            emitente: None,
+            valor: None,
+            situacao: NAO_ENCONTRADO,
+            cadeia: None,
+        }
+    }
+
+    /// Linhas do rótulo: tipo e número, chave, emitente, valor e situação.
+    fn rotulo(&self) -> Vec<String> {
+        let titulo = match self.numero {
+            Some(numero) => format!("{} nº {numero}", self.tipo),
+            None => self.tipo.to_string(),
+        };
+
+        [
+            Some(titulo),
+            Some(self.chave.clone()),
+            self.emitente.clone(),
+            self.valor.map(|valor| format!("Valor: {valor:.2}")),
+            Some(self.situacao.to_string()),
+        ]
+        .into_iter()
+        .flatten()
+        .collect()
+    }
+}
+
+/// Referência declarada entre dois documentos.
+#[derive(Debug, Clone, PartialEq)]
+pub struct ArestaGrafo {
+    /// CT-e que transporta a NF-e ou CT-e original da relação.
+    pub de: String,
+    /// NF-e transportada ou CT-e derivado da relação.
+    pub para: String,
+    /// "NFe Transportada" ou o tipo da relação entre CT-es (ver [`TipoRelacaoCte::descricao`]).
+    pub relacao: String,
+    /// Campo do XML ou evento que declara a referência.
+    pub fonte: String,
+}
+
+/// Grafo de documentos fiscais com as referências declaradas nos CT-es.
+#[derive(Debug, Default, Clone)]
+pub struct GrafoDocumentos {
+    pub nos: BTreeMap<String, NoGrafo>,
+    pub arestas: Vec<ArestaGrafo>,
+}
+
+impl GrafoDocumentos {
+    /// Monta o grafo completo: todos os CT-es e NF-es lidos e as chaves que eles referenciam.
+    ///
+    /// As cadeias de frete seguem os mesmos critérios de [`get_componentes`].
+    pub fn new(docs: &DocsFiscais, propagar: &[TipoRelacaoCte]) -> Self {
+        let componentes = get_componentes(&docs.ctes, propagar);
+        let mut grafo = Self::default();
+
+        for info in &docs.ctes {
+            let Some(cte) = &info.cte else {
+                continue;
+            };
+            grafo.nos.insert(
+                cte.clone(),
+                NoGrafo {
+                    chave: cte.clone(),
+                    tipo: CTE,
+                    numero: info.numero_da_nota,
+                    emitente: info.emitente_nome.clone(),
+                    valor: info.valor_total,
+                    situacao: if info.is_valid() {
+                        VALIDO
+                    } else if info.is_canceled() {
+                        CANCELADO
+                    } else {
+                        SUBSTITUIDO
+                    },
+                    cadeia: None,
+                },
+            );
+        }
+
+        for documento in &docs.nfes_documentos {
+            let Some(nfe) = &documento.nfe else {
+                continue;
+            };
+            grafo.nos.insert(
+                nfe.clone(),
+                NoGrafo {
+                    chave: nfe.clone(),
+                    tipo: NFE,
+                    numero: documento.numero_da_nota,
+                    emitente: documento.emitente_nome.clone(),
+                    valor: documento.valor_total_nfe,
+                    situacao: if documento.is_valid() {
+                        VALIDO
+                    } else {
+                        CANCELADO
+                    },
+                    cadeia: None,
+                },
+            );
+        }
+
+        let mut vistas: HashSet<(String, String, String)> = HashSet::new();
+        for info in &docs.ctes {
+            let Some(cte) = &info.cte else {
+                continue;
+            };
+
+            let nfes = [
+                (&info.remetente_nfes, "XML do CTe: rem/infNFe"),
+                (
+                    &info.nfes_vinculados,
+                    "XML do CTe: infCTeNorm/infDoc/infNFe",
+                ),
+                (
+                    &info.nfes_papel,
+                    "XML do CTe: infCTeNorm/infDoc/infNF ou infOutros",
+                ),
+            ]
+            .into_iter()
+            .flat_map(|(nfes, fonte)| nfes.iter().map(move |nfe| (nfe, fonte)))
+            .filter(|(nfe, _)| *nfe != cte)
+            .map(|(nfe, fonte)| {
+                let aresta = ArestaGrafo {
+                    de: cte.clone(),
+                    para: nfe.clone(),
+                    relacao: "NFe Transportada".to_string(),
+                    fonte: fonte.to_string(),
+                };
+                (aresta, NFE)
+            });
+
+            let ctes = info
+                .relacoes
+                .iter()
+                .filter(|relacao| relacao.original != relacao.derivado)
+                .map(|relacao| {
+                    let aresta = ArestaGrafo {
+                        de: relacao.original.clone(),
+                        para: relacao.derivado.clone(),
+                        relacao: relacao.tipo.descricao().to_string(),
+                        fonte: relacao.fonte.to_string(),
+                    };
+                    (aresta, CTE)
+                });
+
+            for (aresta, tipo_destino) in nfes.chain(ctes).collect::<Vec<_>>() {
+                let chave = (
+                    aresta.de.clone(),
+                    aresta.para.clone(),
+                    aresta.relacao.clone(),
+                );
+                if !vistas.insert(chave) {
+                    continue;
+                }
+                // A NF-e é sempre o destino; nas relações, a outra ponta é um CT-e
+                for (chave, tipo) in [(&aresta.de, CTE), (&aresta.para, tipo_destino)] {
+                    grafo
+                        .nos
+                        .entry(chave.clone())
+                        .or_insert_with(|| NoGrafo::nao_encontrado(chave, tipo));
+                }
+                grafo.arestas.push(aresta);
+            }
+        }
+
+        for no in grafo.nos.values_mut() {
+            no.cadeia = componentes.get(&no.chave).cloned();
+        }
+
+        grafo
+    }
+
+    pub fn is_empty(&self) -> bool {
+        self.nos.is_empty()
+    }
+
+    /// Subgrafo com os documentos de uma cadeia de frete.
+    pub fn cadeia(&self, cadeia: &str) -> Self {
+        let chaves: HashSet<&str> = self
+            .nos
+            .values()
+            .filter(|no| no.cadeia.as_deref() == Some(cadeia))
+            .map(|no| no.chave.as_str())
+            .collect();
+        self.subgrafo(&chaves)
+    }
+
+    /// Subgrafo com os documentos a até `saltos` arestas de `chave`, em qualquer sentido.
+    pub fn vizinhanca(&self, chave: &str, saltos: usize) -> Self {
+        if !self.nos.contains_key(chave) {
+            return Self::default();
+        }
+
+        let mut vizinhos: BTreeMap<&str, Vec<&str>> = BTreeMap::new();
+        for aresta in &self.arestas {
+            vizinhos.entry(&aresta.de).or_default().push(&aresta.para);
+            vizinhos.entry(&aresta.para).or_default().push(&aresta.de);
+        }
+
+        let mut chaves: HashSet<&str> = HashSet::from([chave]);
+        let mut fila: VecDeque<(&str, usize)> = VecDeque::from([(chave, 0)]);
+        while let Some((atual, distancia)) = fila.pop_front() {
+            if distancia == saltos {
+                continue;
+            }
+            for &vizinho in vizinhos.get(atual).into_iter().flatten() {
+                if chaves.insert(vizinho) {
+                    fila.push_back((vizinho, distancia + 1));
+                }
+            }
+        }
+
+        self.subgrafo(&chaves)
+    }
+
+    fn subgrafo(&self, chaves: &HashSet<&str>) -> Self {
+        Self {
+            nos: self
+                .nos
+                .iter()
+                .filter(|(chave, _)| chaves.contains(chave.as_str()))
+                .map(|(chave, no)| (chave.clone(), no.clone()))
+                .collect(),
+            arestas: self
+                .arestas
+                .iter()
+                .filter(|a| chaves.contains(a.de.as_str()) && chaves.contains(a.para.as_str()))
+                .cloned()
+                .collect(),
+        }
+    }
+
+    /// Representação no formato DOT (GraphViz).
+    pub fn to_dot(&self) -> String {
+        let mut dot = String::from("digraph correlacoes {\n");
+        dot.push_str("    rankdir=LR;\n");
+        dot.push_str("    node [fontname=\"Helvetica\", fontsize=10];\n");
+        dot.push_str("    edge [fontname=\"Helvetica\", fontsize=9];\n");
+
+        for no in self.nos.values() {
+            let rotulo: Vec<String> = no.rotulo().iter().map(|linha| escape_dot(linha)).collect();
+            let forma = if no.tipo == CTE { "box" } else { "ellipse" };
+            let estilo = if no.situacao == NAO_ENCONTRADO {
+                "dashed"
+            } else {
+                "solid"
+            };
+            let cor = match no.situacao {
+                CANCELADO => "red",
+                SUBSTITUIDO => "orange",
+                _ => "black",
+            };
+            let _ = writeln!(
+                dot,
+                "    \"{}\" [label=\"{}\", shape={forma}, style={estilo}, color={cor}];",
+                escape_dot(&no.chave),
+                rotulo.join("\\n"),
+            );
+        }
+
+        for aresta in &self.arestas {
+            let _ = writeln!(
+                dot,
+                "    \"{}\" -> \"{}\" [label=\"{}\", tooltip=\"{}\"];",
+                escape_dot(&aresta.de),
+                escape_dot(&aresta.para),
+                escape_dot(&aresta.relacao),
+                escape_dot(&aresta.fonte),
+            );
+        }
+
+        dot.push_str("}\n");
+        dot
+    }
+
+    /// Representação no formato GraphML, com os atributos dos nós e das arestas.
+    pub fn to_graphml(&self) -> String {
+        let mut xml = String::from("<?xml version=\"1.0\" encoding=\"UTF-8\"?>\n");
+        xml.push_str("<graphml xmlns=\"http://graphml.graphdrawing.org/xmlns\">\n");
+
+        let chaves = [
+            ("tipo", "node", "string"),
+            ("numero", "node", "int"),
+            ("emitente", "node", "string"),
+            ("valor", "node", "double"),
+            ("situacao", "node", "string"),
+            ("cadeia", "node", "string"),
+            ("relacao", "edge", "string"),
+            ("fonte", "edge", "string"),
+        ];
+        for (id, dominio, tipo) in chaves {
+            let _ = writeln!(
+                xml,
+                "  <key id=\"{id}\" for=\"{dominio}\" attr.name=\"{id}\" attr.type=\"{tipo}\"/>"
+            );
+        }
+
+        xml.push_str("  <graph id=\"correlacoes\" edgedefault=\"directed\">\n");
+
+        for no in self.nos.values() {
+            let _ = writeln!(xml, "    <node id=\"{}\">", escape_xml(&no.chave));
+            let dados = [
+                ("tipo", Some(no.tipo.to_string())),
+                ("numero", no.numero.map(|numero| numero.to_string())),
+                ("emitente", no.emitente.clone()),
+                ("valor", no.valor.map(|valor| format!("{valor:.2}"))),
+                ("situacao", Some(no.situacao.to_string())),
+                ("cadeia", no.cadeia.clone()),
+            ];
+            for (chave, valor) in dados {
+                if let Some(valor) = valor {
+                    let _ = writeln!(
+                        xml,
+                        "      <data key=\"{chave}\">{}</data>",
+                        escape_xml(&valor)
+                    );
+                }
+            }
+            xml.push_str("    </node>\n");
+        }
+
+        for aresta in &self.arestas {
+            let _ = writeln!(
+                xml,
+                "    <edge source=\"{}\" target=\"{}\">",
+                escape_xml(&aresta.de),
+                escape_xml(&aresta.para),
+            );
+            let _ = writeln!(
+                xml,
+                "      <data key=\"relacao\">{}</data>",
+                escape_xml(&aresta.relacao)
+            );
+            let _ = writeln!(
+                xml,
+                "      <data key=\"fonte\">{}</data>",
+                escape_xml(&aresta.fonte)
+            );
+            xml.push_str("    </edge>\n");
+        }
+
+        xml.push_str("  </graph>\n</graphml>\n");
+        xml
+    }
+}
+
+fn escape_dot(texto: &str) -> String {
+    texto.replace('\\', "\\\\").replace('"', "\\\"")
+}
+
+fn escape_xml(texto: &str) -> String {
+    texto
+        .replace('&', "&amp;")
+        .replace('<', "&lt;")
+        .replace('>', "&gt;")
+        .replace('"', "&quot;")
+        .replace('\'', "&apos;")
+}
+
+/// Grava o grafo de correlações (`--grafo`) nos arquivos `.dot` e `.graphml`.
+///
+/// `caminho` é o nome base do arquivo, sem extensão. Retorna as mensagens de log.
+pub fn exportar_grafo(
+    docs: &DocsFiscais,
+    caminho: &Path,
+    arguments: &Arguments,
+) -> XmlParserResult<Vec<String>> {
+    let mut grafo = GrafoDocumentos::new(docs, &arguments.propagar_nfes);
+
+    if let Some(cadeia) = &arguments.grafo_cadeia {
+        grafo = grafo.cadeia(cadeia);
+    }
+
+    if let Some(chave) = &arguments.grafo_chave {
+        grafo = grafo.vizinhanca(chave, arguments.grafo_saltos);
+    }
+
+    if grafo.is_empty() {
+        return Ok(vec![
+            "Warning: empty correlation graph, no file generated\n".to_string(),
+        ]);
+    }
+
+    let mut logs = Vec::new();
+    for (extensao, conteudo) in [("dot", grafo.to_dot()), ("graphml", grafo.to_graphml())] {
+        // Acrescenta a extensão ao nome: `with_extension` trocaria o trecho após um ponto do modelo.
+        let mut arquivo = caminho.as_os_str().to_os_string();
+        arquivo.push(format!(".{extensao}"));
+        let arquivo = PathBuf::from(arquivo);
+        fs::write(&arquivo, conteudo).map_err(|source| XmlParserError::IoContext {
+            source,
+            path: arquivo.clone(),
+        })?;
+        logs.push(format!(
+            "Success: correlation graph saved to '{}'",
+            arquivo.display()
+        ));
+    }
+
+    Ok(logs)
+}
+
+//----------------------------------------------------------------------------//
+//                                   Tests                                    //
+//----------------------------------------------------------------------------//
+
+/// Run tests with:
+/// cargo test -- --show-output tests_grafo_export
+#[cfg(test)]
+mod tests_grafo_export {
+    use super::*;
+    use crate::{
+        RelacaoCte,
+        xml_structs::{cte::InfoCte, nfe_documento::InfoNfeDocumento},
+    };
+
+    fn docs() -> DocsFiscais {
+        let cte = |chave: &str, nfes: &[&str]| {
+            let mut info = InfoCte::default();
+            info.cte = Some(chave.to_string());
+            info.emitente_nome = Some("Transportadora \"A\"".to_string());
+            info.remetente_nfes = nfes.iter().map(|nfe| nfe.to_string()).collect();
+            info.valor_total = Some(150.0);
+            info
+        };
+
+        // Eventos são anexados ao CT-e original; o XML, ao CT-e derivado
+        let mut cte1 = cte("cte1", &["nfe1", "nfe9"]);
+        cte1.relacoes.push(RelacaoCte::evento(
+            "cte1",
+            "cte2",
+            TipoRelacaoCte::Subcontratacao,
+            "evCTeSubcontratacao",
+        ));
+        let mut cte3 = cte("cte3", &[]);
+        cte3.relacoes.push(RelacaoCte::xml(
+            "cte2",
+            "cte3",
+            TipoRelacaoCte::Complementar,
+            "infCteComp",
+        ));
+
+        let mut nfe1 = InfoNfeDocumento::default();
+        nfe1.nfe = Some("nfe1".to_string());
+
+        DocsFiscais {
+            ctes: vec![cte1, cte("cte2", &[]), cte3, cte("cte5", &[])],
+            nfes_documentos: vec![nfe1],
+            ..Default::default()
+        }
+    }
+
+    #[test]
+    /// `cargo test -- --show-output grafo_dot_e_graphml`
+    fn grafo_dot_e_graphml() {
+        let grafo = GrafoDocumentos::new(&docs(), &TipoRelacaoCte::PROPAGACAO_PADRAO);
+        assert_eq!(grafo.nos.len(), 6);
+        assert_eq!(grafo.arestas.len(), 4);
+        assert_eq!(grafo.nos["nfe9"].situacao, NAO_ENCONTRADO);
+        assert_eq!(grafo.nos["nfe9"].cadeia.as_deref(), Some("cte1"));
+
+        let dot = grafo.to_dot();
+        println!("{dot}");
+        assert!(dot.contains(
+            "\"nfe9\" [label=\"NFe\\nnfe9\\nNão Encontrado\", shape=ellipse, style=dashed"
+        ));
+        assert!(dot.contains("Transportadora \\\"A\\\""));
+        assert!(dot.contains("\"cte1\" -> \"cte2\" [label=\"Subcontratação\""));
+
+        let graphml = grafo.to_graphml();
+        assert!(graphml.contains("<data key=\"emitente\">Transportadora &quot;A&quot;</data>"));
+        assert_eq!(graphml.matches("<edge ").count(), 4);
+
+        let cadeia = grafo.cadeia("cte1");
+        assert_eq!(cadeia.nos.len(), 5);
+        assert!(!cadeia.nos.contains_key("cte5"));
+
+        let vizinhanca = grafo.vizinhanca("nfe1", 2);
+        let chaves: Vec<&str> = vizinhanca.nos.keys().map(String::as_str).collect();
+        assert_eq!(chaves, ["cte1", "cte2", "nfe1", "nfe9"]);
+        assert_eq!(vizinhanca.arestas.len(), 3);
+    }
+}
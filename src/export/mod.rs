@@ -1,9 +1,13 @@
 mod csv;
 mod excel;
 mod format;
+mod grafo;
 mod memory_mode;
+mod tabela;
 
 pub use csv::*;
 pub use excel::*;
 pub use format::*;
+pub use grafo::*;
 pub use memory_mode::*;
+pub use tabela::*;
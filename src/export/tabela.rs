@@ -0,0 +1,65 @@
+//! # Tabelas Exportáveis
+//!
+//! Reúne as tabelas do repositório, de tipos distintos, em uma única lista que alimenta
+//! a gravação em CSV, em arquivos XLSX separados e na planilha única (`--planilha-unica`).
+
+use serde::{Deserialize, Serialize};
+use std::path::{Path, PathBuf};
+use struct_iterable::Iterable;
+
+use crate::{CsvWriter, ExcelMemoryMode, InfoExtension, XlsxWorkbook, XmlParserResult};
+
+/// Tabela gravável em CSV e XLSX, independente do tipo das suas linhas.
+pub trait Tabela: Sync {
+    /// Verifica se a tabela não possui linhas.
+    fn is_empty(&self) -> bool;
+
+    /// Grava as linhas em um arquivo CSV. Tabelas vazias não geram arquivo.
+    fn gravar_csv(&self, output_file: PathBuf, delimiter: char) -> XmlParserResult<()>;
+
+    /// Acrescenta as linhas como abas nomeadas a partir de `aba` (ver [`XlsxWorkbook::add_sheets`]).
+    fn adicionar(&self, workbook: &mut XlsxWorkbook, aba: &str) -> XmlParserResult<()>;
+}
+
+impl<'de, T> Tabela for Vec<T>
+where
+    T: Serialize + Deserialize<'de> + InfoExtension + Iterable + Sync,
+{
+    fn is_empty(&self) -> bool {
+        self.as_slice().is_empty()
+    }
+
+    fn gravar_csv(&self, output_file: PathBuf, delimiter: char) -> XmlParserResult<()> {
+        CsvWriter::new(output_file, delimiter).write(self)
+    }
+
+    fn adicionar(&self, workbook: &mut XlsxWorkbook, aba: &str) -> XmlParserResult<()> {
+        workbook.add_sheets(self, aba)
+    }
+}
+
+/// Tabela do repositório com o nome do seu arquivo de saída e o da sua aba.
+pub struct TabelaExportada<'a> {
+    /// Nome da tabela no arquivo de saída (marcador `{tipo}` de `--nome-arquivo`).
+    pub tipo: &'static str,
+    /// Nome da aba na planilha XLSX.
+    pub aba: &'static str,
+    pub linhas: &'a dyn Tabela,
+}
+
+impl<'a> TabelaExportada<'a> {
+    pub fn new(tipo: &'static str, aba: &'static str, linhas: &'a dyn Tabela) -> Self {
+        Self { tipo, aba, linhas }
+    }
+
+    /// Grava a tabela em um arquivo XLSX próprio. Tabelas vazias não geram arquivo.
+    pub fn gravar_xlsx(
+        &self,
+        output_file: &Path,
+        memory_mode: ExcelMemoryMode,
+    ) -> XmlParserResult<Vec<String>> {
+        let mut workbook = XlsxWorkbook::new(memory_mode)?;
+        self.linhas.adicionar(&mut workbook, self.aba)?;
+        workbook.save(output_file)
+    }
+}
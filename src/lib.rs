@@ -20,14 +20,31 @@ src/
 │   ├── docs_fiscais.rs      # Repositório consolidado (DocsFiscais)
 │   ├── information.rs       # Despachante de parsers (Information / StructExtension)
 │   ├── event.rs             # Vinculação de eventos e cancelamentos
+│   ├── cancelamentos.rs     # Data, protocolo, justificativa e prazo dos cancelamentos
+│   ├── historico.rs         # Tabela "Eventos" e coluna "Último Evento" por documento
+│   ├── inconsistencias.rs   # Eventos órfãos, fora de sequência, anteriores à emissão ou não homologados
+│   ├── correcoes.rs         # Cartas de Correção (CC-e) e correções aplicadas aos CT-es
+│   ├── entregas.rs          # Comprovante e insucesso de entrega de CT-es e NF-es
+│   ├── manifestacao.rs      # Manifestação do Destinatário (estado final por NF-e)
 │   ├── graph.rs             # Algoritmo DSU e fecho transitivo CT-e <-> NF-e
-│   └── aggregations.rs      # Agrupamentos analíticos por NCM/Tomador
+│   ├── explicacao.rs        # Menor caminho que justifica a correlação entre duas chaves
+│   ├── aggregations.rs      # Agrupamentos analíticos por NCM/Tomador
+│   ├── cadeias.rs           # Cadeias de frete (componentes conexas) e tabela "Cadeias"
+│   ├── credito.rs           # Crédito de PIS/COFINS sobre fretes
+│   ├── papel.rs             # Correlação de CT-es com Notas Fiscais em papel (infNF/infOutros)
+│   ├── perspectiva.rs       # Papel e direção (entrada/saída) da empresa auditada
+│   ├── rateio.rs            # Rateio do frete de CT-es sobre itens de NF-es
+│   ├── referencias.rs       # Referências entre NF-es (NFref): devoluções e complementares
+│   ├── relacionamentos.rs   # Tabelas normalizadas CTe x NFe, CTe x CTe e CTe x NCM
+│   └── sugestoes.rs         # Vínculos CTe x NFe sugeridos por evidências (--sugerir-vinculos)
 │
 ├── export/                  # GERADORES DE SAÍDA TABULAR
 │   ├── mod.rs
 │   ├── excel.rs             # Serialização e streaming para XLSX (rust_xlsxwriter)
 │   ├── format.rs            # Estilos, fontes e regras visuais de células
+│   ├── grafo.rs             # Grafo de correlações em DOT (GraphViz) e GraphML
 │   ├── memory_mode.rs       # Perfis de consumo de RAM (Constant, Low, Memory)
+│   ├── tabela.rs            # Lista única das tabelas exportadas (CSV, XLSX e planilha única)
 │   └── csv.rs               # Gravador otimizado de CSV
 │
 ├── validation/              # AUDITORIA E INTEGRIDADE DE SCHEMAS
@@ -61,6 +78,7 @@ src/
     ├── integrated_dev_env.rs
     ├── nfe.rs
     ├── nfe_detalhamento.rs
+    ├── nfe_documento.rs
     ├── nfe_evento.rs
     ├── pagamento.rs
     └── ret_evento.rs
@@ -1,6 +1,10 @@
 use execution_time::ExecutionTime;
-use indicatif::MultiProgress;
-use std::{path::PathBuf, process};
+use indicatif::{MultiProgress, ProgressBar};
+use rayon::prelude::*;
+use std::{
+    path::{Path, PathBuf},
+    process,
+};
 use walkdir::DirEntry;
 
 use read_xml::*;
@@ -72,7 +76,9 @@ fn main() {
 /// 4. **Vinculação de Eventos**: Aplica cancelamentos e complementos aos documentos raiz.
 /// 5. **Deduplicação e Ordenação**: Remove duplicidades e ordena os registros contábeis.
 /// 6. **Cruzamento de Grafos**: Executa a correlação entre fretes (CT-e) e cargas (NF-e).
-/// 7. **Exportação de Dados**: Gera arquivos `.txt` (chaves), `.csv` e `.xlsx`.
+/// 7. **Exportação de Dados**: Gera arquivos `.txt` (chaves), `.csv` e `.xlsx`;
+///    com `--cnpj-base`, também separados em Entradas, Saídas e Não Relacionados;
+///    com `--empresas`, um conjunto de arquivos por CNPJ base.
 ///
 /// # Erros
 ///
@@ -128,6 +134,34 @@ fn run(arguments: &Arguments) -> XmlParserResult<()> {
                 &docs_fiscais.cancel_nfe,
             );
         });
+        s.spawn(|_| {
+            adicionar_eventos_nfe(
+                &mut docs_fiscais.nfes_documentos,
+                &docs_fiscais.eventos_nfe,
+                &docs_fiscais.cancel_nfe,
+            );
+        });
+        s.spawn(|_| {
+            adicionar_eventos_nfe(
+                &mut docs_fiscais.pagamentos,
+                &docs_fiscais.eventos_nfe,
+                &docs_fiscais.cancel_nfe,
+            );
+        });
+        s.spawn(|_| {
+            adicionar_eventos_nfe(
+                &mut docs_fiscais.duplicatas,
+                &docs_fiscais.eventos_nfe,
+                &docs_fiscais.cancel_nfe,
+            );
+        });
+        s.spawn(|_| {
+            adicionar_eventos_nfe(
+                &mut docs_fiscais.volumes,
+                &docs_fiscais.eventos_nfe,
+                &docs_fiscais.cancel_nfe,
+            );
+        });
         s.spawn(|_| {
             adicionar_eventos_cte(
                 &mut docs_fiscais.ctes,
@@ -137,107 +171,194 @@ fn run(arguments: &Arguments) -> XmlParserResult<()> {
         });
     });
 
+    // Data, protocolo, justificativa e prazo dos cancelamentos
+    docs_fiscais.add_cancelamentos();
+
+    // Linha do tempo de eventos de cada documento (tabela "Eventos")
+    docs_fiscais.add_historico_eventos();
+
+    // Eventos órfãos, fora de sequência, anteriores à emissão ou não homologados
+    docs_fiscais.add_inconsistencias_eventos();
+
+    // Cartas de Correção e, com --aplicar-cce, correções aplicadas aos CTes
+    docs_fiscais.add_cartas_correcao(arguments);
+
+    // Comprovante e insucesso de entrega de CTes e NFes
+    docs_fiscais.add_entregas();
+
     // 7. Deduplicação, ordenação contábil e resolução do grafo de correlações
     docs_fiscais.unique();
     docs_fiscais.sort();
     docs_fiscais.get_correlations(arguments);
 
+    // Classificação de cada NFe/CTe em relação à empresa auditada (--cnpj-base)
+    if let Some(cnpj_base) = &arguments.cnpj_base {
+        docs_fiscais.aplicar_perspectiva(cnpj_base);
+    }
+
+    // Diretório e nomes dos arquivos de saída (--output-dir / --nome-arquivo)
+    let template = OutputTemplate::from_arguments(arguments);
+    template.create_dir()?;
+    let cnpj_base = arguments.cnpj_base.as_deref();
+
     // 8. Opcional: Gravação de arquivos particionados com as chaves encontradas (-l)
     if let Some(size) = arguments.linhas {
         let size_u = size.try_into()?;
-        docs_fiscais.print_ctes("ctes", size_u)?;
-        docs_fiscais.print_nfes("nfes", size_u)?;
+        docs_fiscais.print_ctes(template.dir().join("ctes"), size_u)?;
+        docs_fiscais.print_nfes(template.dir().join("nfes"), size_u)?;
+    }
+
+    // 9. Exportação tabular (CSV e XLSX) de todos os documentos
+    exportar_documentos(
+        &docs_fiscais,
+        template.output_filename(cnpj_base),
+        arguments,
+        &multi_progress,
+        &mut multi_progressbar,
+    )?;
+
+    // 10. Opcional: Grafo de correlações em DOT e GraphML (--grafo)
+    if arguments.exportar_grafo() {
+        let output = template.output_filename(cnpj_base);
+        exportar_grafo(&docs_fiscais, &output.caminho("grafo"), arguments)?
+            .iter()
+            .for_each(|line| eprintln!("{line}"));
     }
 
-    let mut output = OutputFilename::default();
+    // 11. Opcional: Arquivos separados em Entradas, Saídas e Não Relacionados (--cnpj-base)
+    if arguments.cnpj_base.is_some() {
+        for (direcao, docs) in docs_fiscais.separar_por_direcao() {
+            let mut output = template.output_filename(cnpj_base);
+            output.add_suffix(direcao.sufixo());
+            exportar_documentos(
+                &docs,
+                output,
+                arguments,
+                &multi_progress,
+                &mut multi_progressbar,
+            )?;
+        }
+    }
 
-    // 9. Opcional: Exportação tabular em formato CSV (-c / --csv)
+    // 12. Opcional: Um conjunto de arquivos por empresa (--empresas)
+    if let Some(empresas) = &arguments.empresas {
+        let cnpj_bases = if empresas.is_empty() {
+            docs_fiscais.get_cnpj_bases(arguments.min_documentos)
+        } else {
+            empresas.clone()
+        };
+
+        for cnpj_base in cnpj_bases {
+            exportar_documentos(
+                &docs_fiscais.filtrar_empresa(&cnpj_base),
+                template.output_filename_empresa(&cnpj_base),
+                arguments,
+                &multi_progress,
+                &mut multi_progressbar,
+            )?;
+        }
+    }
+
+    // 13. Exibição do tempo total de execução (-t / --time)
+    if arguments.time {
+        timer.print_elapsed_time();
+    }
+
+    Ok(())
+}
+
+/// Grava as tabelas de um repositório de documentos em CSV (-c) e XLSX (exceto com -a).
+///
+/// Cada chamada registra novas barras de progresso, de modo que o mesmo repositório
+/// (ou subconjuntos dele) possa ser exportado com nomes de arquivo distintos.
+fn exportar_documentos(
+    docs_fiscais: &DocsFiscais,
+    mut output: OutputFilename,
+    arguments: &Arguments,
+    multi_progress: &MultiProgress,
+    multi_progressbar: &mut MultiProgressBar,
+) -> XmlParserResult<()> {
+    // Tabelas não vazias, na ordem dos arquivos e das abas
+    let tabelas: Vec<TabelaExportada> = docs_fiscais
+        .tabelas()
+        .into_iter()
+        .filter(|tabela| !tabela.linhas.is_empty())
+        .collect();
+
+    // 1. Opcional: Exportação tabular em formato CSV (-c / --csv)
     if arguments.csv {
         output.set_extension("csv");
-        multi_progressbar.add_print_csv(&multi_progress, docs_fiscais.total())?;
-
-        /// Função interna auxiliar para gravação genérica de CSV com feedback na barra.
-        fn exportar_tabela_csv<T: serde::Serialize>(
-            caminho: &std::path::Path,
-            dados: &[T],
-            delimitador: char,
-            barra_progresso: &indicatif::ProgressBar,
-        ) {
-            let writer = CsvWriter::new(caminho.to_path_buf(), delimitador);
-            if writer.write(dados).is_ok() {
-                barra_progresso.inc(1);
-            }
-        }
+        multi_progressbar.add_print_csv(multi_progress, tabelas.len())?;
 
         let delimiter = arguments.delimiter;
         let pb = &multi_progressbar.show_csval;
 
-        // Escrita concorrente dos três arquivos CSV
-        rayon::scope(|s| {
-            s.spawn(|_| exportar_tabela_csv(&output.ctes, &docs_fiscais.ctes, delimiter, pb));
-            s.spawn(|_| exportar_tabela_csv(&output.nfes, &docs_fiscais.nfes, delimiter, pb));
-            s.spawn(|_| {
-                exportar_tabela_csv(&output.efin, &docs_fiscais.efinanceiras, delimiter, pb)
-            });
+        // Escrita concorrente dos arquivos CSV
+        tabelas.par_iter().for_each(|tabela| {
+            let caminho = output.caminho(tabela.tipo);
+            if tabela.linhas.gravar_csv(caminho, delimiter).is_ok() {
+                pb.inc(1);
+            }
         });
 
         pb.finish();
     }
 
-    // 10. Exportação padrão em formato Excel XLSX (a menos que `-a` esteja ativo)
+    // 2. Exportação padrão em formato Excel XLSX (a menos que `-a` esteja ativo)
     if !arguments.avoid {
         output.set_extension("xlsx");
-        multi_progressbar.add_print_xls(&multi_progress, docs_fiscais.total())?;
+        multi_progressbar.add_print_xls(multi_progress, tabelas.len())?;
 
         let memory_mode = arguments.memory_mode;
-        let mut ctes_res = Ok(Vec::new());
-        let mut nfes_res = Ok(Vec::new());
-        let mut efin_res = Ok(Vec::new());
-
-        // Geração concorrente das planilhas na thread-pool do Rayon
-        rayon::scope(|s| {
-            s.spawn(|_| {
-                ctes_res = write_xlsx(&docs_fiscais.ctes, "CTes", &output.ctes, memory_mode);
-                if ctes_res.is_ok() {
-                    multi_progressbar.show_excel.inc(1);
-                }
-            });
-            s.spawn(|_| {
-                nfes_res = write_xlsx(&docs_fiscais.nfes, "NFes", &output.nfes, memory_mode);
-                if nfes_res.is_ok() {
-                    multi_progressbar.show_excel.inc(1);
-                }
-            });
-            s.spawn(|_| {
-                efin_res = write_xlsx(
-                    &docs_fiscais.efinanceiras,
-                    "eFinanceiras",
-                    &output.efin,
-                    memory_mode,
-                );
-                if efin_res.is_ok() {
-                    multi_progressbar.show_excel.inc(1);
-                }
-            });
-        });
+        let pb = &multi_progressbar.show_excel;
+
+        let resultados: Vec<XmlParserResult<Vec<String>>> = if arguments.planilha_unica {
+            // Todas as tabelas como abas de um único arquivo (--planilha-unica)
+            let caminho = output.caminho("planilhas");
+            vec![exportar_planilha_unica(&tabelas, &caminho, memory_mode, pb)]
+        } else {
+            // Geração concorrente das planilhas na thread-pool do Rayon
+            tabelas
+                .par_iter()
+                .map(|tabela| {
+                    let resultado = tabela.gravar_xlsx(&output.caminho(tabela.tipo), memory_mode);
+                    if resultado.is_ok() {
+                        pb.inc(1);
+                    }
+                    resultado
+                })
+                .collect()
+        };
 
-        multi_progressbar.show_excel.finish();
+        pb.finish();
 
         // Propaga o primeiro erro eventual antes de imprimir mensagens de sucesso
-        let ctes_logs = ctes_res?;
-        let nfes_logs = nfes_res?;
-        let efin_logs = efin_res?;
+        let logs = resultados
+            .into_iter()
+            .collect::<XmlParserResult<Vec<Vec<String>>>>()?;
 
         // Descarrega no stderr os logs descritivos de criação dos arquivos
-        for line in ctes_logs.into_iter().chain(nfes_logs).chain(efin_logs) {
+        for line in logs.into_iter().flatten() {
             eprintln!("{line}");
         }
     }
 
-    // 11. Exibição do tempo total de execução (-t / --time)
-    if arguments.time {
-        timer.print_elapsed_time();
+    Ok(())
+}
+
+/// Reúne as tabelas do repositório como abas de uma única planilha XLSX.
+fn exportar_planilha_unica(
+    tabelas: &[TabelaExportada],
+    caminho: &Path,
+    memory_mode: ExcelMemoryMode,
+    barra_progresso: &ProgressBar,
+) -> XmlParserResult<Vec<String>> {
+    let mut workbook = XlsxWorkbook::new(memory_mode)?;
+
+    for tabela in tabelas {
+        tabela.linhas.adicionar(&mut workbook, tabela.aba)?;
+        barra_progresso.inc(1);
     }
 
-    Ok(())
+    workbook.save(caminho)
 }
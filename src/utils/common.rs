@@ -3,7 +3,7 @@
 //! Funções auxiliares sem efeitos colaterais para sanitização, parsing temporal
 //! e formatação customizada de tipos de dados fiscais.
 
-use chrono::NaiveDate;
+use chrono::{Local, NaiveDate, NaiveDateTime};
 use claudiofsr_lib::StrExtension;
 use quick_xml::reader::Reader;
 use serde::Serializer;
@@ -18,31 +18,154 @@ use xml_schema_generator::{Options, into_struct};
 
 use crate::{Arguments, XmlParserError, XmlParserResult};
 
-/// Representa os caminhos de saída para os arquivos gerados (planilhas e CSVs).
+/// Modelo padrão do nome dos arquivos de saída (ver [`OutputTemplate`]).
+pub const OUTPUT_TEMPLATE: &str = "documentos_fiscais-{tipo}";
+
+/// Representa os caminhos de saída para os arquivos gerados (planilhas, CSVs e grafo).
+///
+/// O caminho de cada arquivo é obtido pelo nome da tabela (marcador `{tipo}`) com
+/// [`OutputFilename::caminho`], após aplicar os sufixos e a extensão definidos em lote.
 #[derive(Debug, Clone)]
 pub struct OutputFilename {
-    pub ctes: PathBuf,
-    pub nfes: PathBuf,
-    pub efin: PathBuf,
+    template: OutputTemplate,
+    cnpj: Option<String>,
+    suffixes: Vec<String>,
+    extension: Option<String>,
 }
 
 impl OutputFilename {
-    /// Altera em lote a extensão dos três arquivos de saída.
+    /// Caminho de saída da tabela `tipo`: p. ex. "ctes", "grafo" ou "planilhas".
+    pub fn caminho(&self, tipo: &str) -> PathBuf {
+        // O nome é composto por concatenação: pontos do modelo (p. ex. "rel.{data}")
+        // não são confundidos com a extensão.
+        let mut nome = self
+            .template
+            .render(tipo, self.cnpj.as_deref())
+            .into_os_string();
+
+        for suffix in &self.suffixes {
+            nome.push(format!("-{suffix}"));
+        }
+
+        if let Some(extension) = &self.extension {
+            nome.push(format!(".{extension}"));
+        }
+
+        PathBuf::from(nome)
+    }
+
+    /// Altera em lote a extensão de todos os arquivos de saída.
     #[inline]
     pub fn set_extension(&mut self, extension: &str) {
-        self.ctes.set_extension(extension);
-        self.nfes.set_extension(extension);
-        self.efin.set_extension(extension);
+        self.extension = Some(extension.to_string());
+    }
+
+    /// Acrescenta `-{suffix}` ao nome de todos os arquivos de saída, preservando a extensão.
+    ///
+    /// Exemplo: `documentos_fiscais-ctes.xlsx` -> `documentos_fiscais-ctes-entradas.xlsx`.
+    pub fn add_suffix(&mut self, suffix: &str) {
+        self.suffixes.push(suffix.to_string());
     }
 }
 
 impl Default for OutputFilename {
     fn default() -> Self {
+        OutputTemplate::default().output_filename(None)
+    }
+}
+
+/// Diretório, modelo de nome e instante da execução que definem os arquivos de saída.
+///
+/// Marcadores do modelo: `{tipo}`, `{data}`, `{cnpj}` e `{run}` (ver `--nome-arquivo`).
+#[derive(Debug, Clone)]
+pub struct OutputTemplate {
+    dir: PathBuf,
+    template: String,
+    data: String,
+    run: String,
+}
+
+impl Default for OutputTemplate {
+    fn default() -> Self {
+        Self::new(None, OUTPUT_TEMPLATE, Local::now().naive_local())
+    }
+}
+
+impl OutputTemplate {
+    /// Cria o modelo de saída com o instante de início da execução.
+    pub fn new(dir: Option<&Path>, template: &str, inicio: NaiveDateTime) -> Self {
         Self {
-            ctes: PathBuf::from("documentos_fiscais-ctes"),
-            nfes: PathBuf::from("documentos_fiscais-nfes"),
-            efin: PathBuf::from("documentos_fiscais-efinanceiras"),
+            dir: dir.map(Path::to_path_buf).unwrap_or_default(), // vazio: diretório atual
+            template: template.to_string(),
+            data: inicio.format("%Y-%m-%d").to_string(),
+            run: inicio.format("%Y%m%d_%H%M%S").to_string(),
+        }
+    }
+
+    /// Modelo de saída definido pelas opções `--output-dir` e `--nome-arquivo`.
+    pub fn from_arguments(arguments: &Arguments) -> Self {
+        Self::new(
+            arguments.output_dir.as_deref(),
+            &arguments.nome_arquivo,
+            Local::now().naive_local(),
+        )
+    }
+
+    /// Diretório onde os arquivos são gravados.
+    pub fn dir(&self) -> &Path {
+        &self.dir
+    }
+
+    /// Cria o diretório de saída, caso não exista.
+    pub fn create_dir(&self) -> XmlParserResult<()> {
+        std::fs::create_dir_all(&self.dir).map_err(|err| XmlParserError::IoContext {
+            source: err,
+            path: self.dir.clone(),
+        })
+    }
+
+    /// Substitui os marcadores do modelo para a tabela `tipo`.
+    ///
+    /// Na ausência de CNPJ, o marcador `{cnpj}` é removido junto com um separador adjacente.
+    fn render(&self, tipo: &str, cnpj: Option<&str>) -> PathBuf {
+        let mut nome = self.template.clone();
+
+        match cnpj {
+            Some(cnpj) => nome = nome.replace("{cnpj}", cnpj),
+            None => {
+                for token in ["-{cnpj}", "_{cnpj}", "{cnpj}-", "{cnpj}_", "{cnpj}"] {
+                    nome = nome.replace(token, "");
+                }
+            }
+        }
+
+        let nome = nome
+            .replace("{tipo}", tipo)
+            .replace("{data}", &self.data)
+            .replace("{run}", &self.run);
+
+        self.dir.join(nome)
+    }
+
+    /// Caminhos de saída de todas as tabelas.
+    pub fn output_filename(&self, cnpj: Option<&str>) -> OutputFilename {
+        OutputFilename {
+            template: self.clone(),
+            cnpj: cnpj.map(str::to_string),
+            suffixes: Vec::new(),
+            extension: None,
+        }
+    }
+
+    /// Caminhos de saída das tabelas de uma empresa (`--empresas`).
+    ///
+    /// Se o modelo não contiver `{cnpj}`, o CNPJ base é acrescentado como sufixo.
+    pub fn output_filename_empresa(&self, cnpj_base: &str) -> OutputFilename {
+        let mut output = self.output_filename(Some(cnpj_base));
+        if !self.template.contains("{cnpj}") {
+            output.add_suffix(cnpj_base);
         }
+        output
     }
 }
 
@@ -60,6 +183,18 @@ where
     }
 }
 
+/// Traduz um código de tabela da SEFAZ para o formato descritivo `"código - descrição"`.
+///
+/// Códigos ausentes da tabela são preservados sem descrição para não ocultar
+/// valores novos ou fora do leiaute.
+pub fn descrever_codigo(codigo: &Option<String>, tabela: &[(&str, &str)]) -> Option<String> {
+    let codigo = codigo.as_deref().map(str::trim).filter(|c| !c.is_empty())?;
+    match tabela.iter().find(|(cod, _)| *cod == codigo) {
+        Some((cod, descricao)) => Some(format!("{cod} - {descricao}")),
+        None => Some(codigo.to_string()),
+    }
+}
+
 /// Converte datas no formato textual `YYYY-MM-DD` ou `YYYY-MM-DDTHH:MM:SS` para [`NaiveDate`].
 pub fn get_naive_date_from_yyyy_mm_dd<T>(date: &Option<T>) -> Option<NaiveDate>
 where
@@ -180,6 +315,94 @@ mod tests_utils {
     // NFe Esquemas XML
     // https://www.nfe.fazenda.gov.br/portal/listaConteudo.aspx?tipoConteudo=BMPFMBoln3w=
 
+    #[test]
+    /// `cargo test -- --show-output descrever_codigo_de_tabela`
+    fn descrever_codigo_de_tabela() {
+        let tabela = [("0", "Entrada"), ("1", "Saída")];
+
+        assert_eq!(
+            descrever_codigo(&Some(" 1 ".to_string()), &tabela),
+            Some("1 - Saída".to_string())
+        );
+        assert_eq!(
+            descrever_codigo(&Some("7".to_string()), &tabela),
+            Some("7".to_string())
+        );
+        assert_eq!(descrever_codigo(&Some("  ".to_string()), &tabela), None);
+        assert_eq!(descrever_codigo(&None, &tabela), None);
+    }
+
+    #[test]
+    /// `cargo test -- --show-output acrescentar_sufixo_aos_arquivos`
+    fn acrescentar_sufixo_aos_arquivos() {
+        let mut output = OutputFilename::default();
+        output.add_suffix("entradas");
+        output.set_extension("xlsx");
+        assert_eq!(
+            output.caminho("ctes"),
+            PathBuf::from("documentos_fiscais-ctes-entradas.xlsx")
+        );
+
+        // Com extensão já definida, o sufixo é inserido antes dela
+        let mut output = OutputFilename::default();
+        output.set_extension("csv");
+        output.add_suffix("saidas");
+        assert_eq!(
+            output.caminho("nfes"),
+            PathBuf::from("documentos_fiscais-nfes-saidas.csv")
+        );
+    }
+
+    #[test]
+    /// `cargo test -- --show-output modelo_de_nomes_de_saida`
+    fn modelo_de_nomes_de_saida() {
+        let inicio = NaiveDate::from_ymd_opt(2025, 3, 7)
+            .and_then(|data| data.and_hms_opt(14, 5, 9))
+            .unwrap();
+        let dir = Path::new("/tmp/auditoria");
+
+        let template = OutputTemplate::new(Some(dir), "{cnpj}-{tipo}-{data}-{run}", inicio);
+        let output = template.output_filename(Some("12345678"));
+        assert_eq!(
+            output.caminho("nfes-documentos"),
+            dir.join("12345678-nfes-documentos-2025-03-07-20250307_140509")
+        );
+
+        // Sem CNPJ, o marcador é omitido com o separador adjacente
+        let output = template.output_filename(None);
+        assert_eq!(
+            output.caminho("ctes"),
+            dir.join("ctes-2025-03-07-20250307_140509")
+        );
+
+        // Modelo padrão: mesmos nomes de antes, no diretório atual
+        let template = OutputTemplate::new(None, OUTPUT_TEMPLATE, inicio);
+        let mut output = template.output_filename_empresa("12345678");
+        output.set_extension("xlsx");
+        assert_eq!(
+            output.caminho("efinanceiras"),
+            PathBuf::from("documentos_fiscais-efinanceiras-12345678.xlsx")
+        );
+        assert_eq!(
+            output.caminho("planilhas"),
+            PathBuf::from("documentos_fiscais-planilhas-12345678.xlsx")
+        );
+
+        // Pontos no modelo não são tratados como extensão
+        let template = OutputTemplate::new(None, "rel.{data}-{tipo}", inicio);
+        let mut output = template.output_filename_empresa("12345678");
+        output.set_extension("csv");
+        assert_eq!(
+            output.caminho("ctes"),
+            PathBuf::from("rel.2025-03-07-ctes-12345678.csv")
+        );
+        let output = template.output_filename(None);
+        assert_eq!(
+            output.caminho("grafo"),
+            PathBuf::from("rel.2025-03-07-grafo")
+        );
+    }
+
     #[test]
     /// https://docs.rs/xml_schema_generator/latest/xml_schema_generator/
     ///
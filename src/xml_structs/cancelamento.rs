@@ -43,6 +43,19 @@ impl CancelExt for Option<Cancelamento> {
     }
 }
 
+/// Blocos `infCanc` do retorno homologado e do pedido de cancelamento, nesta ordem.
+///
+/// Os dados da homologação (data, protocolo) prevalecem sobre os do pedido.
+pub fn blocos_inf_canc<'a>(
+    pedido: &'a Option<Cancelamento>,
+    retorno: &'a Option<Retencao>,
+) -> impl Iterator<Item = &'a InfoCancelamento> {
+    retorno
+        .iter()
+        .map(|retorno| &retorno.inf_canc)
+        .chain(pedido.iter().map(|pedido| &pedido.inf_canc))
+}
+
 /// Estrutura correspondente ao evento ou pedido de Cancelamento de documento fiscal (`<cancNFe>` / `<cancCTe>`).
 #[derive(Debug, Serialize, Deserialize)]
 pub struct Cancelamento {
@@ -8,8 +8,10 @@
 //! e limpa (`InfoCteCancel`).
 
 use crate::{
-    Arguments, GetKey, InfoExtension, Information, StructExtension,
-    xml_structs::cancelamento::{CancelExt, Cancelamento, Retencao},
+    Arguments, GetKey, InfoExtension, Information, OptExt, StructExtension,
+    xml_structs::cancelamento::{
+        CancelExt, Cancelamento, InfoCancelamento, Retencao, blocos_inf_canc,
+    },
 };
 use chrono::NaiveDate;
 use serde::{Deserialize, Serialize};
@@ -31,6 +33,18 @@ pub struct InfoCteCancel {
     /// Indicador lógico se o documento fiscal correspondente está cancelado.
     #[serde(default)]
     pub cancelado: bool,
+
+    /// Data e hora da homologação do cancelamento (`dhRecbto`).
+    #[serde(default)]
+    pub dh_cancelamento: Option<String>,
+
+    /// Protocolo de homologação do cancelamento (`nProt`).
+    #[serde(default)]
+    pub protocolo: Option<String>,
+
+    /// Justificativa do cancelamento (`xJust`).
+    #[serde(default)]
+    pub justificativa: Option<String>,
 }
 
 /// Implementação do traço de formatação de metadados para geração do Excel.
@@ -94,12 +108,21 @@ impl ProcCancCte {
         self.get_cte().is_some()
     }
 
+    /// Primeiro valor não vazio do campo entre o retorno homologado e o pedido.
+    fn get_campo(&self, campo: fn(&InfoCancelamento) -> &Option<String>) -> Option<String> {
+        blocos_inf_canc(&self.canc_cte, &self.ret_canc_cte)
+            .find_map(|inf_canc| campo(inf_canc).get_not_empty())
+    }
+
     /// Consolida os dados desserializados em uma estrutura simplificada `InfoCteCancel`.
     pub fn get_info(&self) -> InfoCteCancel {
         InfoCteCancel {
             cte: self.get_cte(),
             dh_recebimento: self.get_dh_recebimento(),
             cancelado: self.informacao_de_cancelamento(),
+            dh_cancelamento: self.get_campo(|inf_canc| &inf_canc.dh_recbto),
+            protocolo: self.get_campo(|inf_canc| &inf_canc.n_prot),
+            justificativa: self.get_campo(|inf_canc| &inf_canc.x_just),
         }
     }
 }
@@ -7,8 +7,10 @@
 //! tabulares intermediárias (`InfoNfeCancel`) para posterior compilação de relatórios e planilhas.
 
 use crate::{
-    Arguments, GetKey, InfoExtension, Information, StructExtension,
-    xml_structs::cancelamento::{CancelExt, Cancelamento, Retencao},
+    Arguments, GetKey, InfoExtension, Information, OptExt, StructExtension,
+    xml_structs::cancelamento::{
+        CancelExt, Cancelamento, InfoCancelamento, Retencao, blocos_inf_canc,
+    },
 };
 use chrono::NaiveDate;
 use serde::{Deserialize, Serialize};
@@ -26,6 +28,18 @@ pub struct InfoNfeCancel {
 
     /// Indicador se a NF-e correspondente está cancelada.
     pub cancelado: bool,
+
+    /// Data e hora da homologação do cancelamento (`dhRecbto`).
+    #[serde(rename = "dh_cancelamento", default)]
+    pub dh_cancelamento: Option<String>,
+
+    /// Protocolo de homologação do cancelamento (`nProt`).
+    #[serde(rename = "protocolo", default)]
+    pub protocolo: Option<String>,
+
+    /// Justificativa do cancelamento (`xJust`).
+    #[serde(rename = "justificativa", default)]
+    pub justificativa: Option<String>,
 }
 
 impl InfoExtension for InfoNfeCancel {}
@@ -79,12 +93,21 @@ impl ProcCancNfe {
         self.get_nfe().is_some()
     }
 
+    /// Primeiro valor não vazio do campo entre o retorno homologado e o pedido.
+    fn get_campo(&self, campo: fn(&InfoCancelamento) -> &Option<String>) -> Option<String> {
+        blocos_inf_canc(&self.canc_nfe, &self.ret_canc_nfe)
+            .find_map(|inf_canc| campo(inf_canc).get_not_empty())
+    }
+
     /// Compila os metadados do XML na estrutura consolidada `InfoNfeCancel`.
     pub fn get_info(&self) -> InfoNfeCancel {
         InfoNfeCancel {
             nfe: self.get_nfe(),
             dh_recebimento: self.get_dh_recebimento(),
             cancelado: self.informacao_de_cancelamento(),
+            dh_cancelamento: self.get_campo(|inf_canc| &inf_canc.dh_recbto),
+            protocolo: self.get_campo(|inf_canc| &inf_canc.n_prot),
+            justificativa: self.get_campo(|inf_canc| &inf_canc.x_just),
         }
     }
 }
@@ -5,9 +5,116 @@
 //! suas respectivas parcelas representadas por duplicatas (`<dup>`).
 
 use chrono::NaiveDate;
+use rust_xlsxwriter::serialize_option_datetime_to_excel;
 use serde::{Deserialize, Serialize};
+use struct_iterable::Iterable;
 
-use crate::OptExt;
+use crate::{GetID, GetKey, InfoExtension, OptExt};
+
+/// Linha da tabela "Duplicatas": uma por parcela (`<dup>`) da NF-e.
+///
+/// Cada parcela é vinculada à chave da NF-e e à fatura (`<fat>`) que a originou.
+/// Uma NF-e com fatura e sem duplicatas gera uma única linha contendo apenas a fatura.
+#[derive(Debug, Default, Serialize, Deserialize, Clone, Iterable)]
+pub struct InfoDuplicata {
+    /// CNPJ do Emitente formatado.
+    #[serde(rename = "CNPJ do Emitente", default)]
+    pub emitente_cnpj: Option<String>,
+
+    /// CPF do Emitente formatado.
+    #[serde(rename = "CPF do Emitente", default)]
+    pub emitente_cpf: Option<String>,
+
+    /// CNPJ do Destinatário formatado.
+    #[serde(rename = "CNPJ do Destinatário", default)]
+    pub destinatario_cnpj: Option<String>,
+
+    /// CPF do Destinatário formatado.
+    #[serde(rename = "CPF do Destinatário", default)]
+    pub destinatario_cpf: Option<String>,
+
+    /// Chave de acesso da NF-e a que a duplicata pertence.
+    #[serde(rename = "Chave do Documento Fiscal", default)]
+    pub nfe: Option<String>,
+
+    /// Indicador se a NF-e correspondente foi cancelada.
+    #[serde(rename = "Cancelado", default)]
+    pub cancelado: Option<String>,
+
+    /// Número sequencial da Nota Fiscal.
+    #[serde(rename = "Nº do Documento Fiscal", default)]
+    pub numero_da_nota: Option<u32>,
+
+    /// Data de emissão da Nota Fiscal.
+    #[serde(
+        rename = "Data de Emissão",
+        serialize_with = "serialize_option_datetime_to_excel",
+        default
+    )]
+    pub data_emissao: Option<NaiveDate>,
+
+    /// Número da fatura (`nFat`).
+    #[serde(rename = "Nº da Fatura", default)]
+    pub n_fat: Option<String>,
+
+    /// Valor original da fatura (`vOrig`).
+    #[serde(rename = "Valor Original da Fatura", default)]
+    pub fatura_original: Option<f64>,
+
+    /// Valor do desconto da fatura (`vDesc`).
+    #[serde(rename = "Valor do Desconto da Fatura", default)]
+    pub fatura_desconto: Option<f64>,
+
+    /// Valor líquido da fatura (`vLiq`).
+    #[serde(rename = "Valor Líquido da Fatura", default)]
+    pub fatura_liquido: Option<f64>,
+
+    /// Posição da parcela dentro do grupo `<cobr>`, iniciando em 1.
+    #[serde(rename = "Nº de Ordem da Parcela", default)]
+    pub ordem: Option<u32>,
+
+    /// Número da duplicata (`nDup`).
+    #[serde(rename = "Nº da Duplicata", default)]
+    pub n_dup: Option<String>,
+
+    /// Data de vencimento da duplicata (`dVenc`).
+    #[serde(
+        rename = "Data de Vencimento",
+        serialize_with = "serialize_option_datetime_to_excel",
+        default
+    )]
+    pub vencimento: Option<NaiveDate>,
+
+    /// Valor da duplicata (`vDup`).
+    #[serde(rename = "Valor da Duplicata", default)]
+    pub valor: Option<f64>,
+}
+
+impl InfoDuplicata {
+    /// Determina se o documento é válido (chave presente e sem marcação de cancelamento).
+    pub fn is_valid(&self) -> bool {
+        self.nfe.is_some() && self.cancelado.is_none()
+    }
+}
+
+impl InfoExtension for InfoDuplicata {}
+
+impl GetKey for InfoDuplicata {
+    fn get_chave(&self) -> Option<String> {
+        self.nfe.clone()
+    }
+}
+
+impl GetID<Option<(String, u32)>> for InfoDuplicata {
+    /// id: (chave, nº de ordem da parcela)
+    fn get_id(&self) -> Option<(String, u32)> {
+        if let (Some(nfe), Some(ordem)) = (&self.nfe, self.ordem) {
+            Some((nfe.clone(), ordem))
+        } else {
+            None
+        }
+    }
+}
 
 /// Informações de Cobrança da Nota Fiscal Eletrônica (`<cobr>`).
 ///
@@ -43,6 +150,43 @@ impl Cobranca {
     pub fn get_fatura(&self) -> Option<&Fat> {
         self.fat.as_ref()
     }
+
+    /// Gera uma linha [`InfoDuplicata`] por `<dup>`, sem os dados de cabeçalho do documento.
+    ///
+    /// Na ausência de duplicatas, a fatura (se houver) é reportada em linha própria.
+    pub fn get_info_duplicatas(&self) -> Vec<InfoDuplicata> {
+        let fatura = InfoDuplicata {
+            n_fat: self.get_fatura().and_then(|f| f.n_fat.get_not_empty()),
+            fatura_original: self.get_fatura().and_then(|f| f.get_valor_original()),
+            fatura_desconto: self.get_fatura().and_then(|f| f.get_valor_desconto()),
+            fatura_liquido: self.get_fatura().and_then(|f| f.get_valor_liquido()),
+            ..Default::default()
+        };
+
+        let duplicatas = self.get_duplicatas();
+
+        if duplicatas.is_empty() {
+            return match self.fat {
+                Some(_) => vec![InfoDuplicata {
+                    ordem: Some(1),
+                    ..fatura
+                }],
+                None => Vec::new(),
+            };
+        }
+
+        duplicatas
+            .iter()
+            .zip(1..)
+            .map(|(dup, ordem)| InfoDuplicata {
+                ordem: Some(ordem),
+                n_dup: dup.n_dup.get_not_empty(),
+                vencimento: dup.get_data_vencimento(),
+                valor: dup.get_valor_duplicata(),
+                ..fatura.clone()
+            })
+            .collect()
+    }
 }
 
 /// Dados da Duplicata de Cobrança (`<dup>`).
@@ -32,15 +32,15 @@
 //! Make PDF files from XSD.
 
 use chrono::NaiveDate;
-use claudiofsr_lib::{BTreeSetExtension, UniqueElements};
+use claudiofsr_lib::{BTreeSetExtension, StrExtension, UniqueElements};
 use rust_xlsxwriter::serialize_option_datetime_to_excel;
 use serde::{Deserialize, Serialize, Serializer};
 use std::collections::BTreeSet;
 use struct_iterable::Iterable;
 
 use crate::{
-    Arguments, GetFirst, GetID, GetKey, InfoExtension, Information, KeysExtension, OptExt,
-    StructExtension, serialize_vec_string,
+    Arguments, DocumentoPapel, GetFirst, GetID, GetKey, InfoExtension, Information, KeysExtension,
+    OptExt, RelacaoCte, StructExtension, TipoRelacaoCte, get_divergencia, serialize_vec_string,
     xml_structs::{
         agente::{Agente, AgenteExtension, TOMADOR_DO_SERVICO},
         assinaturas::{ProtSignature, Signature},
@@ -79,7 +79,7 @@ pub struct InfoCte {
 
     /// Nome ou Razão Social do Emitente.
     #[serde(rename = "Nome ou Razão Social do Emitente", default)]
-    emitente_nome: Option<String>,
+    pub emitente_nome: Option<String>,
 
     /// Nome Fantasia do Emitente.
     #[serde(rename = "Nome Fantasia do Emitente", default)]
@@ -269,9 +269,89 @@ pub struct InfoCte {
     #[serde(rename = "Cancelado", default)]
     pub cancelado: Option<String>,
 
+    /// Data e hora da autorização de uso do CT-e (`dhRecbto` do protocolo).
+    #[serde(rename = "Data e Hora da Autorização", default)]
+    pub dh_autorizacao: Option<String>,
+
+    /// Data e hora do cancelamento (`dhEvento` do evento ou `dhRecbto` do `procCanc`).
+    #[serde(rename = "Data e Hora do Cancelamento", default)]
+    pub dh_cancelamento: Option<String>,
+
+    /// Protocolo de homologação do cancelamento pela SEFAZ (`nProt`).
+    #[serde(rename = "Protocolo do Cancelamento", default)]
+    pub protocolo_cancelamento: Option<String>,
+
+    /// Justificativa do cancelamento (`xJust`).
+    #[serde(rename = "Justificativa do Cancelamento", default)]
+    pub justificativa_cancelamento: Option<String>,
+
+    /// Evento de cancelamento (`Evento 110111` etc.) ou arquivo `procCancCTe`.
+    #[serde(rename = "Origem do Cancelamento", default)]
+    pub origem_cancelamento: Option<String>,
+
+    /// Cancelamento fora do prazo de 7 dias ou posterior a MDF-e ou CT-e substituto.
+    #[serde(rename = "Alerta do Cancelamento", default)]
+    pub alerta_cancelamento: Option<String>,
+
+    /// CT-es que substituem (`infCteSub`) ou anulam (`infCteAnu`) este CT-e.
+    ///
+    /// Ex.: "Substituído por <chave>" ou "Anulado por <chave>".
+    #[serde(rename = "Substituído ou Anulado", default)]
+    pub substituido: Option<String>,
+
+    /// CT-e anulado por este CT-e de anulação (`infCteAnu`), que não transporta carga.
+    ///
+    /// Ex.: "Anula <chave>".
+    #[serde(rename = "CTe de Anulação", default)]
+    pub anulacao: Option<String>,
+
+    /// Resumo do evento mais recente do CT-e (ver tabela "Eventos").
+    #[serde(rename = "Último Evento", default)]
+    pub ultimo_evento: Option<String>,
+
+    /// Colunas sobrescritas pela CC-e vigente (opção `--aplicar-cce`).
+    #[serde(rename = "Colunas Corrigidas por CC-e", default)]
+    pub colunas_corrigidas: Option<String>,
+
+    /// Data e hora da entrega, pelo Comprovante de Entrega mais recente não cancelado.
+    #[serde(rename = "Entregue em", default)]
+    pub entregue_em: Option<String>,
+
+    /// Dias decorridos entre a emissão do CTe e a entrega.
+    #[serde(rename = "Dias entre Emissão e Entrega", default)]
+    pub dias_ate_entrega: Option<i64>,
+
+    /// Documento de identificação de quem recebeu a carga (`nDoc`).
+    #[serde(rename = "Documento de Quem Recebeu a Entrega", default)]
+    pub entrega_documento: Option<String>,
+
+    /// Nome de quem recebeu a carga (`xNome`).
+    #[serde(rename = "Nome de Quem Recebeu a Entrega", default)]
+    pub entrega_nome: Option<String>,
+
+    /// Latitude do ponto de entrega, pelo mesmo Comprovante de Entrega.
+    #[serde(rename = "Latitude da Entrega", default)]
+    pub entrega_latitude: Option<String>,
+
+    /// Longitude do ponto de entrega, pelo mesmo Comprovante de Entrega.
+    #[serde(rename = "Longitude da Entrega", default)]
+    pub entrega_longitude: Option<String>,
+
+    /// Último insucesso na entrega não cancelado: `data: motivo, tentativa`.
+    #[serde(rename = "Insucesso na Entrega", default)]
+    pub insucesso_entrega: Option<String>,
+
+    /// Papéis da empresa auditada (`--cnpj-base`) no CT-e: emitente, tomador, remetente etc.
+    #[serde(rename = "Papel da Empresa Auditada", default)]
+    pub papel_empresa: Option<String>,
+
+    /// Direção da operação do ponto de vista da empresa auditada.
+    #[serde(rename = "Entrada ou Saída da Empresa Auditada", default)]
+    pub direcao: Option<String>,
+
     /// Número sequencial da Nota Fiscal/Documento.
     #[serde(rename = "Nº do Documento Fiscal", default)]
-    numero_da_nota: Option<u32>,
+    pub numero_da_nota: Option<u32>,
 
     /// Código CFOP aplicável ao serviço.
     #[serde(rename = "CFOP (Código Fiscal de Operações e Prestações)", default)]
@@ -307,7 +387,19 @@ pub struct InfoCte {
         serialize_with = "serialize_vec_string",
         default
     )]
-    nfes_vinculados: Vec<String>,
+    pub nfes_vinculados: Vec<String>,
+
+    /// Chaves de NF-es correlacionadas às Notas Fiscais em papel e outros documentos de `infDoc`.
+    #[serde(
+        rename = "NFes Correlacionadas a Documentos em Papel",
+        serialize_with = "serialize_vec_string",
+        default
+    )]
+    pub nfes_papel: Vec<String>,
+
+    /// Identificador da cadeia de frete (componente conexa) a que o CT-e pertence.
+    #[serde(rename = "Cadeia de Frete", default)]
+    pub cadeia: Option<String>,
 
     /// Chaves de NF-es de terceiros cruzadas e correlacionadas.
     #[serde(
@@ -329,6 +421,54 @@ pub struct InfoCte {
     #[serde(rename = "Valor Total de NFes", default)]
     pub valor_total_nfes: Option<f64>,
 
+    /// Medições da carga (`<infQ>`): unidade, tipo de medida e quantidade.
+    #[serde(
+        rename = "Quantidades da Carga",
+        serialize_with = "serialize_vec_string",
+        default
+    )]
+    pub quantidades_carga: Vec<String>,
+
+    /// Peso declarado da carga em kg, preferencialmente o peso bruto.
+    #[serde(rename = "Peso da Carga (kg)", default)]
+    pub peso_carga: Option<f64>,
+
+    /// Valor declarado da carga (`vCarga`).
+    #[serde(rename = "Valor da Carga", default)]
+    pub valor_carga: Option<f64>,
+
+    /// Soma dos pesos brutos (`pesoB`) das NF-es correlacionadas, se todas o informam.
+    #[serde(rename = "Peso Bruto de NFes (kg)", default)]
+    pub peso_bruto_nfes: Option<f64>,
+
+    /// Soma dos pesos líquidos (`pesoL`) das NF-es correlacionadas, se todas o informam.
+    #[serde(rename = "Peso Líquido de NFes (kg)", default)]
+    pub peso_liquido_nfes: Option<f64>,
+
+    /// Soma do valor total (`vNF`) das NF-es correlacionadas.
+    #[serde(rename = "Valor de NFes (vNF)", default)]
+    pub valor_nfes: Option<f64>,
+
+    /// Peso da carga menos o peso bruto das NF-es.
+    #[serde(rename = "Divergência de Peso (kg)", default)]
+    pub divergencia_peso: Option<f64>,
+
+    /// Divergência de peso relativa ao peso bruto das NF-es.
+    #[serde(rename = "Divergência de Peso (%)", default)]
+    pub divergencia_peso_pct: Option<f64>,
+
+    /// Valor da carga menos o valor total das NF-es.
+    #[serde(rename = "Divergência de Valor da Carga", default)]
+    pub divergencia_valor: Option<f64>,
+
+    /// Divergência de valor relativa ao valor total das NF-es.
+    #[serde(rename = "Divergência de Valor da Carga (%)", default)]
+    pub divergencia_valor_pct: Option<f64>,
+
+    /// "Sim" quando peso ou valor divergem acima da tolerância; "Não" caso contrário.
+    #[serde(rename = "Divergência de Carga", default)]
+    pub divergencia_carga: Option<String>,
+
     /// Valor de prestação total apurado para o serviço de transporte (vPrest).
     #[serde(rename = "Valor Total do CTe", default)]
     pub valor_total: Option<f64>,
@@ -368,6 +508,32 @@ pub struct InfoCte {
     /// Valor calculado correspondente ao ICMS debitado.
     #[serde(rename = "Valor de ICMS", default)]
     v_icms: Option<f64>,
+
+    /// Crédito de PIS/COFINS sobre o frete: "Sim", "Não" ou "Indeterminado".
+    #[serde(rename = "Crédito de PIS/COFINS sobre o Frete", default)]
+    pub credito_frete: Option<String>,
+
+    /// Parcela do valor do CT-e que compõe a base de cálculo do crédito.
+    #[serde(rename = "Valor da Base de Cálculo do Crédito", default)]
+    pub base_credito_frete: Option<f64>,
+
+    /// Justificativa da classificação do crédito sobre o frete.
+    #[serde(rename = "Fundamentação do Crédito sobre o Frete", default)]
+    pub fundamentacao_credito_frete: Option<String>,
+
+    /// Relações tipadas com outros CT-es (XML e eventos). Não exportada: ver tabela "CTes x CTes".
+    ///
+    /// Mantida como último campo para não deslocar as colunas exportadas.
+    #[serde(skip)]
+    pub relacoes: Vec<RelacaoCte>,
+
+    /// Caminho do arquivo XML de origem. Não exportado: usado em `--explicar`.
+    #[serde(skip)]
+    pub arquivo: Option<String>,
+
+    /// Referências sem chave de acesso (`infNF` e `infOutros`). Não exportadas: ver `nfes_papel`.
+    #[serde(skip)]
+    pub documentos_papel: Vec<DocumentoPapel>,
 }
 
 impl InfoCte {
@@ -376,9 +542,13 @@ impl InfoCte {
         self.cte.clone()
     }
 
-    /// Determina se o documento de transporte é válido.
+    /// Determina se o documento de transporte é válido: nem cancelado, nem substituído ou anulado,
+    /// nem CT-e de anulação.
     pub fn is_valid(&self) -> bool {
-        self.cte.is_some() && self.cancelado.is_none()
+        self.cte.is_some()
+            && self.cancelado.is_none()
+            && self.substituido.is_none()
+            && self.anulacao.is_none()
     }
 
     /// Determina se o documento de transporte foi cancelado.
@@ -471,6 +641,131 @@ impl InfoCte {
         }
     }
 
+    /// Verifica se o tomador do serviço pertence à empresa de CNPJ base (8 dígitos) informado.
+    pub fn tomador_pertence_a(&self, cnpj_base: &str) -> bool {
+        self.get_cnpj_cpf_base_do_tomador()
+            .is_some_and(|base| base.remove_non_digits() == cnpj_base)
+    }
+
+    /// Confronta o peso e o valor declarados na carga do CT-e com a soma das NF-es correlacionadas.
+    ///
+    /// Requer `peso_bruto_nfes` e `valor_nfes` previamente apurados. A carga é considerada
+    /// divergente quando a diferença percentual excede a `tolerancia` (em %) ou quando
+    /// há diferença sobre uma referência nula.
+    pub fn conferir_carga(&mut self, tolerancia: f64) {
+        (self.divergencia_peso, self.divergencia_peso_pct) =
+            get_divergencia(self.peso_carga, self.peso_bruto_nfes);
+        (self.divergencia_valor, self.divergencia_valor_pct) =
+            get_divergencia(self.valor_carga, self.valor_nfes);
+
+        let divergencias: Vec<bool> = [
+            (self.divergencia_peso, self.divergencia_peso_pct),
+            (self.divergencia_valor, self.divergencia_valor_pct),
+        ]
+        .into_iter()
+        .filter_map(|divergencia| match divergencia {
+            (_, Some(pct)) => Some(pct.abs() > tolerancia),
+            (Some(diferenca), None) => Some(diferenca != 0.0),
+            (None, None) => None,
+        })
+        .collect();
+
+        self.divergencia_carga = (!divergencias.is_empty()).then(|| {
+            let divergente = divergencias.into_iter().any(|d| d);
+            if divergente { "Sim" } else { "Não" }.to_string()
+        });
+    }
+
+    /// Aplica uma correção estruturada da CC-e (`grupoAlterado`, `campoAlterado` e
+    /// `valorAlterado`) sobre a coluna equivalente.
+    ///
+    /// Retorna o nome da coluna sobrescrita, ou `None` se o campo não tem coluna
+    /// correspondente ou se o valor corrigido é vazio ou inválido. O valor da prestação
+    /// não é corrigível por CC-e (art. 58-B, § 1º, do Convênio SINIEF 06/89).
+    pub fn aplicar_correcao(
+        &mut self,
+        grupo: &str,
+        campo: &str,
+        valor: &str,
+    ) -> Option<&'static str> {
+        let valor = valor.trim();
+        if valor.is_empty() {
+            return None;
+        }
+
+        let texto = match (grupo.trim(), campo.trim()) {
+            ("rem", "xNome") => (
+                &mut self.remetente_nome,
+                "Nome ou Razão Social do Remetente",
+            ),
+            ("rem", "xFant") => (&mut self.remetente_fantasia, "Nome Fantasia do Remetente"),
+            ("enderReme", "xMun") => (&mut self.remetente_ender_municipio, "Municípo do Remetente"),
+            ("enderReme", "UF") => (&mut self.remetente_ender_estado, "Estado do Remetente"),
+            ("dest", "xNome") => (
+                &mut self.destinatario_nome,
+                "Nome ou Razão Social do Destinatário",
+            ),
+            ("dest", "xFant") => (
+                &mut self.destinatario_fantasia,
+                "Nome Fantasia do Destinatário",
+            ),
+            ("enderDest", "xMun") => (
+                &mut self.destinatario_ender_municipio,
+                "Municípo do Destinatário",
+            ),
+            ("enderDest", "UF") => (
+                &mut self.destinatario_ender_estado,
+                "Estado do Destinatário",
+            ),
+            ("exped", "xNome") => (
+                &mut self.expedidor_nome,
+                "Nome ou Razão Social do Expedidor",
+            ),
+            ("exped", "xFant") => (&mut self.expedidor_fantasia, "Nome Fantasia do Expedidor"),
+            ("enderExped", "xMun") => {
+                (&mut self.expedidor_ender_municipio, "Municípo do Expedidor")
+            }
+            ("enderExped", "UF") => (&mut self.expedidor_ender_estado, "Estado do Expedidor"),
+            ("receb", "xNome") => (
+                &mut self.recebedor_nome,
+                "Nome ou Razão Social do Recebedor",
+            ),
+            ("receb", "xFant") => (&mut self.recebedor_fantasia, "Nome Fantasia do Recebedor"),
+            ("enderReceb", "xMun") => {
+                (&mut self.recebedor_ender_municipio, "Municípo do Recebedor")
+            }
+            ("enderReceb", "UF") => (&mut self.recebedor_ender_estado, "Estado do Recebedor"),
+            ("toma" | "toma4", "xNome") => {
+                (&mut self.tomador_nome, "Nome ou Razão Social do Tomador")
+            }
+            ("toma" | "toma4", "xFant") => (&mut self.tomador_fantasia, "Nome Fantasia do Tomador"),
+            ("enderToma", "xMun") => (&mut self.tomador_ender_municipio, "Municípo do Tomador"),
+            ("enderToma", "UF") => (&mut self.tomador_ender_estado, "Estado do Tomador"),
+            ("ide", "CFOP") => {
+                self.cfop = Some(valor.parse().ok()?);
+                return Some("CFOP (Código Fiscal de Operações e Prestações)");
+            }
+            ("infCarga", "vCarga") => {
+                self.valor_carga = Some(Some(valor).to_float64()?);
+                return Some("Valor da Carga");
+            }
+            _ => return None,
+        };
+
+        let (coluna, nome) = texto;
+        *coluna = Some(valor.to_string());
+        Some(nome)
+    }
+
+    /// Registra o arquivo XML de origem do CT-e e das relações declaradas nele.
+    pub fn set_arquivo(&mut self, xml_path: &std::path::Path) {
+        let arquivo = xml_path.display().to_string();
+        self.relacoes
+            .iter_mut()
+            .for_each(|relacao| relacao.arquivo = Some(arquivo.clone()));
+        self.arquivo = Some(arquivo);
+    }
+
     /// Remove duplicidades e ordena os vetores internos de referências de forma concorrente.
     pub fn get_unique_elements(&mut self) {
         [
@@ -482,26 +777,43 @@ impl InfoCte {
         .into_iter()
         .filter(|vec| vec.len() > 1)
         .for_each(|vec| vec.unique_ordered());
+
+        self.relacoes.sort();
+        self.relacoes.dedup_by(|a, b| a.mesma_relacao(b));
     }
 
     /// Consolida as chaves de todos os CT-es correlacionados declarados.
     pub fn get_correlated_ctes(&self) -> Vec<String> {
+        let referenciados = self
+            .relacoes
+            .iter()
+            .map(|relacao| relacao.referenciado().to_string());
+
         [&self.cte_complementar, &self.cte_anteriores]
             .into_iter()
             .flatten()
             .cloned()
+            .chain(referenciados)
             .collect::<BTreeSet<_>>()
             .to_vec()
     }
 
     /// Consolida as chaves de todas as NF-es correlacionadas declaradas.
+    ///
+    /// Inclui as NF-es encontradas para os documentos em papel (ver [`correlacionar_documentos_papel`]).
+    ///
+    /// [`correlacionar_documentos_papel`]: crate::correlacionar_documentos_papel
     pub fn get_correlated_nfes(&self) -> Vec<String> {
-        [&self.remetente_nfes, &self.nfes_vinculados]
-            .into_iter()
-            .flatten()
-            .cloned()
-            .collect::<BTreeSet<_>>()
-            .to_vec()
+        [
+            &self.remetente_nfes,
+            &self.nfes_vinculados,
+            &self.nfes_papel,
+        ]
+        .into_iter()
+        .flatten()
+        .cloned()
+        .collect::<BTreeSet<_>>()
+        .to_vec()
     }
 }
 
@@ -576,7 +888,9 @@ impl StructExtension for CteProc {
             println!("cte xml_path: {xml_path:?}");
             println!("cte_proc: {self:#?}\n");
         }
-        Information::Cte(Box::new(self.get_info()))
+        let mut info = self.get_info();
+        info.set_arquivo(xml_path);
+        Information::Cte(Box::new(info))
     }
 }
 
@@ -947,6 +1261,53 @@ impl CteProc {
             .collect()
     }
 
+    pub fn get_documentos_papel(&self) -> Vec<DocumentoPapel> {
+        self.cte
+            .inf_cte
+            .iter()
+            .flat_map(|info| info.get_documentos_papel())
+            .collect()
+    }
+
+    /// Relações tipadas declaradas no XML: este CT-e é o derivado e referencia o original.
+    pub fn get_relacoes(&self) -> Vec<RelacaoCte> {
+        let (Some(cte), Some(info)) = (self.get_cte(), self.cte.inf_cte.as_ref()) else {
+            return Vec::new();
+        };
+        let cte = cte.as_str();
+
+        let referencias: [(TipoRelacaoCte, &'static str, Vec<String>); 5] = [
+            (
+                TipoRelacaoCte::Complementar,
+                "infCteComp",
+                info.get_cte_comp(),
+            ),
+            (
+                TipoRelacaoCte::Multimodal,
+                "infServVinc",
+                info.get_cte_mult(),
+            ),
+            (TipoRelacaoCte::Anterior, "docAnt", info.get_info_de_ctes()),
+            (
+                TipoRelacaoCte::Substituicao,
+                "infCteSub",
+                info.get_cte_sub(),
+            ),
+            (TipoRelacaoCte::Anulacao, "infCteAnu", info.get_cte_anu()),
+        ];
+
+        referencias
+            .into_iter()
+            .flat_map(|(tipo, campo, originais)| {
+                originais
+                    .into_iter()
+                    .filter(|original| original != cte)
+                    .map(move |original| RelacaoCte::xml(&original, cte, tipo, campo))
+                    .collect::<Vec<_>>()
+            })
+            .collect()
+    }
+
     pub fn get_value_total(&self) -> Option<f64> {
         self.cte.inf_cte.as_ref().map(|info| info.v_prest.v_tprest)
     }
@@ -955,9 +1316,18 @@ impl CteProc {
         self.cte.inf_cte.as_ref().map(|inf| &inf.imposto)
     }
 
+    pub fn get_inf_carga(&self) -> Option<&InfCarga> {
+        self.cte
+            .inf_cte
+            .as_ref()
+            .and_then(|inf| inf.inf_cte_norm.as_ref())
+            .and_then(|norm| norm.inf_carga.as_ref())
+    }
+
     /// Executa a extração do subnó `InfCte` e monta a estrutura `InfoCte` consolidada.
     pub fn get_info(&self) -> InfoCte {
         let imposto = self.get_imposto();
+        let carga = self.get_inf_carga();
         let mut info_cte = InfoCte {
             versao: self.get_versao(),
 
@@ -1013,15 +1383,50 @@ impl CteProc {
             cte: self.get_cte(),
             doc_tipo: "CTe".to_string(),
             cancelado: None,
+            dh_autorizacao: self.prot_cte.inf_prot.dh_recbto.get_not_empty(),
+            dh_cancelamento: None,
+            protocolo_cancelamento: None,
+            justificativa_cancelamento: None,
+            origem_cancelamento: None,
+            alerta_cancelamento: None,
+            substituido: None,
+            anulacao: None,
+            ultimo_evento: None,
+            colunas_corrigidas: None,
+            entregue_em: None,
+            dias_ate_entrega: None,
+            entrega_documento: None,
+            entrega_nome: None,
+            entrega_latitude: None,
+            entrega_longitude: None,
+            insucesso_entrega: None,
+            papel_empresa: None,
+            direcao: None,
             numero_da_nota: self.get_numero_da_nota(),
             cfop: self.get_cfop(),
             data_emissao: self.get_data_emissao(),
             cte_complementar: self.get_cte_complementar(),
             cte_anteriores: self.get_cte_anteriores(),
             nfes_vinculados: self.get_nfes_vinculados(),
+            nfes_papel: Vec::new(),
+            relacoes: self.get_relacoes(),
+            arquivo: None,
+            documentos_papel: self.get_documentos_papel(),
+            cadeia: None,
             nfes: Vec::new(),
             ncm_descricao: Vec::new(),
             valor_total_nfes: None,
+            quantidades_carga: carga.map(|c| c.get_quantidades()).unwrap_or_default(),
+            peso_carga: carga.and_then(|c| c.get_peso_carga()),
+            valor_carga: carga.and_then(|c| c.get_valor_carga()),
+            peso_bruto_nfes: None,
+            peso_liquido_nfes: None,
+            valor_nfes: None,
+            divergencia_peso: None,
+            divergencia_peso_pct: None,
+            divergencia_valor: None,
+            divergencia_valor_pct: None,
+            divergencia_carga: None,
             valor_total: self.get_value_total(),
 
             aliq_pis: imposto.and_then(|i| i.get_aliq_pis()),
@@ -1033,6 +1438,10 @@ impl CteProc {
             v_bc_icms: imposto.and_then(|i| i.get_v_bc_icms()),
             aliq_icms: imposto.and_then(|i| i.get_aliq_icms()),
             v_icms: imposto.and_then(|i| i.get_v_icms()),
+
+            credito_frete: None,
+            base_credito_frete: None,
+            fundamentacao_credito_frete: None,
         };
 
         info_cte.corrigir_codigo_do_tomador();
@@ -1323,6 +1732,21 @@ impl InfCte {
             .collect()
     }
 
+    fn get_cte_sub(&self) -> Vec<String> {
+        self.inf_cte_norm
+            .iter()
+            .flat_map(|info_normal| info_normal.inf_cte_sub.iter())
+            .flat_map(|sub| Some(sub.ch_cte.as_str()).get_key())
+            .collect()
+    }
+
+    fn get_cte_anu(&self) -> Vec<String> {
+        self.inf_cte_anu
+            .iter()
+            .flat_map(|anu| anu.ch_cte.get_key())
+            .collect()
+    }
+
     fn get_info_de_ctes(&self) -> Vec<String> {
         match self.inf_cte_norm.as_ref() {
             Some(info) => info.get_docs_anteriores(),
@@ -1336,6 +1760,13 @@ impl InfCte {
             None => Vec::new(),
         }
     }
+
+    fn get_documentos_papel(&self) -> Vec<DocumentoPapel> {
+        match self.inf_cte_norm.as_ref() {
+            Some(info) => info.get_documentos_papel(),
+            None => Vec::new(),
+        }
+    }
 }
 
 /// Bloco de Valores da Prestação de Serviço de Transporte (`<vPrest>`).
@@ -1500,6 +1931,54 @@ mod test_functions {
 
         Ok(())
     }
+
+    #[test]
+    /// `cargo test -- --show-output conferencia_de_carga`
+    fn conferencia_de_carga() -> XmlParserResult<()> {
+        // Sem peso bruto explícito: adota a primeira medição em KG ("PESO REAL")
+        let path = Path::new("35220998765432101234567894741048320396789012_CTe.xml");
+        let info_cte = CteProc::xml_parse(path)?.get_info();
+        println!("quantidades_carga: {:#?}", info_cte.quantidades_carga);
+        assert_eq!(info_cte.quantidades_carga.len(), 5);
+        assert_eq!(info_cte.quantidades_carga[3], "01 - KG, PESO REAL: 43.7");
+        assert_eq!(info_cte.peso_carga, Some(43.7));
+        assert_eq!(info_cte.valor_carga, Some(12345.67));
+
+        let path = Path::new("41220878899001122334455667788990011223344555_CTe.xml");
+        let mut info_cte = CteProc::xml_parse(path)?.get_info();
+        assert_eq!(info_cte.peso_carga, Some(1000.0));
+        assert_eq!(info_cte.valor_carga, Some(123456.78));
+
+        // Peso bruto em toneladas tem prioridade sobre as demais medições
+        let carga: InfCarga = quick_xml::de::from_str(
+            "<infCarga><vCarga>500.00</vCarga>\
+             <infQ><cUnid>01</cUnid><tpMed>PESO CUBADO</tpMed><qCarga>900</qCarga></infQ>\
+             <infQ><cUnid>02</cUnid><tpMed>PESO BRUTO</tpMed><qCarga>1.25</qCarga></infQ></infCarga>",
+        )?;
+        assert_eq!(carga.get_peso_carga(), Some(1250.0));
+
+        // Dentro da tolerância
+        info_cte.peso_bruto_nfes = Some(980.0);
+        info_cte.valor_nfes = Some(123456.78);
+        info_cte.conferir_carga(5.0);
+        assert_eq!(info_cte.divergencia_peso, Some(20.0));
+        assert_eq!(info_cte.divergencia_peso_pct, Some(2.04));
+        assert_eq!(info_cte.divergencia_valor, Some(0.0));
+        assert_eq!(info_cte.divergencia_carga, Some("Não".to_string()));
+
+        // Acima da tolerância
+        info_cte.conferir_carga(1.0);
+        assert_eq!(info_cte.divergencia_carga, Some("Sim".to_string()));
+
+        // Sem referência das NF-es não há conferência
+        info_cte.peso_bruto_nfes = None;
+        info_cte.valor_nfes = None;
+        info_cte.conferir_carga(1.0);
+        assert_eq!(info_cte.divergencia_peso, None);
+        assert_eq!(info_cte.divergencia_carga, None);
+
+        Ok(())
+    }
 }
 
 #[cfg(test)]
@@ -11,10 +11,20 @@
 use serde::{Deserialize, Serialize};
 
 use crate::{
-    OptExt,
+    DocumentoPapel, OptExt, descrever_codigo, get_naive_date_from_yyyy_mm_dd,
     xml_structs::{cobranca::Cobranca, entrega::Entrega},
 };
 
+/// Código da unidade de medida da carga (`cUnid`).
+const UNIDADE_DE_MEDIDA: [(&str, &str); 6] = [
+    ("00", "M3"),
+    ("01", "KG"),
+    ("02", "TON"),
+    ("03", "UNIDADE"),
+    ("04", "LITROS"),
+    ("05", "MMBTU"),
+];
+
 /// Trait utilitário para mesclar chaves de acesso de documentos anteriores.
 pub trait GetDocs {
     /// Obtém a chave de acesso do CT-e se presente.
@@ -207,6 +217,14 @@ impl InfCteNorm {
             None => Vec::new(),
         }
     }
+
+    /// Obtém as referências a documentos transportados sem chave de acesso.
+    pub fn get_documentos_papel(&self) -> Vec<DocumentoPapel> {
+        match self.inf_doc.as_ref() {
+            Some(info) => info.get_documentos_papel(),
+            None => Vec::new(),
+        }
+    }
 }
 
 /// Grupo de Documentos Anteriores de Transporte (`<docAnt>`).
@@ -397,6 +415,77 @@ pub struct InfQ {
     pub tp_med: Option<String>,
 }
 
+impl InfCarga {
+    /// Valor total declarado da carga (`vCarga`).
+    pub fn get_valor_carga(&self) -> Option<f64> {
+        self.v_carga.to_float64()
+    }
+
+    /// Retorna as medições (`<infQ>`), ou uma lista vazia se ausentes.
+    #[inline]
+    pub fn get_medicoes(&self) -> &[InfQ] {
+        self.inf_q.as_deref().unwrap_or(&[])
+    }
+
+    /// Descrição textual de cada medição, p. ex. `"01 - KG, PESO BRUTO: 1000"`.
+    pub fn get_quantidades(&self) -> Vec<String> {
+        self.get_medicoes()
+            .iter()
+            .filter_map(|inf_q| inf_q.descrever())
+            .collect()
+    }
+
+    /// Peso declarado da carga em kg.
+    ///
+    /// Entre as medições em KG ou TON, prioriza aquela cujo `tpMed` indica peso bruto;
+    /// na ausência, adota a primeira medição de peso informada.
+    pub fn get_peso_carga(&self) -> Option<f64> {
+        let medicoes = self.get_medicoes();
+        medicoes
+            .iter()
+            .filter(|inf_q| inf_q.is_peso_bruto())
+            .find_map(|inf_q| inf_q.get_peso_kg())
+            .or_else(|| medicoes.iter().find_map(|inf_q| inf_q.get_peso_kg()))
+    }
+}
+
+impl InfQ {
+    /// Quantidade medida (`qCarga`).
+    pub fn get_quantidade(&self) -> Option<f64> {
+        self.q_carga.to_float64()
+    }
+
+    /// Descreve a unidade de medida (`cUnid`), p. ex. `"01 - KG"`.
+    pub fn get_unidade(&self) -> Option<String> {
+        descrever_codigo(&self.c_unid, &UNIDADE_DE_MEDIDA)
+    }
+
+    /// Quantidade convertida para kg quando a unidade for KG ou TON.
+    pub fn get_peso_kg(&self) -> Option<f64> {
+        let quantidade = self.get_quantidade()?;
+        match self.c_unid.get_not_empty()?.as_str() {
+            "01" => Some(quantidade),
+            "02" => Some(quantidade * 1000.0),
+            _ => None,
+        }
+    }
+
+    /// Verifica se o tipo de medida (`tpMed`) se refere ao peso bruto.
+    pub fn is_peso_bruto(&self) -> bool {
+        self.tp_med
+            .as_deref()
+            .is_some_and(|tp_med| tp_med.to_uppercase().contains("BRUTO"))
+    }
+
+    /// Formata a medição como `"unidade, tipo de medida: quantidade"`.
+    pub fn descrever(&self) -> Option<String> {
+        let quantidade = self.get_quantidade()?;
+        let unidade = self.get_unidade().unwrap_or_default();
+        let tp_med = self.tp_med.get_not_empty().unwrap_or_default();
+        Some(format!("{unidade}, {tp_med}: {quantidade}"))
+    }
+}
+
 /// Dados específicos correspondentes a CT-e Substituído (`<infCteSub>`).
 #[derive(Debug, Serialize, Deserialize, Clone)]
 pub struct InfCteSub {
@@ -512,6 +601,17 @@ impl InfDoc {
             None => Vec::new(),
         }
     }
+
+    /// Consolida as Notas Fiscais em papel (`infNF`) e os outros documentos (`infOutros`).
+    pub fn get_documentos_papel(&self) -> Vec<DocumentoPapel> {
+        let notas = self.inf_nf.iter().flatten().map(InfNf::get_documento);
+        let outros = self
+            .inf_outros
+            .iter()
+            .flatten()
+            .map(InfOutros::get_documento);
+        notas.chain(outros).collect()
+    }
 }
 
 /// Detalhamento de Nota Fiscal física transportada (`<infNF>`).
@@ -594,6 +694,21 @@ pub struct InfNf {
     pub v_st: Option<String>,
 }
 
+impl InfNf {
+    /// Referência à Nota Fiscal em papel para correlação por série e número.
+    pub fn get_documento(&self) -> DocumentoPapel {
+        DocumentoPapel {
+            grupo: "infNF",
+            modelo: self.modelo.clone(),
+            serie: self.serie.clone(),
+            numero: self.n_doc.clone(),
+            data_emissao: get_naive_date_from_yyyy_mm_dd(&self.d_emi),
+            valor: self.v_nf.to_float64(),
+            ..Default::default()
+        }
+    }
+}
+
 /// Unidade de carga associada a Notas Fiscais físicas (`<infUnidCarga>`).
 #[derive(Debug, Serialize, Deserialize, Clone)]
 pub struct InfUnidCarga {
@@ -886,6 +1001,21 @@ pub struct InfOutros {
     pub v_doc_fisc: Option<String>,
 }
 
+impl InfOutros {
+    /// Referência ao documento para correlação por número (não há série em `infOutros`).
+    pub fn get_documento(&self) -> DocumentoPapel {
+        DocumentoPapel {
+            grupo: "infOutros",
+            tipo: self.tp_doc.clone(),
+            descricao: self.desc_outros.clone(),
+            numero: self.n_doc.clone(),
+            data_emissao: get_naive_date_from_yyyy_mm_dd(&self.d_emi),
+            valor: self.v_doc_fisc.to_float64(),
+            ..Default::default()
+        }
+    }
+}
+
 /// Unidades de carga acopladas a outros documentos transportados (`<infUnidCarga>`).
 #[derive(Debug, Serialize, Deserialize, Clone)]
 pub struct OutrosInfUnidCarga {
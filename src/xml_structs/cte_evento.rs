@@ -2,16 +2,20 @@
 //!
 //! Este módulo gerencia a desserialização e estruturação dos dados de eventos do CT-e,
 //! como o cancelamento, carta de correção (CC-e), registro de passagem, subcontratação,
-//! substituição e complementação de valores.
+//! substituição, complementação de valores e comprovante ou insucesso de entrega.
 
 use chrono::NaiveDate;
 use serde::{Deserialize, Serialize};
 use std::collections::BTreeSet;
 
 use crate::{
-    Arguments, GetKey, InfoExtension, Information, OptExt, StructExtension,
-    get_naive_date_from_yyyy_mm_dd,
-    xml_structs::{agente::Agente, assinaturas::Signature, ret_evento::RetEvento},
+    Arguments, EventoEntrega, GetKey, InfoExtension, Information, OptExt, RelacaoCte,
+    StructExtension, TipoRelacaoCte, descrever_motivo_insucesso, get_naive_date_from_yyyy_mm_dd,
+    xml_structs::{
+        agente::Agente,
+        assinaturas::Signature,
+        ret_evento::{RetEvento, is_situacao_homologada},
+    },
 };
 
 /// Representação intermediária e consolidada de um Evento de CT-e.
@@ -31,10 +35,30 @@ pub struct InfoCteEvento {
     pub cte_redespacho_interno: Option<String>,
     /// Chave de acesso do CT-e de subcontratação se aplicável.
     pub cte_subcontratacao: Option<String>,
-    /// Chave de acesso do CT-e substituído se aplicável.
+    /// Chave de acesso do CT-e de substituição se aplicável (evento registrado no CT-e substituído).
     pub cte_substituido: Option<String>,
     /// Chave de acesso do CT-e vinculado multimodal se aplicável.
     pub cte_vinculado: Option<String>,
+    /// Caminho do arquivo XML do evento.
+    pub arquivo: Option<String>,
+    /// Código do tipo de evento (`tpEvento`), p. ex. "610110" para a prestação em desacordo.
+    pub tp_evento: Option<String>,
+    /// Descrição do evento (`descEvento` do grupo específico).
+    pub desc_evento: Option<String>,
+    /// Número sequencial do evento para a mesma chave (`nSeqEvento`).
+    pub n_seq_evento: Option<u32>,
+    /// Data e hora do evento (`dhEvento`), no formato AAAA-MM-DDTHH:MM:SSTZD.
+    pub dh_evento: Option<String>,
+    /// Protocolo de registro do evento (`retEventoCTe/infEvento/nProt`).
+    pub protocolo: Option<String>,
+    /// Situação do registro: `cStat - xMotivo`.
+    pub situacao: Option<String>,
+    /// Texto do evento: correções, justificativa ou documento vinculado.
+    pub detalhe: Option<String>,
+    /// Correções estruturadas da Carta de Correção (`evCCeCTe/infCorrecao`).
+    pub correcoes: Vec<InfCorrecao>,
+    /// Comprovante ou insucesso de entrega e os respectivos cancelamentos.
+    pub entrega: Option<EventoEntrega>,
 }
 
 impl InfoCteEvento {
@@ -56,6 +80,63 @@ impl InfoCteEvento {
         .into_iter()
         .collect()
     }
+
+    /// Relações tipadas declaradas no evento: o CT-e do evento é o original
+    /// e a chave informada é o CT-e derivado.
+    pub fn get_relacoes(&self) -> Vec<RelacaoCte> {
+        let Some(cte) = &self.cte else {
+            return Vec::new();
+        };
+
+        [
+            (
+                &self.cte_complementar,
+                TipoRelacaoCte::Complementar,
+                "evCTeComplementar",
+            ),
+            (
+                &self.cte_redespacho,
+                TipoRelacaoCte::Redespacho,
+                "evCTeRedespacho",
+            ),
+            (
+                &self.cte_redespacho_interno,
+                TipoRelacaoCte::RedespachoIntermediario,
+                "evCTeRedespachoInter",
+            ),
+            (
+                &self.cte_subcontratacao,
+                TipoRelacaoCte::Subcontratacao,
+                "evCTeSubcontratacao",
+            ),
+            (
+                &self.cte_substituido,
+                TipoRelacaoCte::Substituicao,
+                "evCTeSubstituido",
+            ),
+            (
+                &self.cte_vinculado,
+                TipoRelacaoCte::Multimodal,
+                "evCTeMultimodal",
+            ),
+        ]
+        .into_iter()
+        .filter_map(|(derivado, tipo, evento)| {
+            derivado
+                .as_ref()
+                .filter(|derivado| *derivado != cte)
+                .map(|derivado| {
+                    RelacaoCte::evento(cte, derivado, tipo, evento)
+                        .com_arquivo(self.arquivo.clone())
+                })
+        })
+        .collect()
+    }
+
+    /// Verifica se o evento foi registrado pela SEFAZ (`cStat` 135 ou 136).
+    pub fn is_homologado(&self) -> bool {
+        is_situacao_homologada(self.situacao.as_deref())
+    }
 }
 
 /// <https://doc.rust-lang.org/book/ch10-02-traits.html#default-implementations>
@@ -110,7 +191,9 @@ impl StructExtension for ProcEventoCte {
             println!("evento cte xml_path: {xml_path:?}");
             println!("proc_evento_cte: {self:#?}\n");
         }
-        Information::EventoCte(Box::new(self.get_info()))
+        let mut info = self.get_info();
+        info.arquivo = Some(xml_path.display().to_string());
+        Information::EventoCte(Box::new(info))
     }
 }
 
@@ -196,6 +279,8 @@ impl ProcEventoCte {
 
     /// Consolida todos os metadados do processamento do evento de CT-e na estrutura `InfoCteEvento`.
     pub fn get_info(&self) -> InfoCteEvento {
+        let inf_evento = &self.evento_cte.inf_evento;
+        let retorno = self.ret_evento_cte.inf_evento.as_ref();
         InfoCteEvento {
             cte: self.get_cte(),
             dh_emi: self.get_data_emissao(),
@@ -206,6 +291,24 @@ impl ProcEventoCte {
             cte_subcontratacao: self.get_cte_subcontratacao(),
             cte_substituido: self.get_cte_substituido(),
             cte_vinculado: self.get_cte_vinculado(),
+            arquivo: None,
+            tp_evento: inf_evento.tp_evento.get_not_empty(),
+            desc_evento: inf_evento.det_evento.get_desc_evento(),
+            n_seq_evento: inf_evento
+                .n_seq_evento
+                .as_deref()
+                .and_then(|n| n.trim().parse().ok()),
+            dh_evento: inf_evento.dh_evento.clone(),
+            protocolo: retorno.and_then(|r| r.n_prot.clone()),
+            situacao: retorno.and_then(|r| r.get_situacao()),
+            detalhe: inf_evento.det_evento.get_detalhe(),
+            correcoes: inf_evento
+                .det_evento
+                .ev_cce_cte
+                .as_ref()
+                .map(|cce| cce.inf_correcao.clone())
+                .unwrap_or_default(),
+            entrega: inf_evento.det_evento.get_entrega(),
         }
     }
 }
@@ -356,6 +459,152 @@ pub struct DetEvento {
     /// Evento de vinculação ao CT-e Multimodal se aplicável.
     #[serde(rename = "evCTeMultimodal", default)]
     pub ev_cte_multimodal: Option<EvCteMultimodal>,
+
+    /// Evento do tomador: prestação do serviço em desacordo.
+    #[serde(rename = "evPrestDesacordo", default)]
+    pub ev_prest_desacordo: Option<EvPrestDesacordo>,
+
+    /// Comprovante de Entrega do CT-e.
+    #[serde(rename = "evCECTe", default)]
+    pub ev_ce_cte: Option<EvCeCte>,
+
+    /// Cancelamento do Comprovante de Entrega do CT-e.
+    #[serde(rename = "evCancCECTe", default)]
+    pub ev_canc_ce_cte: Option<EvCancCeCte>,
+
+    /// Insucesso na Entrega do CT-e.
+    #[serde(rename = "evIECTe", default)]
+    pub ev_ie_cte: Option<EvIeCte>,
+
+    /// Cancelamento do Insucesso na Entrega do CT-e.
+    #[serde(rename = "evCancIECTe", default)]
+    pub ev_canc_ie_cte: Option<EvCancIeCte>,
+}
+
+impl DetEvento {
+    /// Descrição do evento declarada no grupo específico (`descEvento`).
+    pub fn get_desc_evento(&self) -> Option<String> {
+        [
+            self.ev_cce_cte.as_ref().map(|e| &e.desc_evento),
+            self.ev_cte_autorizado_mdfe.as_ref().map(|e| &e.desc_evento),
+            self.ev_canc_cte.as_ref().map(|e| &e.desc_evento),
+            self.ev_cte_complementar.as_ref().map(|e| &e.desc_evento),
+            self.ev_cte_redespacho.as_ref().map(|e| &e.desc_evento),
+            self.ev_cte_redespacho_inter
+                .as_ref()
+                .map(|e| &e.desc_evento),
+            self.ev_cte_reg_passagem_auto
+                .as_ref()
+                .map(|e| &e.desc_evento),
+            self.ev_cte_subcontratacao.as_ref().map(|e| &e.desc_evento),
+            self.ev_cte_substituido.as_ref().map(|e| &e.desc_evento),
+            self.ev_cte_multimodal.as_ref().map(|e| &e.desc_evento),
+            self.ev_prest_desacordo.as_ref().map(|e| &e.desc_evento),
+            self.ev_ce_cte.as_ref().map(|e| &e.desc_evento),
+            self.ev_canc_ce_cte.as_ref().map(|e| &e.desc_evento),
+            self.ev_ie_cte.as_ref().map(|e| &e.desc_evento),
+            self.ev_canc_ie_cte.as_ref().map(|e| &e.desc_evento),
+        ]
+        .into_iter()
+        .flatten()
+        .find_map(|desc_evento| desc_evento.get_not_empty())
+    }
+
+    /// Dados do comprovante ou do insucesso de entrega, ou o protocolo cancelado.
+    pub fn get_entrega(&self) -> Option<EventoEntrega> {
+        if let Some(ce) = &self.ev_ce_cte {
+            return Some(EventoEntrega {
+                dh_entrega: ce.dh_entrega.get_not_empty(),
+                documento: ce.n_doc.get_not_empty(),
+                nome: ce.x_nome.get_not_empty(),
+                latitude: ce.latitude.get_not_empty(),
+                longitude: ce.longitude.get_not_empty(),
+                ..Default::default()
+            });
+        }
+
+        if let Some(ie) = &self.ev_ie_cte {
+            return Some(EventoEntrega {
+                dh_entrega: ie.dh_tentativa_entrega.get_not_empty(),
+                latitude: ie.latitude.get_not_empty(),
+                longitude: ie.longitude.get_not_empty(),
+                n_tentativa: ie.n_tentativa.get_not_empty(),
+                motivo: descrever_motivo_insucesso(&ie.tp_motivo, &ie.x_just_motivo),
+                ..Default::default()
+            });
+        }
+
+        [
+            self.ev_canc_ce_cte.as_ref().map(|e| &e.n_prot_ce),
+            self.ev_canc_ie_cte.as_ref().map(|e| &e.n_prot_ie),
+        ]
+        .into_iter()
+        .flatten()
+        .find_map(|protocolo| protocolo.get_not_empty())
+        .map(|protocolo| EventoEntrega {
+            protocolo_cancelado: Some(protocolo),
+            ..Default::default()
+        })
+    }
+
+    /// Texto do evento para a tabela "Eventos".
+    ///
+    /// CC-e: campos corrigidos; cancelamento: `xJust`; prestação em desacordo: `xObs`;
+    /// insucesso na entrega: `xJustMotivo`; demais eventos: chave do CT-e ou MDF-e vinculado.
+    pub fn get_detalhe(&self) -> Option<String> {
+        let correcoes = self
+            .ev_cce_cte
+            .iter()
+            .flat_map(|cce| &cce.inf_correcao)
+            .map(|correcao| correcao.descrever());
+
+        let justificativas = [
+            self.ev_canc_cte.as_ref().map(|e| &e.x_just),
+            self.ev_prest_desacordo.as_ref().map(|e| &e.x_obs),
+            self.ev_ie_cte.as_ref().map(|e| &e.x_just_motivo),
+        ]
+        .into_iter()
+        .flatten()
+        .filter_map(|texto| texto.get_not_empty());
+
+        let ctes = [
+            self.ev_cte_complementar.as_ref().map(|e| &e.ch_cte_compl),
+            self.ev_cte_redespacho.as_ref().map(|e| &e.ch_cte_redesp),
+            self.ev_cte_redespacho_inter
+                .as_ref()
+                .map(|e| &e.ch_cte_redesp_inter),
+            self.ev_cte_subcontratacao
+                .as_ref()
+                .map(|e| &e.ch_cte_subcon),
+            self.ev_cte_substituido
+                .as_ref()
+                .map(|e| &e.ch_cte_substituicao),
+            self.ev_cte_multimodal.as_ref().map(|e| &e.ch_cte_vinculado),
+        ]
+        .into_iter()
+        .flatten()
+        .filter_map(|chave| chave.get_key())
+        .map(|chave| format!("CTe {chave}"));
+
+        let mdfes = [
+            self.ev_cte_autorizado_mdfe
+                .as_ref()
+                .map(|e| &e.mdfe.ch_mdfe),
+            self.ev_cte_reg_passagem_auto.as_ref().map(|e| &e.ch_mdfe),
+        ]
+        .into_iter()
+        .flatten()
+        .filter_map(|chave| chave.get_key())
+        .map(|chave| format!("MDFe {chave}"));
+
+        let partes: Vec<String> = correcoes
+            .chain(justificativas)
+            .chain(ctes)
+            .chain(mdfes)
+            .collect();
+
+        (!partes.is_empty()).then(|| partes.join("; "))
+    }
 }
 
 /// Dados relativos à Carta de Correção Eletrônica do CT-e (`<evCCeCTe>`).
@@ -375,7 +624,7 @@ pub struct EvCceCte {
 }
 
 /// Dados estruturados para detalhamento de uma retificação de campo (`<infCorrecao>`).
-#[derive(Debug, Serialize, Deserialize)]
+#[derive(Debug, Default, Serialize, Deserialize, Clone)]
 pub struct InfCorrecao {
     /// Nome do nó pai do grupo XML alterado.
     #[serde(rename = "grupoAlterado", default)]
@@ -388,6 +637,23 @@ pub struct InfCorrecao {
     /// Novo valor correspondente atribuído ao campo retificado.
     #[serde(rename = "valorAlterado", default)]
     pub valor_alterado: Option<String>,
+
+    /// Índice da ocorrência do grupo alterado, quando o grupo se repete (p. ex. `infQ`).
+    #[serde(rename = "nroItemAlterado", default)]
+    pub nro_item_alterado: Option<String>,
+}
+
+impl InfCorrecao {
+    /// Correção no formato `grupo/campo: valor`.
+    pub fn descrever(&self) -> String {
+        let campo = [&self.grupo_alterado, &self.campo_alterado]
+            .into_iter()
+            .filter_map(|nome| nome.get_not_empty())
+            .collect::<Vec<_>>()
+            .join("/");
+        let valor = self.valor_alterado.get_not_empty().unwrap_or_default();
+        format!("{campo}: {valor}")
+    }
 }
 
 /// Evento correspondente à autorização emitida pelo webservice MDF-e (`<evCTeAutorizadoMDFe>`).
@@ -671,3 +937,152 @@ impl EvCteMultimodal {
         self.ch_cte_vinculado.get_key()
     }
 }
+
+/// Evento do tomador que declara a prestação do serviço em desacordo (`<evPrestDesacordo>`).
+#[derive(Debug, Serialize, Deserialize)]
+pub struct EvPrestDesacordo {
+    /// Descrição literal correspondente ao evento.
+    #[serde(rename = "descEvento", default)]
+    pub desc_evento: Option<String>,
+
+    /// Indicador de operação em desacordo (sempre "1").
+    #[serde(rename = "indDesacordoOper", default)]
+    pub ind_desacordo_oper: Option<String>,
+
+    /// Observações do tomador sobre o desacordo.
+    #[serde(rename = "xObs", default)]
+    pub x_obs: Option<String>,
+}
+
+/// Comprovante de Entrega do CT-e (`<evCECTe>`).
+#[derive(Debug, Serialize, Deserialize)]
+pub struct EvCeCte {
+    /// Descrição literal correspondente ao evento.
+    #[serde(rename = "descEvento", default)]
+    pub desc_evento: Option<String>,
+
+    /// Número do protocolo de autorização do CT-e.
+    #[serde(rename = "nProt", default)]
+    pub n_prot: Option<String>,
+
+    /// Data e hora da entrega da carga.
+    #[serde(rename = "dhEntrega", default)]
+    pub dh_entrega: Option<String>,
+
+    /// Documento de identificação de quem recebeu a carga.
+    #[serde(rename = "nDoc", default)]
+    pub n_doc: Option<String>,
+
+    /// Nome de quem recebeu a carga.
+    #[serde(rename = "xNome", default)]
+    pub x_nome: Option<String>,
+
+    /// Latitude do ponto de entrega.
+    #[serde(rename = "latitude", default)]
+    pub latitude: Option<String>,
+
+    /// Longitude do ponto de entrega.
+    #[serde(rename = "longitude", default)]
+    pub longitude: Option<String>,
+
+    /// Hash (SHA-1, Base64) da imagem do comprovante de entrega.
+    #[serde(rename = "hashEntrega", default)]
+    pub hash_entrega: Option<String>,
+
+    /// Data e hora de geração do hash do comprovante.
+    #[serde(rename = "dhHashEntrega", default)]
+    pub dh_hash_entrega: Option<String>,
+
+    /// NF-es entregues, quando a entrega é parcial.
+    #[serde(rename = "infEntrega", default)]
+    pub inf_entrega: Vec<InfEntrega>,
+}
+
+/// Insucesso na Entrega do CT-e (`<evIECTe>`).
+#[derive(Debug, Serialize, Deserialize)]
+pub struct EvIeCte {
+    /// Descrição literal correspondente ao evento.
+    #[serde(rename = "descEvento", default)]
+    pub desc_evento: Option<String>,
+
+    /// Número do protocolo de autorização do CT-e.
+    #[serde(rename = "nProt", default)]
+    pub n_prot: Option<String>,
+
+    /// Data e hora da tentativa de entrega.
+    #[serde(rename = "dhTentativaEntrega", default)]
+    pub dh_tentativa_entrega: Option<String>,
+
+    /// Número da tentativa de entrega.
+    #[serde(rename = "nTentativa", default)]
+    pub n_tentativa: Option<String>,
+
+    /// Motivo do insucesso: 1 - Recebedor não Encontrado; 2 - Recusa do Recebedor;
+    /// 3 - Endereço Inexistente; 4 - Outros.
+    #[serde(rename = "tpMotivo", default)]
+    pub tp_motivo: Option<String>,
+
+    /// Justificativa do insucesso, obrigatória para o motivo 4 - Outros.
+    #[serde(rename = "xJustMotivo", default)]
+    pub x_just_motivo: Option<String>,
+
+    /// Latitude do ponto da tentativa de entrega.
+    #[serde(rename = "latitude", default)]
+    pub latitude: Option<String>,
+
+    /// Longitude do ponto da tentativa de entrega.
+    #[serde(rename = "longitude", default)]
+    pub longitude: Option<String>,
+
+    /// Hash (SHA-1, Base64) da imagem da tentativa de entrega.
+    #[serde(rename = "hashTentativaEntrega", default)]
+    pub hash_tentativa_entrega: Option<String>,
+
+    /// Data e hora de geração do hash da tentativa.
+    #[serde(rename = "dhHashTentativaEntrega", default)]
+    pub dh_hash_tentativa_entrega: Option<String>,
+
+    /// NF-es da tentativa, quando a entrega é parcial.
+    #[serde(rename = "infEntrega", default)]
+    pub inf_entrega: Vec<InfEntrega>,
+}
+
+/// NF-e abrangida pelo comprovante ou insucesso de entrega (`<infEntrega>`).
+#[derive(Debug, Serialize, Deserialize)]
+pub struct InfEntrega {
+    /// Chave de acesso da NF-e.
+    #[serde(rename = "chNFe", default)]
+    pub ch_nfe: Option<String>,
+}
+
+/// Cancelamento do Comprovante de Entrega do CT-e (`<evCancCECTe>`).
+#[derive(Debug, Serialize, Deserialize)]
+pub struct EvCancCeCte {
+    /// Descrição literal correspondente ao evento.
+    #[serde(rename = "descEvento", default)]
+    pub desc_evento: Option<String>,
+
+    /// Número do protocolo de autorização do CT-e.
+    #[serde(rename = "nProt", default)]
+    pub n_prot: Option<String>,
+
+    /// Protocolo do evento de Comprovante de Entrega cancelado.
+    #[serde(rename = "nProtCE", default)]
+    pub n_prot_ce: Option<String>,
+}
+
+/// Cancelamento do Insucesso na Entrega do CT-e (`<evCancIECTe>`).
+#[derive(Debug, Serialize, Deserialize)]
+pub struct EvCancIeCte {
+    /// Descrição literal correspondente ao evento.
+    #[serde(rename = "descEvento", default)]
+    pub desc_evento: Option<String>,
+
+    /// Número do protocolo de autorização do CT-e.
+    #[serde(rename = "nProt", default)]
+    pub n_prot: Option<String>,
+
+    /// Protocolo do evento de Insucesso na Entrega cancelado.
+    #[serde(rename = "nProtIE", default)]
+    pub n_prot_ie: Option<String>,
+}
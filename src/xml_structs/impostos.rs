@@ -23,8 +23,9 @@ Isso torna os tipos explícitos para o compilador e evita que a análise de tipo
 coerções implícitas na chamada de métodos do trait FromStr.
 */
 
-use claudiofsr_lib::OptionExtension;
+use claudiofsr_lib::{OptionExtension, RoundFloat};
 use serde::{Deserialize, Serialize};
+use std::fmt;
 
 /// Bloco consolidador de tributos e impostos incidentes sobre o item (`<imposto>`).
 #[derive(Debug, Serialize, Deserialize, Clone)]
@@ -125,6 +126,102 @@ impl Imposto {
         self.cofins.as_ref().and_then(|c| c.get_cofins_valor())
     }
 
+    // Detalhamento do PIS por regime de base de cálculo (ad valorem / ad rem)
+
+    pub fn get_tipo_base_pis(&self) -> Option<TipoBaseCalculo> {
+        self.pis.as_ref().and_then(|p| p.get_pis_tipo_base())
+    }
+
+    pub fn get_v_bc_pis(&self) -> Option<f64> {
+        self.pis
+            .as_ref()
+            .and_then(|p| p.get_pis_base_calculo_valor())
+    }
+
+    pub fn get_q_bc_pis(&self) -> Option<f64> {
+        self.pis
+            .as_ref()
+            .and_then(|p| p.get_pis_base_calculo_quantidade())
+    }
+
+    pub fn get_aliq_reais_pis(&self) -> Option<f64> {
+        self.pis
+            .as_ref()
+            .and_then(|p| p.get_pis_aliquota_em_reais())
+    }
+
+    // Detalhamento da COFINS por regime de base de cálculo (ad valorem / ad rem)
+
+    pub fn get_tipo_base_cofins(&self) -> Option<TipoBaseCalculo> {
+        self.cofins.as_ref().and_then(|c| c.get_cofins_tipo_base())
+    }
+
+    pub fn get_v_bc_cofins(&self) -> Option<f64> {
+        self.cofins
+            .as_ref()
+            .and_then(|c| c.get_cofins_base_calculo_valor())
+    }
+
+    pub fn get_q_bc_cofins(&self) -> Option<f64> {
+        self.cofins
+            .as_ref()
+            .and_then(|c| c.get_cofins_base_calculo_quantidade())
+    }
+
+    pub fn get_aliq_reais_cofins(&self) -> Option<f64> {
+        self.cofins
+            .as_ref()
+            .and_then(|c| c.get_cofins_aliquota_em_reais())
+    }
+
+    // PIS e COFINS devidos por Substituição Tributária
+
+    pub fn get_v_bc_pisst(&self) -> Option<f64> {
+        self.pisst.as_ref().and_then(|p| p.get_valor_base_calc())
+    }
+
+    pub fn get_aliq_pisst(&self) -> Option<f64> {
+        self.pisst.as_ref().and_then(|p| p.get_aliquota())
+    }
+
+    pub fn get_q_bc_pisst(&self) -> Option<f64> {
+        self.pisst
+            .as_ref()
+            .and_then(|p| p.get_quantidade_base_calc())
+    }
+
+    pub fn get_aliq_reais_pisst(&self) -> Option<f64> {
+        self.pisst.as_ref().and_then(|p| p.get_aliquota_em_reais())
+    }
+
+    pub fn get_v_pisst(&self) -> Option<f64> {
+        self.pisst.as_ref().and_then(|p| p.get_valor())
+    }
+
+    pub fn get_v_bc_cofinsst(&self) -> Option<f64> {
+        self.cofinsst.as_ref().and_then(|c| c.get_valor_base_calc())
+    }
+
+    pub fn get_aliq_cofinsst(&self) -> Option<f64> {
+        self.cofinsst.as_ref().and_then(|c| c.get_aliquota())
+    }
+
+    pub fn get_q_bc_cofinsst(&self) -> Option<f64> {
+        self.cofinsst
+            .as_ref()
+            .and_then(|c| c.get_quantidade_base_calc())
+    }
+
+    pub fn get_aliq_reais_cofinsst(&self) -> Option<f64> {
+        self.cofinsst
+            .as_ref()
+            .and_then(|c| c.get_aliquota_em_reais())
+    }
+
+    pub fn get_v_cofinsst(&self) -> Option<f64> {
+        self.cofinsst.as_ref().and_then(|c| c.get_valor())
+    }
+
     /// Obtém o valor monetário correspondente à Base de Cálculo do ICMS.
     pub fn get_v_bc_icms(&self) -> Option<f64> {
         self.icms
@@ -1420,6 +1517,89 @@ impl Issqn {
     }
 }
 
+/// Regime da base de cálculo das contribuições PIS/COFINS.
+///
+/// * `Valor`: tributação ad valorem (`vBC` x alíquota percentual).
+/// * `Quantidade`: tributação ad rem (`qBCProd` x `vAliqProd`, alíquota em reais por unidade).
+/// * `NaoTributado`: grupos `PISNT`/`COFINSNT`, sem base de cálculo.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum TipoBaseCalculo {
+    Valor,
+    Quantidade,
+    NaoTributado,
+}
+
+impl TipoBaseCalculo {
+    /// Infere o regime a partir dos campos do grupo "Outras Operações" (`PISOutr`/`COFINSOutr`),
+    /// em que o XSD admite tanto o par `vBC`/alíquota percentual quanto `qBCProd`/`vAliqProd`.
+    fn from_campos(ad_valorem: [Option<&str>; 2], ad_rem: [Option<&str>; 2]) -> Option<Self> {
+        let informado = |campos: [Option<&str>; 2]| {
+            campos
+                .iter()
+                .any(|campo| campo.is_some_and(|s| !s.trim().is_empty()))
+        };
+
+        if informado(ad_rem) {
+            Some(Self::Quantidade)
+        } else if informado(ad_valorem) {
+            Some(Self::Valor)
+        } else {
+            None
+        }
+    }
+}
+
+impl fmt::Display for TipoBaseCalculo {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        let descricao = match self {
+            Self::Valor => "Valor (ad valorem)",
+            Self::Quantidade => "Quantidade (ad rem)",
+            Self::NaoTributado => "Não Tributado",
+        };
+        write!(f, "{descricao}")
+    }
+}
+
+/// Apura a base de cálculo monetária de PIS/COFINS (`vBC`).
+///
+/// Na tributação ad valorem, a base não informada no XML é reconstituída a partir do
+/// valor da contribuição e da alíquota percentual.
+fn calcular_base_valor(
+    tipo: Option<TipoBaseCalculo>,
+    valor_base: Option<f64>,
+    aliquota: Option<f64>,
+    valor: Option<f64>,
+) -> Option<f64> {
+    match tipo {
+        Some(TipoBaseCalculo::Valor) => valor_base.or_else(|| match (valor, aliquota) {
+            (Some(v), Some(aliq)) if aliq > 0.0 => Some((v * 100.0 / aliq).round_float(2)),
+            _ => None,
+        }),
+        _ => valor_base,
+    }
+}
+
+/// Apura a quantidade que serve de base de cálculo de PIS/COFINS (`qBCProd`).
+///
+/// Na tributação ad rem, a quantidade não informada no XML é reconstituída a partir do
+/// valor da contribuição e da alíquota em reais por unidade.
+fn calcular_base_quantidade(
+    tipo: Option<TipoBaseCalculo>,
+    quantidade_base: Option<f64>,
+    aliquota_em_reais: Option<f64>,
+    valor: Option<f64>,
+) -> Option<f64> {
+    match tipo {
+        Some(TipoBaseCalculo::Quantidade) => {
+            quantidade_base.or_else(|| match (valor, aliquota_em_reais) {
+                (Some(v), Some(aliq)) if aliq > 0.0 => Some((v / aliq).round_float(4)),
+                _ => None,
+            })
+        }
+        _ => quantidade_base,
+    }
+}
+
 /// Bloco correspondente ao imposto PIS (`<PIS>`).
 #[derive(Debug, Serialize, Deserialize, Clone)]
 pub struct Pis {
@@ -1464,6 +1644,14 @@ impl Pis {
             .or_else(|| self.pisoutr.as_ref().and_then(|p| p.p_pis.parse_opt()))
     }
 
+    /// Alíquota percentual do PIS (tributação ad valorem), sem a alíquota em reais da pauta.
+    fn get_pis_aliquota_percentual(&self) -> Option<f64> {
+        self.pisaliq
+            .as_ref()
+            .and_then(|p| p.p_pis.parse_opt())
+            .or_else(|| self.pisoutr.as_ref().and_then(|p| p.p_pis.parse_opt()))
+    }
+
     /// Obtém de forma declarativa o valor monetário calculado do PIS.
     pub fn get_pis_valor(&self) -> Option<f64> {
         self.pisaliq
@@ -1472,6 +1660,72 @@ impl Pis {
             .or_else(|| self.pisqtde.as_ref().and_then(|p| p.v_pis.parse_opt()))
             .or_else(|| self.pisoutr.as_ref().and_then(|p| p.v_pis.parse_opt()))
     }
+
+    /// Identifica o regime da base de cálculo do PIS conforme o grupo informado no XML.
+    pub fn get_pis_tipo_base(&self) -> Option<TipoBaseCalculo> {
+        if self.pisaliq.is_some() {
+            Some(TipoBaseCalculo::Valor)
+        } else if self.pisqtde.is_some() {
+            Some(TipoBaseCalculo::Quantidade)
+        } else if self.pisnt.is_some() {
+            Some(TipoBaseCalculo::NaoTributado)
+        } else {
+            self.pisoutr.as_ref().and_then(|p| {
+                TipoBaseCalculo::from_campos(
+                    [p.v_bc.as_deref(), p.p_pis.as_deref()],
+                    [p.q_bcprod.as_deref(), p.v_aliq_prod.as_deref()],
+                )
+            })
+        }
+    }
+
+    /// Obtém o valor monetário da base de cálculo do PIS (`vBC`).
+    pub fn get_pis_valor_base_calc(&self) -> Option<f64> {
+        self.pisaliq
+            .as_ref()
+            .and_then(|p| p.v_bc.parse_opt())
+            .or_else(|| self.pisoutr.as_ref().and_then(|p| p.v_bc.parse_opt()))
+    }
+
+    /// Obtém a quantidade vendida que serve de base de cálculo do PIS (`qBCProd`).
+    pub fn get_pis_quantidade_base_calc(&self) -> Option<f64> {
+        self.pisqtde
+            .as_ref()
+            .and_then(|p| p.q_bcprod.parse_opt())
+            .or_else(|| self.pisoutr.as_ref().and_then(|p| p.q_bcprod.parse_opt()))
+    }
+
+    /// Obtém a alíquota do PIS em reais por unidade de produto (`vAliqProd`).
+    pub fn get_pis_aliquota_em_reais(&self) -> Option<f64> {
+        self.pisqtde
+            .as_ref()
+            .and_then(|p| p.v_aliq_prod.parse_opt())
+            .or_else(|| {
+                self.pisoutr
+                    .as_ref()
+                    .and_then(|p| p.v_aliq_prod.parse_opt())
+            })
+    }
+
+    /// Apura a base de cálculo monetária do PIS, reconstituída na tributação ad valorem.
+    pub fn get_pis_base_calculo_valor(&self) -> Option<f64> {
+        calcular_base_valor(
+            self.get_pis_tipo_base(),
+            self.get_pis_valor_base_calc(),
+            self.get_pis_aliquota_percentual(),
+            self.get_pis_valor(),
+        )
+    }
+
+    /// Apura a quantidade base de cálculo do PIS, reconstituída na tributação ad rem.
+    pub fn get_pis_base_calculo_quantidade(&self) -> Option<f64> {
+        calcular_base_quantidade(
+            self.get_pis_tipo_base(),
+            self.get_pis_quantidade_base_calc(),
+            self.get_pis_aliquota_em_reais(),
+            self.get_pis_valor(),
+        )
+    }
 }
 
 /// PIS tributado por alíquota percentual (`<PISAliq>`).
@@ -1574,6 +1828,33 @@ pub struct Pisst {
     pub text: Option<String>,
 }
 
+impl Pisst {
+    /// Obtém a base de cálculo monetária do PIS ST.
+    pub fn get_valor_base_calc(&self) -> Option<f64> {
+        self.v_bc.parse_opt()
+    }
+
+    /// Obtém a alíquota percentual do PIS ST.
+    pub fn get_aliquota(&self) -> Option<f64> {
+        self.p_pis.parse_opt()
+    }
+
+    /// Obtém a quantidade que serve de base de cálculo do PIS ST.
+    pub fn get_quantidade_base_calc(&self) -> Option<f64> {
+        self.q_bcprod.parse_opt()
+    }
+
+    /// Obtém a alíquota do PIS ST em reais por unidade de produto.
+    pub fn get_aliquota_em_reais(&self) -> Option<f64> {
+        self.v_aliq_prod.parse_opt()
+    }
+
+    /// Obtém o valor calculado do PIS ST.
+    pub fn get_valor(&self) -> Option<f64> {
+        self.v_pis.parse_opt()
+    }
+}
+
 /// Bloco correspondente ao imposto COFINS (`<COFINS>`).
 #[derive(Debug, Serialize, Deserialize, Clone)]
 pub struct Cofins {
@@ -1622,6 +1903,18 @@ impl Cofins {
             })
     }
 
+    /// Alíquota percentual da COFINS (tributação ad valorem), sem a alíquota em reais da pauta.
+    fn get_cofins_aliquota_percentual(&self) -> Option<f64> {
+        self.cofinsaliq
+            .as_ref()
+            .and_then(|c| c.p_cofins.parse_opt())
+            .or_else(|| {
+                self.cofinsoutr
+                    .as_ref()
+                    .and_then(|c| c.p_cofins.parse_opt())
+            })
+    }
+
     /// Obtém de forma declarativa o valor monetário calculado do COFINS.
     pub fn get_cofins_valor(&self) -> Option<f64> {
         self.cofinsaliq
@@ -1638,6 +1931,76 @@ impl Cofins {
                     .and_then(|c| c.v_cofins.parse_opt())
             })
     }
+
+    /// Identifica o regime da base de cálculo da COFINS conforme o grupo informado no XML.
+    pub fn get_cofins_tipo_base(&self) -> Option<TipoBaseCalculo> {
+        if self.cofinsaliq.is_some() {
+            Some(TipoBaseCalculo::Valor)
+        } else if self.cofinsqtde.is_some() {
+            Some(TipoBaseCalculo::Quantidade)
+        } else if self.cofinsnt.is_some() {
+            Some(TipoBaseCalculo::NaoTributado)
+        } else {
+            self.cofinsoutr.as_ref().and_then(|c| {
+                TipoBaseCalculo::from_campos(
+                    [c.v_bc.as_deref(), c.p_cofins.as_deref()],
+                    [c.q_bcprod.as_deref(), c.v_aliq_prod.as_deref()],
+                )
+            })
+        }
+    }
+
+    /// Obtém o valor monetário da base de cálculo da COFINS (`vBC`).
+    pub fn get_cofins_valor_base_calc(&self) -> Option<f64> {
+        self.cofinsaliq
+            .as_ref()
+            .and_then(|c| c.v_bc.parse_opt())
+            .or_else(|| self.cofinsoutr.as_ref().and_then(|c| c.v_bc.parse_opt()))
+    }
+
+    /// Obtém a quantidade vendida que serve de base de cálculo da COFINS (`qBCProd`).
+    pub fn get_cofins_quantidade_base_calc(&self) -> Option<f64> {
+        self.cofinsqtde
+            .as_ref()
+            .and_then(|c| c.q_bcprod.parse_opt())
+            .or_else(|| {
+                self.cofinsoutr
+                    .as_ref()
+                    .and_then(|c| c.q_bcprod.parse_opt())
+            })
+    }
+
+    /// Obtém a alíquota da COFINS em reais por unidade de produto (`vAliqProd`).
+    pub fn get_cofins_aliquota_em_reais(&self) -> Option<f64> {
+        self.cofinsqtde
+            .as_ref()
+            .and_then(|c| c.v_aliq_prod.parse_opt())
+            .or_else(|| {
+                self.cofinsoutr
+                    .as_ref()
+                    .and_then(|c| c.v_aliq_prod.parse_opt())
+            })
+    }
+
+    /// Apura a base de cálculo monetária da COFINS, reconstituída na tributação ad valorem.
+    pub fn get_cofins_base_calculo_valor(&self) -> Option<f64> {
+        calcular_base_valor(
+            self.get_cofins_tipo_base(),
+            self.get_cofins_valor_base_calc(),
+            self.get_cofins_aliquota_percentual(),
+            self.get_cofins_valor(),
+        )
+    }
+
+    /// Apura a quantidade base de cálculo da COFINS, reconstituída na tributação ad rem.
+    pub fn get_cofins_base_calculo_quantidade(&self) -> Option<f64> {
+        calcular_base_quantidade(
+            self.get_cofins_tipo_base(),
+            self.get_cofins_quantidade_base_calc(),
+            self.get_cofins_aliquota_em_reais(),
+            self.get_cofins_valor(),
+        )
+    }
 }
 
 /// COFINS tributado por alíquota percentual (`<COFINSAliq>`).
@@ -1740,6 +2103,33 @@ pub struct Cofinsst {
     pub text: Option<String>,
 }
 
+impl Cofinsst {
+    /// Obtém a base de cálculo monetária da COFINS ST.
+    pub fn get_valor_base_calc(&self) -> Option<f64> {
+        self.v_bc.parse_opt()
+    }
+
+    /// Obtém a alíquota percentual da COFINS ST.
+    pub fn get_aliquota(&self) -> Option<f64> {
+        self.p_cofins.parse_opt()
+    }
+
+    /// Obtém a quantidade que serve de base de cálculo da COFINS ST.
+    pub fn get_quantidade_base_calc(&self) -> Option<f64> {
+        self.q_bcprod.parse_opt()
+    }
+
+    /// Obtém a alíquota da COFINS ST em reais por unidade de produto.
+    pub fn get_aliquota_em_reais(&self) -> Option<f64> {
+        self.v_aliq_prod.parse_opt()
+    }
+
+    /// Obtém o valor calculado da COFINS ST.
+    pub fn get_valor(&self) -> Option<f64> {
+        self.v_cofins.parse_opt()
+    }
+}
+
 /// Bloco do ICMS para UF de Destino (`<ICMSUFDest>`).
 #[derive(Debug, Serialize, Deserialize, Clone)]
 pub struct Icmsufdest {
@@ -2350,3 +2740,121 @@ pub struct Icms61 {
     #[serde(rename = "$text", default)]
     pub text: Option<String>,
 }
+
+//----------------------------------------------------------------------------//
+//                                   Tests                                    //
+//----------------------------------------------------------------------------//
+
+/// Run tests with:
+/// cargo test -- --show-output tests_pis_cofins
+#[cfg(test)]
+mod tests_pis_cofins {
+    use super::*;
+
+    #[test]
+    /// `cargo test -- --show-output test_pis_cofins_ad_valorem`
+    fn test_pis_cofins_ad_valorem() {
+        let xml = r#"
+            <imposto>
+                <PIS><PISAliq><CST>01</CST><vBC>1000.00</vBC><pPIS>1.65</pPIS><vPIS>16.50</vPIS></PISAliq></PIS>
+                <COFINS><COFINSAliq><CST>01</CST><vBC>1000.00</vBC><pCOFINS>7.60</pCOFINS><vCOFINS>76.00</vCOFINS></COFINSAliq></COFINS>
+            </imposto>
+        "#;
+        let imposto: Imposto = quick_xml::de::from_str(xml).expect("Falha ao parsear imposto");
+
+        assert_eq!(imposto.get_tipo_base_pis(), Some(TipoBaseCalculo::Valor));
+        assert_eq!(imposto.get_v_bc_pis(), Some(1000.0));
+        assert_eq!(imposto.get_q_bc_pis(), None);
+        assert_eq!(imposto.get_aliq_pis(), Some(1.65));
+
+        assert_eq!(imposto.get_tipo_base_cofins(), Some(TipoBaseCalculo::Valor));
+        assert_eq!(imposto.get_v_bc_cofins(), Some(1000.0));
+        assert_eq!(imposto.get_q_bc_cofins(), None);
+        assert_eq!(imposto.get_v_cofins(), Some(76.0));
+    }
+
+    #[test]
+    /// `cargo test -- --show-output test_pis_cofins_ad_rem`
+    fn test_pis_cofins_ad_rem() {
+        let xml = r#"
+            <imposto>
+                <PIS><PISQtde><CST>03</CST><qBCProd>2000.0000</qBCProd><vAliqProd>0.1000</vAliqProd><vPIS>200.00</vPIS></PISQtde></PIS>
+                <COFINS><COFINSOutr><CST>99</CST><qBCProd>2000.0000</qBCProd><vAliqProd>0.4600</vAliqProd><vCOFINS>920.00</vCOFINS></COFINSOutr></COFINS>
+            </imposto>
+        "#;
+        let imposto: Imposto = quick_xml::de::from_str(xml).expect("Falha ao parsear imposto");
+
+        // A quantidade base de cálculo não se confunde com a base monetária.
+        assert_eq!(
+            imposto.get_tipo_base_pis(),
+            Some(TipoBaseCalculo::Quantidade)
+        );
+        assert_eq!(imposto.get_aliq_pis(), Some(0.1));
+        assert_eq!(imposto.get_aliq_reais_pis(), Some(0.1));
+        assert_eq!(imposto.get_q_bc_pis(), Some(2000.0));
+        assert_eq!(imposto.get_v_bc_pis(), None);
+        assert_eq!(imposto.get_v_pis(), Some(200.0));
+
+        assert_eq!(imposto.get_cst_cofins(), Some(99));
+        assert_eq!(
+            imposto.get_tipo_base_cofins(),
+            Some(TipoBaseCalculo::Quantidade)
+        );
+        assert_eq!(imposto.get_aliq_reais_cofins(), Some(0.46));
+        assert_eq!(imposto.get_q_bc_cofins(), Some(2000.0));
+        assert_eq!(imposto.get_v_bc_cofins(), None);
+    }
+
+    #[test]
+    /// `cargo test -- --show-output test_pis_cofins_base_reconstituida`
+    fn test_pis_cofins_base_reconstituida() {
+        // Grupo "Outras Operações" sem vBC: a base é reconstituída a partir de vPIS / pPIS.
+        let xml = r#"<PIS><PISOutr><CST>49</CST><vBC></vBC><pPIS>1.65</pPIS><vPIS>33.00</vPIS></PISOutr></PIS>"#;
+        let pis: Pis = quick_xml::de::from_str(xml).expect("Falha ao parsear PIS");
+        assert_eq!(pis.get_pis_tipo_base(), Some(TipoBaseCalculo::Valor));
+        assert_eq!(pis.get_pis_valor_base_calc(), None);
+        assert_eq!(pis.get_pis_base_calculo_valor(), Some(2000.0));
+        assert_eq!(pis.get_pis_base_calculo_quantidade(), None);
+
+        let xml = r#"<PIS><PISNT><CST>06</CST></PISNT></PIS>"#;
+        let pis: Pis = quick_xml::de::from_str(xml).expect("Falha ao parsear PIS");
+        assert_eq!(pis.get_pis_tipo_base(), Some(TipoBaseCalculo::NaoTributado));
+        assert_eq!(pis.get_pis_base_calculo_valor(), None);
+        assert_eq!(pis.get_pis_base_calculo_quantidade(), None);
+
+        assert_eq!(
+            calcular_base_valor(Some(TipoBaseCalculo::Valor), None, Some(1.65), Some(33.0)),
+            Some(2000.0)
+        );
+        assert_eq!(
+            calcular_base_quantidade(
+                Some(TipoBaseCalculo::Quantidade),
+                None,
+                Some(0.5),
+                Some(10.0)
+            ),
+            Some(20.0)
+        );
+    }
+
+    #[test]
+    /// `cargo test -- --show-output test_pis_cofins_substituicao_tributaria`
+    fn test_pis_cofins_substituicao_tributaria() {
+        let xml = r#"
+            <imposto>
+                <PISST><vBC>500.00</vBC><pPIS>1.65</pPIS><vPIS>8.25</vPIS></PISST>
+                <COFINSST><qBCProd>100.0000</qBCProd><vAliqProd>0.2000</vAliqProd><vCOFINS>20.00</vCOFINS></COFINSST>
+            </imposto>
+        "#;
+        let imposto: Imposto = quick_xml::de::from_str(xml).expect("Falha ao parsear imposto");
+
+        assert_eq!(imposto.get_v_bc_pisst(), Some(500.0));
+        assert_eq!(imposto.get_aliq_pisst(), Some(1.65));
+        assert_eq!(imposto.get_v_pisst(), Some(8.25));
+        assert_eq!(imposto.get_q_bc_cofinsst(), Some(100.0));
+        assert_eq!(imposto.get_aliq_reais_cofinsst(), Some(0.2));
+        assert_eq!(imposto.get_v_cofinsst(), Some(20.0));
+        // Sem grupos PIS/COFINS próprios.
+        assert_eq!(imposto.get_tipo_base_pis(), None);
+    }
+}
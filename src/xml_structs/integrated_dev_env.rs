@@ -8,10 +8,42 @@ use chrono::NaiveDate;
 use serde::{Deserialize, Serialize};
 
 use crate::{
-    get_naive_date_from_yyyy_mm_dd,
+    OptExt, descrever_codigo, get_naive_date_from_yyyy_mm_dd,
     xml_structs::agente::{Agente, AgenteExtension},
 };
 
+/// Tipo do Documento Fiscal (`tpNF`).
+const TIPO_DE_OPERACAO: [(&str, &str); 2] = [("0", "Entrada"), ("1", "Saída")];
+
+/// Finalidade de emissão da NF-e (`finNFe`).
+const FINALIDADE_DE_EMISSAO: [(&str, &str); 4] = [
+    ("1", "NF-e normal"),
+    ("2", "NF-e complementar"),
+    ("3", "NF-e de ajuste"),
+    ("4", "Devolução de mercadoria"),
+];
+
+/// Identificador de local de destino da operação (`idDest`).
+const DESTINO_DA_OPERACAO: [(&str, &str); 3] = [
+    ("1", "Operação interna"),
+    ("2", "Operação interestadual"),
+    ("3", "Operação com exterior"),
+];
+
+/// Indicador de operação com Consumidor Final (`indFinal`).
+const CONSUMIDOR_FINAL: [(&str, &str); 2] = [("0", "Normal"), ("1", "Consumidor final")];
+
+/// Indicador de presença do comprador no estabelecimento (`indPres`).
+const PRESENCA_DO_COMPRADOR: [(&str, &str); 7] = [
+    ("0", "Não se aplica"),
+    ("1", "Operação presencial"),
+    ("2", "Operação não presencial, pela Internet"),
+    ("3", "Operação não presencial, Teleatendimento"),
+    ("4", "NFC-e em operação com entrega a domicílio"),
+    ("5", "Operação presencial, fora do estabelecimento"),
+    ("9", "Operação não presencial, outros"),
+];
+
 /// Dados de Identificação do Documento Fiscal Eletrônico (`<ide>`).
 ///
 /// Contém informações gerais sobre a emissão do documento, parâmetros de controle,
@@ -243,6 +275,64 @@ impl Ide {
             .and_then(|numero| numero.trim().parse::<u16>().ok())
     }
 
+    /// Retorna o modelo do documento fiscal (p. ex., `55` para NF-e).
+    pub fn get_modelo(&self) -> Option<String> {
+        self.modelo.as_deref().map(|m| m.trim().to_string())
+    }
+
+    /// Retorna a série do documento fiscal.
+    pub fn get_serie(&self) -> Option<String> {
+        self.serie.as_deref().map(|s| s.trim().to_string())
+    }
+
+    /// Retorna a natureza da operação (`natOp`) declarada pelo emitente.
+    pub fn get_natureza_operacao(&self) -> Option<String> {
+        self.nat_operacao.as_deref().map(|n| n.trim().to_string())
+    }
+
+    /// Descreve o tipo de operação (`tpNF`): `"0 - Entrada"` ou `"1 - Saída"`.
+    pub fn get_tipo_operacao(&self) -> Option<String> {
+        descrever_codigo(&self.tp_nf, &TIPO_DE_OPERACAO)
+    }
+
+    /// Descreve a finalidade de emissão da NF-e (`finNFe`).
+    pub fn get_finalidade(&self) -> Option<String> {
+        descrever_codigo(&self.fin_nfe, &FINALIDADE_DE_EMISSAO)
+    }
+
+    /// Chaves das NF-es referenciadas (`NFref/refNFe`).
+    pub fn get_nfes_referenciadas(&self) -> Vec<String> {
+        self.nfref
+            .iter()
+            .flatten()
+            .filter_map(|nfref| nfref.ref_nfe.get_key())
+            .collect()
+    }
+
+    /// Descrições dos demais documentos referenciados (`refNF`, `refNFP`, `refECF` e `refCTe`).
+    pub fn get_outros_documentos_referenciados(&self) -> Vec<String> {
+        self.nfref
+            .iter()
+            .flatten()
+            .flat_map(|nfref| nfref.descrever_outros())
+            .collect()
+    }
+
+    /// Descreve o local de destino da operação (`idDest`).
+    pub fn get_destino_operacao(&self) -> Option<String> {
+        descrever_codigo(&self.id_dest, &DESTINO_DA_OPERACAO)
+    }
+
+    /// Descreve o indicador de operação com consumidor final (`indFinal`).
+    pub fn get_consumidor_final(&self) -> Option<String> {
+        descrever_codigo(&self.ind_final, &CONSUMIDOR_FINAL)
+    }
+
+    /// Descreve o indicador de presença do comprador (`indPres`).
+    pub fn get_presenca_comprador(&self) -> Option<String> {
+        descrever_codigo(&self.ind_pres, &PRESENCA_DO_COMPRADOR)
+    }
+
     /// Obtém de forma preguiçosa a data de emissão do documento fiscal a partir de `dEmi` ou de `dhEmi`.
     pub fn get_dt_emissao(&self) -> Option<NaiveDate> {
         get_naive_date_from_yyyy_mm_dd(&self.d_emi)
@@ -350,6 +440,64 @@ pub struct NFref {
     pub ref_nfp: Option<RefNfp>,
 }
 
+impl NFref {
+    /// Descreve as referências que não são chaves de NF-e.
+    pub fn descrever_outros(&self) -> Vec<String> {
+        let ref_nf = self.ref_nf.as_ref().map(|nf| {
+            descrever_campos(
+                "NF",
+                &[
+                    ("modelo", nf.modelo.as_ref()),
+                    ("série", nf.serie.as_ref()),
+                    ("nº", nf.n_nf.as_ref()),
+                    ("emitente", nf.cnpj.as_ref().or(nf.cpf.as_ref())),
+                    ("AAMM", nf.aamm.as_ref()),
+                ],
+            )
+        });
+        let ref_nfp = self.ref_nfp.as_ref().map(|nfp| {
+            descrever_campos(
+                "NF de Produtor",
+                &[
+                    ("modelo", nfp.modelo.as_ref()),
+                    ("série", nfp.serie.as_ref()),
+                    ("nº", nfp.n_nf.as_ref()),
+                    ("emitente", nfp.cnpj.as_ref().or(nfp.cpf.as_ref())),
+                    ("AAMM", nfp.aamm.as_ref()),
+                ],
+            )
+        });
+        let ref_ecf = self.ref_ecf.as_ref().map(|ecf| {
+            descrever_campos(
+                "Cupom Fiscal",
+                &[
+                    ("modelo", ecf.modelo.as_ref()),
+                    ("ECF nº", ecf.n_ecf.as_ref()),
+                    ("COO", ecf.n_coo.as_ref()),
+                ],
+            )
+        });
+        let ref_cte = self.ref_cte.get_key().map(|cte| format!("CTe {cte}"));
+
+        [ref_nf, ref_nfp, ref_ecf, ref_cte]
+            .into_iter()
+            .flatten()
+            .collect()
+    }
+}
+
+/// Junta o título aos campos presentes: "NF modelo 01 série 1 nº 123".
+fn descrever_campos(titulo: &str, campos: &[(&str, Option<&String>)]) -> String {
+    std::iter::once(titulo.to_string())
+        .chain(
+            campos
+                .iter()
+                .filter_map(|(rotulo, valor)| valor.map(|v| format!("{rotulo} {}", v.trim()))),
+        )
+        .collect::<Vec<_>>()
+        .join(" ")
+}
+
 /// Dados de Nota Fiscal de papel modelo convencional referenciada (`<refNF>`).
 #[derive(Debug, Serialize, Deserialize, Clone)]
 pub struct RefNf {
@@ -15,6 +15,7 @@ pub mod impostos;
 pub mod integrated_dev_env;
 pub mod nfe;
 pub mod nfe_detalhamento;
+pub mod nfe_documento;
 pub mod nfe_evento;
 pub mod pagamento;
 pub mod ret_evento;
@@ -45,12 +45,13 @@ use crate::{
         agente::{Agente, AgenteExtension},
         assinaturas::Signature,
         aut_xml::{AutXML, InfRespTec},
-        cobranca::Cobranca,
+        cobranca::{Cobranca, InfoDuplicata},
         entrega::Entrega,
         impostos::Total,
         integrated_dev_env::Ide,
         nfe_detalhamento::*,
-        pagamento::Pagamento,
+        nfe_documento::InfoNfeDocumento,
+        pagamento::{InfoPagamento, Pagamento},
     },
 };
 
@@ -94,7 +95,7 @@ pub struct InfoNfe {
 
     /// CNPJ do Destinatário formatado.
     #[serde(rename = "CNPJ do Destinatário", default)]
-    destinatario_cnpj: Option<String>,
+    pub destinatario_cnpj: Option<String>,
 
     /// CPF do Destinatário formatado.
     #[serde(rename = "CPF do Destinatário", default)]
@@ -144,6 +145,18 @@ pub struct InfoNfe {
     )]
     data_saida: Option<NaiveDate>,
 
+    /// Tipo de operação (`tpNF`): Entrada ou Saída, conforme declarado pelo emitente.
+    #[serde(rename = "Tipo de Operação", default)]
+    pub tipo_operacao: Option<String>,
+
+    /// Papel da empresa auditada (`--cnpj-base`) na NF-e: emitente e/ou destinatário.
+    #[serde(rename = "Papel da Empresa Auditada", default)]
+    pub papel_empresa: Option<String>,
+
+    /// Direção da operação do ponto de vista da empresa auditada.
+    #[serde(rename = "Entrada ou Saída da Empresa Auditada", default)]
+    pub direcao: Option<String>,
+
     /// Número do item dentro da Nota Fiscal.
     #[serde(rename = "Nº do Item", default)]
     pub n_item: Option<u32>,
@@ -162,7 +175,7 @@ pub struct InfoNfe {
 
     /// Código Fiscal de Operações e Prestações (CFOP) do item.
     #[serde(rename = "CFOP (Código Fiscal de Operações e Prestações)", default)]
-    cfop: Option<u16>,
+    pub cfop: Option<u16>,
 
     /// Código da Nomenclatura Comum do Mercosul (NCM).
     #[serde(rename = "NCM (Nomenclatura Comum do Mercosul)", default)]
@@ -181,11 +194,15 @@ pub struct InfoNfe {
 
     /// Código de Situação Tributária (CST) do PIS.
     #[serde(rename = "CST de PIS/PASEP", default)]
-    cst_pis: Option<u8>,
+    pub cst_pis: Option<u8>,
 
     /// Código de Situação Tributária (CST) do COFINS.
     #[serde(rename = "CST de COFINS", default)]
-    cst_cofins: Option<u8>,
+    pub cst_cofins: Option<u8>,
+
+    /// Identificador da cadeia de frete dos CT-es que transportam a NF-e.
+    #[serde(rename = "Cadeia de Frete", default)]
+    pub cadeia: Option<String>,
 
     /// Chaves de CT-e relacionados a este documento fiscal.
     #[serde(
@@ -207,6 +224,12 @@ pub struct InfoNfe {
     #[serde(rename = "Valor Total de CTes", default)]
     pub valor_total_ctes: Option<f64>,
 
+    /// Parcela do frete dos CT-es correlacionados atribuída a este item.
+    ///
+    /// Ver [`crate::CriterioRateio`].
+    #[serde(rename = "Valor do Frete Rateado (CTes)", default)]
+    pub valor_frete_rateado: Option<f64>,
+
     /// Valor Total da Nota Fiscal Eletrônica (vNF).
     #[serde(rename = "Valor Total da NFe", default)]
     pub valor_total_nfe: Option<f64>,
@@ -247,6 +270,82 @@ pub struct InfoNfe {
     #[serde(rename = "Valor de COFINS", default)]
     v_cofins: Option<f64>,
 
+    /// Regime da base de cálculo do PIS: ad valorem, ad rem ou não tributado.
+    #[serde(rename = "Tipo de Base de Cálculo de PIS/PASEP", default)]
+    tipo_base_pis: Option<String>,
+
+    /// Base de cálculo monetária do PIS (vBC), reconstituída de vPIS / pPIS quando ausente
+    /// na tributação ad valorem.
+    #[serde(rename = "Valor da Base de Cálculo de PIS/PASEP", default)]
+    v_bc_pis: Option<f64>,
+
+    /// Quantidade vendida adotada como base de cálculo do PIS (qBCProd), reconstituída de
+    /// vPIS / vAliqProd quando ausente na tributação ad rem.
+    #[serde(rename = "Quantidade da Base de Cálculo de PIS/PASEP", default)]
+    q_bc_pis: Option<f64>,
+
+    /// Alíquota do PIS em reais por unidade de produto (vAliqProd).
+    #[serde(rename = "Alíquota de PIS/PASEP em Reais por Unidade", default)]
+    aliq_reais_pis: Option<f64>,
+
+    /// Regime da base de cálculo da COFINS: ad valorem, ad rem ou não tributado.
+    #[serde(rename = "Tipo de Base de Cálculo de COFINS", default)]
+    tipo_base_cofins: Option<String>,
+
+    /// Base de cálculo monetária da COFINS (vBC), reconstituída de vCOFINS / pCOFINS quando
+    /// ausente na tributação ad valorem.
+    #[serde(rename = "Valor da Base de Cálculo de COFINS", default)]
+    v_bc_cofins: Option<f64>,
+
+    /// Quantidade vendida adotada como base de cálculo da COFINS (qBCProd), reconstituída de
+    /// vCOFINS / vAliqProd quando ausente na tributação ad rem.
+    #[serde(rename = "Quantidade da Base de Cálculo de COFINS", default)]
+    q_bc_cofins: Option<f64>,
+
+    /// Alíquota da COFINS em reais por unidade de produto (vAliqProd).
+    #[serde(rename = "Alíquota de COFINS em Reais por Unidade", default)]
+    aliq_reais_cofins: Option<f64>,
+
+    /// Base de cálculo monetária do PIS devido por Substituição Tributária.
+    #[serde(rename = "Valor da Base de Cálculo de PIS/PASEP ST", default)]
+    v_bc_pisst: Option<f64>,
+
+    /// Alíquota percentual do PIS ST.
+    #[serde(rename = "Alíquota de PIS/PASEP ST", default)]
+    aliq_pisst: Option<f64>,
+
+    /// Quantidade adotada como base de cálculo do PIS ST.
+    #[serde(rename = "Quantidade da Base de Cálculo de PIS/PASEP ST", default)]
+    q_bc_pisst: Option<f64>,
+
+    /// Alíquota do PIS ST em reais por unidade de produto.
+    #[serde(rename = "Alíquota de PIS/PASEP ST em Reais por Unidade", default)]
+    aliq_reais_pisst: Option<f64>,
+
+    /// Valor do PIS devido por Substituição Tributária.
+    #[serde(rename = "Valor de PIS/PASEP ST", default)]
+    v_pisst: Option<f64>,
+
+    /// Base de cálculo monetária da COFINS devida por Substituição Tributária.
+    #[serde(rename = "Valor da Base de Cálculo de COFINS ST", default)]
+    v_bc_cofinsst: Option<f64>,
+
+    /// Alíquota percentual da COFINS ST.
+    #[serde(rename = "Alíquota de COFINS ST", default)]
+    aliq_cofinsst: Option<f64>,
+
+    /// Quantidade adotada como base de cálculo da COFINS ST.
+    #[serde(rename = "Quantidade da Base de Cálculo de COFINS ST", default)]
+    q_bc_cofinsst: Option<f64>,
+
+    /// Alíquota da COFINS ST em reais por unidade de produto.
+    #[serde(rename = "Alíquota de COFINS ST em Reais por Unidade", default)]
+    aliq_reais_cofinsst: Option<f64>,
+
+    /// Valor da COFINS devida por Substituição Tributária.
+    #[serde(rename = "Valor de COFINS ST", default)]
+    v_cofinsst: Option<f64>,
+
     /// Valor monetário calculado correspondente ao IPI do item.
     #[serde(rename = "Valor de IPI", default)]
     v_ipi: Option<f64>,
@@ -312,6 +411,21 @@ impl GetID<Option<(String, u32)>> for InfoNfe {
     }
 }
 
+/// Tabelas extraídas de um único arquivo XML de NF-e.
+#[derive(Debug, Default, Serialize, Deserialize, Clone)]
+pub struct TabelasNfe {
+    /// Uma linha por item da NF-e.
+    pub itens: Vec<InfoNfe>,
+    /// Uma única linha com os dados de cabeçalho da NF-e.
+    pub documento: InfoNfeDocumento,
+    /// Uma linha por forma de pagamento (`<detPag>`).
+    pub pagamentos: Vec<InfoPagamento>,
+    /// Uma linha por parcela de cobrança (`<dup>`).
+    pub duplicatas: Vec<InfoDuplicata>,
+    /// Uma linha por volume transportado (`<vol>`).
+    pub volumes: Vec<InfoVolume>,
+}
+
 /// Representa a extração primária dos dados consolidados de um item da NF-e.
 #[derive(Debug, Default, Serialize, Deserialize, Clone)]
 pub struct Item {
@@ -345,6 +459,42 @@ pub struct Item {
     v_pis: Option<f64>,
     /// Valor acumulado apurado para o COFINS.
     v_cofins: Option<f64>,
+    /// Regime da base de cálculo do PIS.
+    tipo_base_pis: Option<String>,
+    /// Base de cálculo monetária do PIS.
+    v_bc_pis: Option<f64>,
+    /// Quantidade base de cálculo do PIS.
+    q_bc_pis: Option<f64>,
+    /// Alíquota do PIS em reais por unidade.
+    aliq_reais_pis: Option<f64>,
+    /// Regime da base de cálculo da COFINS.
+    tipo_base_cofins: Option<String>,
+    /// Base de cálculo monetária da COFINS.
+    v_bc_cofins: Option<f64>,
+    /// Quantidade base de cálculo da COFINS.
+    q_bc_cofins: Option<f64>,
+    /// Alíquota da COFINS em reais por unidade.
+    aliq_reais_cofins: Option<f64>,
+    /// Base de cálculo do PIS ST.
+    v_bc_pisst: Option<f64>,
+    /// Alíquota percentual do PIS ST.
+    aliq_pisst: Option<f64>,
+    /// Quantidade base de cálculo do PIS ST.
+    q_bc_pisst: Option<f64>,
+    /// Alíquota do PIS ST em reais por unidade.
+    aliq_reais_pisst: Option<f64>,
+    /// Valor do PIS ST.
+    v_pisst: Option<f64>,
+    /// Base de cálculo da COFINS ST.
+    v_bc_cofinsst: Option<f64>,
+    /// Alíquota percentual da COFINS ST.
+    aliq_cofinsst: Option<f64>,
+    /// Quantidade base de cálculo da COFINS ST.
+    q_bc_cofinsst: Option<f64>,
+    /// Alíquota da COFINS ST em reais por unidade.
+    aliq_reais_cofinsst: Option<f64>,
+    /// Valor da COFINS ST.
+    v_cofinsst: Option<f64>,
     /// Valor acumulado apurado para o IPI.
     v_ipi: Option<f64>,
     /// Valor acumulado apurado para o ISS.
@@ -387,7 +537,13 @@ impl StructExtension for NfeProc {
             println!("nfe xml_path: {xml_path:?}");
             println!("nfe_proc: {self:#?}\n");
         }
-        Information::Nfe(self.get_infos())
+        Information::Nfe(Box::new(TabelasNfe {
+            itens: self.get_infos(),
+            documento: self.get_documento(),
+            pagamentos: self.get_pagamentos(),
+            duplicatas: self.get_duplicatas(),
+            volumes: self.get_volumes(),
+        }))
     }
 }
 
@@ -522,6 +678,14 @@ impl NfeProc {
             .and_then(|information| information.ide.get_dt_saida())
     }
 
+    /// Descreve o tipo de operação da NF-e (`tpNF`): `"0 - Entrada"` ou `"1 - Saída"`.
+    pub fn get_tipo_operacao(&self) -> Option<String> {
+        self.nfe
+            .inf_nfe
+            .as_ref()
+            .and_then(|information| information.ide.get_tipo_operacao())
+    }
+
     /// Extrai o valor consolidado final do documento fiscal (vNF).
     pub fn get_total_da_nfe(&self) -> Option<f64> {
         self.nfe
@@ -555,6 +719,24 @@ impl NfeProc {
                         aliq_cofins: imposto.get_aliq_cofins(),
                         v_pis: imposto.get_v_pis(),
                         v_cofins: imposto.get_v_cofins(),
+                        tipo_base_pis: imposto.get_tipo_base_pis().map(|t| t.to_string()),
+                        v_bc_pis: imposto.get_v_bc_pis(),
+                        q_bc_pis: imposto.get_q_bc_pis(),
+                        aliq_reais_pis: imposto.get_aliq_reais_pis(),
+                        tipo_base_cofins: imposto.get_tipo_base_cofins().map(|t| t.to_string()),
+                        v_bc_cofins: imposto.get_v_bc_cofins(),
+                        q_bc_cofins: imposto.get_q_bc_cofins(),
+                        aliq_reais_cofins: imposto.get_aliq_reais_cofins(),
+                        v_bc_pisst: imposto.get_v_bc_pisst(),
+                        aliq_pisst: imposto.get_aliq_pisst(),
+                        q_bc_pisst: imposto.get_q_bc_pisst(),
+                        aliq_reais_pisst: imposto.get_aliq_reais_pisst(),
+                        v_pisst: imposto.get_v_pisst(),
+                        v_bc_cofinsst: imposto.get_v_bc_cofinsst(),
+                        aliq_cofinsst: imposto.get_aliq_cofinsst(),
+                        q_bc_cofinsst: imposto.get_q_bc_cofinsst(),
+                        aliq_reais_cofinsst: imposto.get_aliq_reais_cofinsst(),
+                        v_cofinsst: imposto.get_v_cofinsst(),
                         v_ipi: imposto.get_v_ipi(),
                         v_iss: imposto.get_v_iss(),
                         v_bc_icms: imposto.get_v_bc_icms(),
@@ -583,6 +765,68 @@ impl NfeProc {
             .and_then(|information| information.get_info_adic_fisco())
     }
 
+    /// Retorna as formas de pagamento (`<detPag>`) acrescidas da identificação da NF-e.
+    pub fn get_pagamentos(&self) -> Vec<InfoPagamento> {
+        let Some(pag) = self.nfe.inf_nfe.as_ref().and_then(|info| info.pag.as_ref()) else {
+            return Vec::new();
+        };
+
+        let mut pagamentos = pag.get_info_pagamentos();
+        for pagamento in pagamentos.iter_mut() {
+            pagamento.emitente_cnpj = self.get_emitente_cnpj();
+            pagamento.emitente_cpf = self.get_emitente_cpf();
+            pagamento.destinatario_cnpj = self.get_destinatario_cnpj();
+            pagamento.destinatario_cpf = self.get_destinatario_cpf();
+            pagamento.nfe = self.get_nfe();
+            pagamento.numero_da_nota = self.get_numero_da_nota();
+            pagamento.data_emissao = self.get_data_emissao();
+        }
+        pagamentos
+    }
+
+    /// Retorna as parcelas de cobrança (`<cobr>`) acrescidas da identificação da NF-e.
+    pub fn get_duplicatas(&self) -> Vec<InfoDuplicata> {
+        let Some(cobr) = self
+            .nfe
+            .inf_nfe
+            .as_ref()
+            .and_then(|info| info.cobr.as_ref())
+        else {
+            return Vec::new();
+        };
+
+        let mut duplicatas = cobr.get_info_duplicatas();
+        for duplicata in duplicatas.iter_mut() {
+            duplicata.emitente_cnpj = self.get_emitente_cnpj();
+            duplicata.emitente_cpf = self.get_emitente_cpf();
+            duplicata.destinatario_cnpj = self.get_destinatario_cnpj();
+            duplicata.destinatario_cpf = self.get_destinatario_cpf();
+            duplicata.nfe = self.get_nfe();
+            duplicata.numero_da_nota = self.get_numero_da_nota();
+            duplicata.data_emissao = self.get_data_emissao();
+        }
+        duplicatas
+    }
+
+    /// Retorna os volumes transportados (`<vol>`) acrescidos da identificação da NF-e.
+    pub fn get_volumes(&self) -> Vec<InfoVolume> {
+        let Some(info) = self.nfe.inf_nfe.as_ref() else {
+            return Vec::new();
+        };
+
+        let mut volumes = info.transp.get_info_volumes();
+        for volume in volumes.iter_mut() {
+            volume.emitente_cnpj = self.get_emitente_cnpj();
+            volume.emitente_cpf = self.get_emitente_cpf();
+            volume.nfe = self.get_nfe();
+            volume.numero_da_nota = self.get_numero_da_nota();
+            volume.data_emissao = self.get_data_emissao();
+            volume.modalidade_frete = info.transp.get_modalidade_frete();
+            volume.transportadora_cnpj = info.transp.get_transportadora_cnpj();
+        }
+        volumes
+    }
+
     /// Retorna uma coleção de estruturas `InfoNfe` unificadas a partir dos itens do XML da NF-e.
     pub fn get_infos(&self) -> Vec<InfoNfe> {
         let mut infos = Vec::new();
@@ -611,6 +855,9 @@ impl NfeProc {
                 numero_da_nota: self.get_numero_da_nota(),
                 data_emissao: self.get_data_emissao(),
                 data_saida: self.get_data_saida(),
+                tipo_operacao: self.get_tipo_operacao(),
+                papel_empresa: None,
+                direcao: None,
                 n_item: item.n_item,
                 numero_de_itens,
                 n_di: item.n_di,
@@ -621,9 +868,11 @@ impl NfeProc {
                 info_adic_fisco: self.get_info_adic_fisco(),
                 cst_pis: item.cst_pis,
                 cst_cofins: item.cst_cofins,
+                cadeia: None,
                 ctes: Vec::new(),
                 tomadores: Vec::new(),
                 valor_total_ctes: None,
+                valor_frete_rateado: None,
                 valor_total_nfe: self.get_total_da_nfe(),
                 valor_total_itens,
                 v_prod: item.v_prod, // valor do Item
@@ -634,6 +883,24 @@ impl NfeProc {
                 aliq_cofins: item.aliq_cofins,
                 v_pis: item.v_pis,
                 v_cofins: item.v_cofins,
+                tipo_base_pis: item.tipo_base_pis,
+                v_bc_pis: item.v_bc_pis,
+                q_bc_pis: item.q_bc_pis,
+                aliq_reais_pis: item.aliq_reais_pis,
+                tipo_base_cofins: item.tipo_base_cofins,
+                v_bc_cofins: item.v_bc_cofins,
+                q_bc_cofins: item.q_bc_cofins,
+                aliq_reais_cofins: item.aliq_reais_cofins,
+                v_bc_pisst: item.v_bc_pisst,
+                aliq_pisst: item.aliq_pisst,
+                q_bc_pisst: item.q_bc_pisst,
+                aliq_reais_pisst: item.aliq_reais_pisst,
+                v_pisst: item.v_pisst,
+                v_bc_cofinsst: item.v_bc_cofinsst,
+                aliq_cofinsst: item.aliq_cofinsst,
+                q_bc_cofinsst: item.q_bc_cofinsst,
+                aliq_reais_cofinsst: item.aliq_reais_cofinsst,
+                v_cofinsst: item.v_cofinsst,
                 v_ipi: item.v_ipi,
                 v_iss: item.v_iss,
                 v_bc_icms: item.v_bc_icms,
@@ -1013,4 +1280,129 @@ mod test_functions {
 
         Ok(())
     }
+
+    #[test]
+    /// `cargo test -- --show-output pagamentos_e_duplicatas`
+    fn pagamentos_e_duplicatas() -> XmlParserResult<()> {
+        let path = Path::new("35250199999999999999550000000000021001034139_NFe.xml");
+        let nfe_proc = NfeProc::xml_parse(path)?;
+
+        // <pag>: um único detPag com boleto bancário
+        let pagamentos = nfe_proc.get_pagamentos();
+        println!("pagamentos: {pagamentos:#?}");
+        assert_eq!(pagamentos.len(), 1);
+        assert_eq!(pagamentos[0].nfe, nfe_proc.get_nfe());
+        assert_eq!(pagamentos[0].n_pag, Some(1));
+        assert_eq!(pagamentos[0].meio, Some("15 - Boleto Bancário".to_string()));
+        assert_eq!(pagamentos[0].valor, Some(1234.56));
+
+        // <cobr>: fatura sem duplicatas gera uma única linha
+        let duplicatas = nfe_proc.get_duplicatas();
+        println!("duplicatas: {duplicatas:#?}");
+        assert_eq!(duplicatas.len(), 1);
+        assert_eq!(duplicatas[0].nfe, nfe_proc.get_nfe());
+        assert_eq!(duplicatas[0].n_fat, Some("FAT-123".to_string()));
+        assert_eq!(duplicatas[0].fatura_liquido, Some(1234.56));
+        assert_eq!(duplicatas[0].n_dup, None);
+
+        // Fatura parcelada com cartão de crédito
+        let cobr: Cobranca = quick_xml::de::from_str(
+            "<cobr><fat><nFat>77</nFat><vOrig>300.00</vOrig><vLiq>300.00</vLiq></fat>\
+             <dup><nDup>001</nDup><dVenc>2025-02-10</dVenc><vDup>100.00</vDup></dup>\
+             <dup><nDup>002</nDup><dVenc>2025-03-10</dVenc><vDup>200.00</vDup></dup></cobr>",
+        )?;
+        let duplicatas = cobr.get_info_duplicatas();
+        assert_eq!(duplicatas.len(), 2);
+        assert_eq!(duplicatas[1].ordem, Some(2));
+        assert_eq!(duplicatas[1].n_dup, Some("002".to_string()));
+        assert_eq!(
+            duplicatas[1].vencimento,
+            NaiveDate::from_ymd_opt(2025, 3, 10)
+        );
+        assert_eq!(duplicatas[1].valor, Some(200.0));
+        assert_eq!(duplicatas[1].n_fat, Some("77".to_string()));
+
+        let pag: Pagamento = quick_xml::de::from_str(
+            "<pag><detPag><indPag>1</indPag><tPag>03</tPag><vPag>310.00</vPag>\
+             <card><tpIntegra>2</tpIntegra><CNPJ>12345678000199</CNPJ><tBand>01</tBand>\
+             <cAut>A1B2</cAut></card></detPag><vTroco>10.00</vTroco></pag>",
+        )?;
+        let pagamentos = pag.get_info_pagamentos();
+        assert_eq!(pagamentos.len(), 1);
+        assert_eq!(
+            pagamentos[0].indicador,
+            Some("1 - Pagamento a Prazo".to_string())
+        );
+        assert_eq!(pagamentos[0].bandeira, Some("01 - Visa".to_string()));
+        assert_eq!(
+            pagamentos[0].cnpj_credenciadora,
+            Some("12345678000199".to_string())
+        );
+        assert_eq!(pagamentos[0].autorizacao, Some("A1B2".to_string()));
+        assert_eq!(pagamentos[0].troco, Some(10.0));
+
+        Ok(())
+    }
+
+    #[test]
+    /// `cargo test -- --show-output transporte_e_volumes`
+    fn transporte_e_volumes() -> XmlParserResult<()> {
+        let path = Path::new("35250199999999999999550000000000021001034139_NFe.xml");
+        let nfe_proc = NfeProc::xml_parse(path)?;
+
+        // <transp>: FOB com um único volume
+        let volumes = nfe_proc.get_volumes();
+        println!("volumes: {volumes:#?}");
+        assert_eq!(volumes.len(), 1);
+        assert_eq!(volumes[0].nfe, nfe_proc.get_nfe());
+        assert_eq!(volumes[0].ordem, Some(1));
+        assert_eq!(volumes[0].quantidade, Some(12));
+        assert_eq!(volumes[0].peso_liquido, Some(123.456));
+        assert_eq!(volumes[0].peso_bruto, Some(234.567));
+        assert_eq!(
+            volumes[0].modalidade_frete,
+            Some("1 - Contratação do Frete por conta do Destinatário (FOB)".to_string())
+        );
+
+        // Transportadora, veículo, reboques e vários volumes com lacres
+        let transp: Transp = quick_xml::de::from_str(
+            "<transp><modFrete>0</modFrete>\
+             <transporta><CNPJ>12345678000199</CNPJ><xNome>TRANSPORTES TESTE</xNome><UF>SP</UF></transporta>\
+             <veicTransp><placa>ABC1D23</placa><UF>SP</UF></veicTransp>\
+             <reboque><placa>XYZ9A87</placa><UF>SP</UF></reboque>\
+             <vol><qVol>2</qVol><esp>CAIXA</esp><pesoL>10.5</pesoL><pesoB>11.25</pesoB>\
+             <lacres><nLacre>L1</nLacre></lacres><lacres><nLacre>L2</nLacre></lacres></vol>\
+             <vol><qVol>3</qVol><esp>PALETE</esp><pesoL>100.0</pesoL><pesoB>120.5</pesoB></vol></transp>",
+        )?;
+        assert_eq!(
+            transp.get_transportadora_cnpj(),
+            Some("12.345.678/0001-99".to_string())
+        );
+        assert_eq!(
+            transp.get_transportadora_nome(),
+            Some("TRANSPORTES TESTE".to_string())
+        );
+        assert_eq!(transp.get_placa_veiculo(), Some("ABC1D23".to_string()));
+        assert_eq!(transp.get_placas_reboques(), ["XYZ9A87"]);
+        assert_eq!(transp.get_quantidade_volumes(), Some(5));
+        assert_eq!(transp.get_peso_liquido(), Some(110.5));
+        assert_eq!(transp.get_peso_bruto(), Some(131.75));
+
+        let volumes = transp.get_info_volumes();
+        assert_eq!(volumes.len(), 2);
+        assert_eq!(volumes[0].lacres, ["L1", "L2"]);
+        assert_eq!(volumes[1].ordem, Some(2));
+        assert_eq!(volumes[1].especie, Some("PALETE".to_string()));
+
+        // Volume sem peso bruto: o total não é apurado para não subestimar a carga
+        let transp: Transp = quick_xml::de::from_str(
+            "<transp><modFrete>0</modFrete>\
+             <vol><qVol>1</qVol><pesoL>10.0</pesoL><pesoB>12.0</pesoB></vol>\
+             <vol><qVol>1</qVol><pesoL>5.0</pesoL></vol></transp>",
+        )?;
+        assert_eq!(transp.get_peso_liquido(), Some(15.0));
+        assert_eq!(transp.get_peso_bruto(), None);
+
+        Ok(())
+    }
 }
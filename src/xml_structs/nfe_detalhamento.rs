@@ -9,10 +9,26 @@
 //! para assegurar compatibilidade posicional de leitura em fluxo e tolerância
 //! a variações regulatórias do leiaute nacional.
 
-use claudiofsr_lib::StrExtension;
+use chrono::NaiveDate;
+use claudiofsr_lib::{OptionExtension, RoundFloat, StrExtension};
+use rust_xlsxwriter::serialize_option_datetime_to_excel;
 use serde::{Deserialize, Serialize};
-
-use crate::xml_structs::{aut_xml::InfProtocolo, impostos::Imposto};
+use struct_iterable::Iterable;
+
+use crate::{
+    GetID, GetKey, InfoExtension, descrever_codigo, serialize_vec_string,
+    xml_structs::{aut_xml::InfProtocolo, impostos::Imposto},
+};
+
+/// Modalidade do frete (`modFrete`).
+const MODALIDADE_DO_FRETE: [(&str, &str); 6] = [
+    ("0", "Contratação do Frete por conta do Remetente (CIF)"),
+    ("1", "Contratação do Frete por conta do Destinatário (FOB)"),
+    ("2", "Contratação do Frete por conta de Terceiros"),
+    ("3", "Transporte Próprio por conta do Remetente"),
+    ("4", "Transporte Próprio por conta do Destinatário"),
+    ("9", "Sem Ocorrência de Transporte"),
+];
 
 /// Bloco de Detalhes dos Itens da NF-e (`<det>`).
 #[derive(Debug, Serialize, Deserialize, Clone)]
@@ -727,6 +743,213 @@ pub struct Transp {
     pub vol: Option<Vec<Vol>>,
 }
 
+impl Transp {
+    /// Descreve a modalidade do frete (`modFrete`), p. ex. `"9 - Sem Ocorrência de Transporte"`.
+    pub fn get_modalidade_frete(&self) -> Option<String> {
+        descrever_codigo(&self.mod_frete, &MODALIDADE_DO_FRETE)
+    }
+
+    /// CNPJ formatado da transportadora.
+    pub fn get_transportadora_cnpj(&self) -> Option<String> {
+        self.transporta.as_ref().and_then(|t| t.get_cnpj())
+    }
+
+    /// CPF formatado do transportador autônomo.
+    pub fn get_transportadora_cpf(&self) -> Option<String> {
+        self.transporta.as_ref().and_then(|t| t.get_cpf())
+    }
+
+    /// Razão social ou nome da transportadora.
+    pub fn get_transportadora_nome(&self) -> Option<String> {
+        self.transporta.as_ref().and_then(|t| t.x_nome.parse_opt())
+    }
+
+    /// Sigla da UF da transportadora.
+    pub fn get_transportadora_estado(&self) -> Option<String> {
+        self.transporta.as_ref().and_then(|t| t.uf.parse_opt())
+    }
+
+    /// Placa do veículo tracionador (`veicTransp`).
+    pub fn get_placa_veiculo(&self) -> Option<String> {
+        self.veic_transp.as_ref().and_then(|v| v.placa.parse_opt())
+    }
+
+    /// Placas dos reboques vinculados ao transporte.
+    pub fn get_placas_reboques(&self) -> Vec<String> {
+        self.reboque
+            .iter()
+            .flatten()
+            .filter_map(|reboque| reboque.placa.parse_opt())
+            .collect()
+    }
+
+    /// Retorna os volumes transportados (`<vol>`), ou uma lista vazia se ausentes.
+    #[inline]
+    pub fn get_volumes(&self) -> &[Vol] {
+        self.vol.as_deref().unwrap_or(&[])
+    }
+
+    /// Soma das quantidades de volumes (`qVol`) declaradas.
+    pub fn get_quantidade_volumes(&self) -> Option<u64> {
+        self.get_volumes()
+            .iter()
+            .filter_map(|vol| vol.get_quantidade())
+            .reduce(|acc, q| acc + q)
+    }
+
+    /// Soma dos pesos líquidos (`pesoL`) dos volumes, em kg.
+    ///
+    /// Retorna `None` se algum volume não declarar o peso, pois a soma parcial o subestimaria.
+    pub fn get_peso_liquido(&self) -> Option<f64> {
+        let volumes = self.get_volumes();
+        if volumes.is_empty() {
+            return None;
+        }
+        volumes
+            .iter()
+            .map(|vol| vol.get_peso_liquido())
+            .sum::<Option<f64>>()
+            .map(|peso| peso.round_float(3))
+    }
+
+    /// Soma dos pesos brutos (`pesoB`) dos volumes, em kg.
+    ///
+    /// Retorna `None` se algum volume não declarar o peso, pois a soma parcial o subestimaria.
+    pub fn get_peso_bruto(&self) -> Option<f64> {
+        let volumes = self.get_volumes();
+        if volumes.is_empty() {
+            return None;
+        }
+        volumes
+            .iter()
+            .map(|vol| vol.get_peso_bruto())
+            .sum::<Option<f64>>()
+            .map(|peso| peso.round_float(3))
+    }
+
+    /// Gera uma linha [`InfoVolume`] por `<vol>`, sem os dados de cabeçalho da NF-e.
+    pub fn get_info_volumes(&self) -> Vec<InfoVolume> {
+        self.get_volumes()
+            .iter()
+            .zip(1..)
+            .map(|(vol, ordem)| InfoVolume {
+                ordem: Some(ordem),
+                quantidade: vol.get_quantidade(),
+                especie: vol.esp.parse_opt(),
+                marca: vol.marca.parse_opt(),
+                numeracao: vol.n_vol.parse_opt(),
+                peso_liquido: vol.get_peso_liquido(),
+                peso_bruto: vol.get_peso_bruto(),
+                lacres: vol
+                    .lacres
+                    .iter()
+                    .flatten()
+                    .filter_map(|lacre| lacre.n_lacre.parse_opt())
+                    .collect(),
+                ..Default::default()
+            })
+            .collect()
+    }
+}
+
+/// Linha da tabela "Volumes": uma por volume transportado (`<vol>`) da NF-e.
+#[derive(Debug, Default, Serialize, Deserialize, Clone, Iterable)]
+pub struct InfoVolume {
+    /// CNPJ do Emitente formatado.
+    #[serde(rename = "CNPJ do Emitente", default)]
+    pub emitente_cnpj: Option<String>,
+
+    /// CPF do Emitente formatado.
+    #[serde(rename = "CPF do Emitente", default)]
+    pub emitente_cpf: Option<String>,
+
+    /// Chave de acesso da NF-e a que o volume pertence.
+    #[serde(rename = "Chave do Documento Fiscal", default)]
+    pub nfe: Option<String>,
+
+    /// Indicador se a NF-e correspondente foi cancelada.
+    #[serde(rename = "Cancelado", default)]
+    pub cancelado: Option<String>,
+
+    /// Número sequencial da Nota Fiscal.
+    #[serde(rename = "Nº do Documento Fiscal", default)]
+    pub numero_da_nota: Option<u32>,
+
+    /// Data de emissão da Nota Fiscal.
+    #[serde(
+        rename = "Data de Emissão",
+        serialize_with = "serialize_option_datetime_to_excel",
+        default
+    )]
+    pub data_emissao: Option<NaiveDate>,
+
+    /// Modalidade do frete (`modFrete`) da NF-e.
+    #[serde(rename = "Modalidade do Frete", default)]
+    pub modalidade_frete: Option<String>,
+
+    /// CNPJ formatado da transportadora.
+    #[serde(rename = "CNPJ da Transportadora", default)]
+    pub transportadora_cnpj: Option<String>,
+
+    /// Posição do `<vol>` dentro do grupo `<transp>`, iniciando em 1.
+    #[serde(rename = "Nº de Ordem do Volume", default)]
+    pub ordem: Option<u32>,
+
+    /// Quantidade de volumes transportados (`qVol`).
+    #[serde(rename = "Quantidade de Volumes", default)]
+    pub quantidade: Option<u64>,
+
+    /// Espécie dos volumes (`esp`).
+    #[serde(rename = "Espécie dos Volumes", default)]
+    pub especie: Option<String>,
+
+    /// Marca dos volumes (`marca`).
+    #[serde(rename = "Marca dos Volumes", default)]
+    pub marca: Option<String>,
+
+    /// Numeração dos volumes (`nVol`).
+    #[serde(rename = "Numeração dos Volumes", default)]
+    pub numeracao: Option<String>,
+
+    /// Peso líquido em kg (`pesoL`).
+    #[serde(rename = "Peso Líquido (kg)", default)]
+    pub peso_liquido: Option<f64>,
+
+    /// Peso bruto em kg (`pesoB`).
+    #[serde(rename = "Peso Bruto (kg)", default)]
+    pub peso_bruto: Option<f64>,
+
+    /// Números dos lacres aplicados (`nLacre`).
+    #[serde(rename = "Lacres", serialize_with = "serialize_vec_string", default)]
+    pub lacres: Vec<String>,
+}
+
+impl InfoVolume {
+    /// Determina se o documento é válido (chave presente e sem marcação de cancelamento).
+    pub fn is_valid(&self) -> bool {
+        self.nfe.is_some() && self.cancelado.is_none()
+    }
+}
+
+impl InfoExtension for InfoVolume {}
+
+impl GetKey for InfoVolume {
+    fn get_chave(&self) -> Option<String> {
+        self.nfe.clone()
+    }
+}
+
+impl GetID<Option<(String, u32)>> for InfoVolume {
+    /// id: (chave, nº de ordem do volume)
+    fn get_id(&self) -> Option<(String, u32)> {
+        if let (Some(nfe), Some(ordem)) = (&self.nfe, self.ordem) {
+            Some((nfe.clone(), ordem))
+        } else {
+            None
+        }
+    }
+}
+
 /// Dados cadastrais de identificação da Transportadora (`<transporta>`).
 #[derive(Debug, Serialize, Deserialize, Clone)]
 pub struct Transporta {
@@ -763,6 +986,18 @@ pub struct Transporta {
     pub text: Option<String>,
 }
 
+impl Transporta {
+    /// CNPJ da transportadora formatado com a máscara padrão.
+    pub fn get_cnpj(&self) -> Option<String> {
+        self.cnpj.as_ref().map(|c| c.trim().format_cnpj())
+    }
+
+    /// CPF do transportador formatado com a máscara padrão.
+    pub fn get_cpf(&self) -> Option<String> {
+        self.cpf.as_ref().map(|c| c.trim().format_cpf())
+    }
+}
+
 /// Informações relativas a Retenção de ICMS de Transporte (`<retTransp>`).
 #[derive(Debug, Serialize, Deserialize, Clone)]
 pub struct RetTransp {
@@ -871,6 +1106,23 @@ pub struct Vol {
     pub lacres: Option<Vec<Lacres>>,
 }
 
+impl Vol {
+    /// Quantidade de volumes (`qVol`) como inteiro.
+    pub fn get_quantidade(&self) -> Option<u64> {
+        self.q_vol.parse_opt()
+    }
+
+    /// Peso líquido (`pesoL`) em kg.
+    pub fn get_peso_liquido(&self) -> Option<f64> {
+        self.peso_l.parse_opt()
+    }
+
+    /// Peso bruto (`pesoB`) em kg.
+    pub fn get_peso_bruto(&self) -> Option<f64> {
+        self.peso_b.parse_opt()
+    }
+}
+
 /// Dados de Lacres de segurança utilizados nos volumes (`<lacres>`).
 #[derive(Debug, Serialize, Deserialize, Clone)]
 pub struct Lacres {
@@ -0,0 +1,667 @@
+//! # Visão de Cabeçalho da NF-e
+//!
+//! Enquanto [`InfoNfe`](crate::xml_structs::nfe::InfoNfe) gera uma linha por item,
+//! repetindo o cabeçalho e o `valor_total_nfe` em cada uma delas, este módulo
+//! consolida **uma única linha por NF-e**: identificação (`<ide>`), todos os totais
+//! de `<ICMSTot>`, dados de transporte (modalidade do frete, transportadora, veículo
+//! e volumes), resumo dos pagamentos e os totais dos CT-es correlacionados.
+//!
+//! Somas realizadas sobre esta tabela não sofrem a multiplicação de valores
+//! que ocorre ao somar colunas de cabeçalho na tabela de itens.
+
+use chrono::NaiveDate;
+use claudiofsr_lib::RoundFloat;
+use rust_xlsxwriter::serialize_option_datetime_to_excel;
+use serde::{Deserialize, Serialize};
+use std::collections::BTreeSet;
+use struct_iterable::Iterable;
+
+use crate::{
+    GetID, GetKey, InfoExtension, KeysExtension, OptExt, serialize_vec_string,
+    xml_structs::{impostos::IcmsTot, nfe::NfeProc},
+};
+
+/// Linha consolidada com os dados de cabeçalho de uma NF-e.
+#[derive(Debug, Default, Serialize, Deserialize, Clone, Iterable)]
+pub struct InfoNfeDocumento {
+    /// CNPJ do Emitente formatado.
+    #[serde(rename = "CNPJ do Emitente", default)]
+    pub emitente_cnpj: Option<String>,
+
+    /// CPF do Emitente formatado.
+    #[serde(rename = "CPF do Emitente", default)]
+    pub emitente_cpf: Option<String>,
+
+    /// Nome ou Razão Social do Emitente.
+    #[serde(rename = "Nome ou Razão Social do Emitente", default)]
+    pub emitente_nome: Option<String>,
+
+    /// Sigla do Estado (UF) do Emitente.
+    #[serde(rename = "Estado do Emitente", default)]
+    emitente_ender_estado: Option<String>,
+
+    /// CNPJ do Destinatário formatado.
+    #[serde(rename = "CNPJ do Destinatário", default)]
+    pub destinatario_cnpj: Option<String>,
+
+    /// CPF do Destinatário formatado.
+    #[serde(rename = "CPF do Destinatário", default)]
+    pub destinatario_cpf: Option<String>,
+
+    /// Nome ou Razão Social do Destinatário.
+    #[serde(rename = "Nome ou Razão Social do Destinatário", default)]
+    destinatario_nome: Option<String>,
+
+    /// Sigla do Estado (UF) do Destinatário.
+    #[serde(rename = "Estado do Destinatário", default)]
+    destinatario_ender_estado: Option<String>,
+
+    /// Chave de acesso única do documento fiscal contendo 44 dígitos.
+    #[serde(rename = "Chave do Documento Fiscal", default)]
+    pub nfe: Option<String>,
+
+    /// Registro indicador da origem do documento (p. ex., "NFe").
+    #[serde(rename = "Registro de Origem")]
+    doc_tipo: String,
+
+    /// Indicador se o documento fiscal correspondente foi cancelado.
+    #[serde(rename = "Cancelado", default)]
+    pub cancelado: Option<String>,
+
+    /// Data e hora da autorização de uso da NF-e (`dhRecbto` do protocolo).
+    #[serde(rename = "Data e Hora da Autorização", default)]
+    pub dh_autorizacao: Option<String>,
+
+    /// Data e hora do cancelamento (`dhEvento` do evento ou `dhRecbto` do `procCanc`).
+    #[serde(rename = "Data e Hora do Cancelamento", default)]
+    pub dh_cancelamento: Option<String>,
+
+    /// Protocolo de homologação do cancelamento pela SEFAZ (`nProt`).
+    #[serde(rename = "Protocolo do Cancelamento", default)]
+    pub protocolo_cancelamento: Option<String>,
+
+    /// Justificativa do cancelamento (`xJust`).
+    #[serde(rename = "Justificativa do Cancelamento", default)]
+    pub justificativa_cancelamento: Option<String>,
+
+    /// Evento de cancelamento (`Evento 110111` etc.) ou arquivo `procCancNFe`.
+    #[serde(rename = "Origem do Cancelamento", default)]
+    pub origem_cancelamento: Option<String>,
+
+    /// Cancelamento fora do prazo de 24 horas da autorização.
+    #[serde(rename = "Alerta do Cancelamento", default)]
+    pub alerta_cancelamento: Option<String>,
+
+    /// Resumo do evento mais recente da NF-e (ver tabela "Eventos").
+    #[serde(rename = "Último Evento", default)]
+    pub ultimo_evento: Option<String>,
+
+    /// Estado final da manifestação do destinatário: `código - descrição`.
+    #[serde(rename = "Manifestação do Destinatário", default)]
+    pub manifestacao: Option<String>,
+
+    /// Data e hora do evento que define a manifestação.
+    #[serde(rename = "Data e Hora da Manifestação", default)]
+    pub dh_manifestacao: Option<String>,
+
+    /// Data e hora da primeira Ciência da Operação (210210).
+    #[serde(rename = "Data e Hora da Ciência da Operação", default)]
+    pub dh_ciencia: Option<String>,
+
+    /// Operação não realizada pelo destinatário, mas correlacionada a CTes.
+    #[serde(rename = "Alerta da Manifestação", default)]
+    pub alerta_manifestacao: Option<String>,
+
+    /// Data e hora da entrega, pelo Comprovante de Entrega mais recente não cancelado.
+    #[serde(rename = "Entregue em", default)]
+    pub entregue_em: Option<String>,
+
+    /// Dias decorridos entre a emissão da NFe e a entrega.
+    #[serde(rename = "Dias entre Emissão e Entrega", default)]
+    pub dias_ate_entrega: Option<i64>,
+
+    /// Documento de identificação de quem recebeu a carga (`nDoc`).
+    #[serde(rename = "Documento de Quem Recebeu a Entrega", default)]
+    pub entrega_documento: Option<String>,
+
+    /// Nome de quem recebeu a carga (`xNome`).
+    #[serde(rename = "Nome de Quem Recebeu a Entrega", default)]
+    pub entrega_nome: Option<String>,
+
+    /// Latitude do ponto de entrega, pelo mesmo Comprovante de Entrega.
+    #[serde(rename = "Latitude da Entrega", default)]
+    pub entrega_latitude: Option<String>,
+
+    /// Longitude do ponto de entrega, pelo mesmo Comprovante de Entrega.
+    #[serde(rename = "Longitude da Entrega", default)]
+    pub entrega_longitude: Option<String>,
+
+    /// Último insucesso na entrega não cancelado: `data: motivo, tentativa`.
+    #[serde(rename = "Insucesso na Entrega", default)]
+    pub insucesso_entrega: Option<String>,
+
+    /// Modelo do documento fiscal (`mod`).
+    #[serde(rename = "Modelo do Documento Fiscal", default)]
+    modelo: Option<String>,
+
+    /// Série do documento fiscal (`serie`).
+    #[serde(rename = "Série do Documento Fiscal", default)]
+    pub serie: Option<String>,
+
+    /// Número sequencial da Nota Fiscal.
+    #[serde(rename = "Nº do Documento Fiscal", default)]
+    pub numero_da_nota: Option<u32>,
+
+    /// Data de emissão da Nota Fiscal.
+    #[serde(
+        rename = "Data de Emissão",
+        serialize_with = "serialize_option_datetime_to_excel",
+        default
+    )]
+    pub data_emissao: Option<NaiveDate>,
+
+    /// Data em que ocorreu a saída ou a entrega da mercadoria.
+    #[serde(
+        rename = "Data de Saída / Entrega",
+        serialize_with = "serialize_option_datetime_to_excel",
+        default
+    )]
+    data_saida: Option<NaiveDate>,
+
+    /// Tipo de operação (`tpNF`): Entrada ou Saída.
+    #[serde(rename = "Tipo de Operação", default)]
+    pub tipo_operacao: Option<String>,
+
+    /// Papel da empresa auditada (`--cnpj-base`) na NF-e: emitente e/ou destinatário.
+    #[serde(rename = "Papel da Empresa Auditada", default)]
+    pub papel_empresa: Option<String>,
+
+    /// Direção da operação do ponto de vista da empresa auditada.
+    #[serde(rename = "Entrada ou Saída da Empresa Auditada", default)]
+    pub direcao: Option<String>,
+
+    /// Finalidade de emissão (`finNFe`): normal, complementar, ajuste ou devolução.
+    #[serde(rename = "Finalidade de Emissão", default)]
+    pub finalidade: Option<String>,
+
+    /// Natureza da operação (`natOp`) declarada pelo emitente.
+    #[serde(rename = "Natureza da Operação", default)]
+    natureza_operacao: Option<String>,
+
+    /// Local de destino da operação (`idDest`).
+    #[serde(rename = "Destino da Operação", default)]
+    destino_operacao: Option<String>,
+
+    /// Operação com consumidor final (`indFinal`).
+    #[serde(rename = "Indicador de Consumidor Final", default)]
+    consumidor_final: Option<String>,
+
+    /// Presença do comprador no estabelecimento (`indPres`).
+    #[serde(rename = "Indicador de Presença do Comprador", default)]
+    presenca_comprador: Option<String>,
+
+    /// Quantidade de itens contidos na Nota Fiscal.
+    #[serde(rename = "Nº de Itens")]
+    numero_de_itens: usize,
+
+    /// Modalidade do frete (`modFrete`).
+    #[serde(rename = "Modalidade do Frete", default)]
+    modalidade_frete: Option<String>,
+
+    /// CNPJ da transportadora (`transporta/CNPJ`).
+    #[serde(rename = "CNPJ da Transportadora", default)]
+    pub transportadora_cnpj: Option<String>,
+
+    /// CPF do transportador autônomo (`transporta/CPF`).
+    #[serde(rename = "CPF da Transportadora", default)]
+    pub transportadora_cpf: Option<String>,
+
+    /// Razão social ou nome da transportadora.
+    #[serde(rename = "Nome ou Razão Social da Transportadora", default)]
+    transportadora_nome: Option<String>,
+
+    /// Sigla do Estado (UF) da transportadora.
+    #[serde(rename = "Estado da Transportadora", default)]
+    transportadora_estado: Option<String>,
+
+    /// Placa do veículo tracionador (`veicTransp/placa`).
+    #[serde(rename = "Placa do Veículo", default)]
+    placa_veiculo: Option<String>,
+
+    /// Placas dos reboques (`reboque/placa`).
+    #[serde(
+        rename = "Placas dos Reboques",
+        serialize_with = "serialize_vec_string",
+        default
+    )]
+    placas_reboques: Vec<String>,
+
+    /// Soma das quantidades de volumes (`qVol`).
+    #[serde(rename = "Quantidade de Volumes", default)]
+    quantidade_volumes: Option<u64>,
+
+    /// Soma dos pesos líquidos dos volumes (`pesoL`).
+    #[serde(rename = "Peso Líquido (kg)", default)]
+    pub peso_liquido: Option<f64>,
+
+    /// Soma dos pesos brutos dos volumes (`pesoB`).
+    #[serde(rename = "Peso Bruto (kg)", default)]
+    pub peso_bruto: Option<f64>,
+
+    /// Meios de pagamento distintos informados em `<detPag>`.
+    #[serde(
+        rename = "Formas de Pagamento",
+        serialize_with = "serialize_vec_string",
+        default
+    )]
+    formas_pagamento: Vec<String>,
+
+    /// Soma dos valores pagos (`vPag`) em todas as formas de pagamento.
+    #[serde(rename = "Valor Total Pago", default)]
+    valor_total_pago: Option<f64>,
+
+    /// Valor do troco devolvido (`vTroco`).
+    #[serde(rename = "Valor do Troco", default)]
+    valor_troco: Option<f64>,
+
+    /// Identificador da cadeia de frete dos CT-es que transportam a NF-e.
+    #[serde(rename = "Cadeia de Frete", default)]
+    pub cadeia: Option<String>,
+
+    /// Chaves de CT-e relacionados a este documento fiscal.
+    #[serde(
+        rename = "Informações de CTes relacionados",
+        serialize_with = "serialize_vec_string",
+        default
+    )]
+    pub ctes: Vec<String>,
+
+    /// Detalhes de tomadores vinculados aos CT-es relacionados.
+    #[serde(
+        rename = "(CNPJ_CPF e Atributo) dos Tomadores dos CTes com Valores e Porcentagens decrescentes",
+        serialize_with = "serialize_vec_string",
+        default
+    )]
+    pub tomadores: Vec<String>,
+
+    /// Valor consolidado dos fretes de CT-es correlacionados.
+    #[serde(rename = "Valor Total de CTes", default)]
+    pub valor_total_ctes: Option<f64>,
+
+    // =========================================================================
+    // Referências entre NF-es (`NFref`)
+    // =========================================================================
+    /// Chaves das NF-es referenciadas por esta NF-e (`refNFe`).
+    #[serde(
+        rename = "NFes Referenciadas",
+        serialize_with = "serialize_vec_string",
+        default
+    )]
+    pub nfes_referenciadas: Vec<String>,
+
+    /// Notas em papel, cupons fiscais e CT-es referenciados (`refNF`, `refNFP`, `refECF`, `refCTe`).
+    #[serde(
+        rename = "Outros Documentos Referenciados",
+        serialize_with = "serialize_vec_string",
+        default
+    )]
+    pub outros_documentos_referenciados: Vec<String>,
+
+    /// Chaves das NF-es válidas que referenciam esta NF-e.
+    #[serde(
+        rename = "Referenciada pelas NFes",
+        serialize_with = "serialize_vec_string",
+        default
+    )]
+    pub referenciada_por: Vec<String>,
+
+    /// Valor da NF-e menos o valor das devoluções (`finNFe = 4`) que referenciam apenas esta NF-e.
+    #[serde(rename = "Valor Líquido após Devoluções", default)]
+    pub valor_liquido: Option<f64>,
+
+    /// Devoluções que referenciam mais de uma NF-e original: o valor não é abatido por ser ambíguo.
+    #[serde(rename = "Alerta do Valor Líquido", default)]
+    pub alerta_valor_liquido: Option<String>,
+
+    /// Em devoluções: CT-es correlacionados às NF-es originais, para o estorno do crédito sobre o frete.
+    #[serde(
+        rename = "CTes da NFe Original (Devolução)",
+        serialize_with = "serialize_vec_string",
+        default
+    )]
+    pub ctes_nfe_original: Vec<String>,
+
+    // =========================================================================
+    // Totais de ICMSTot
+    // =========================================================================
+    /// Base de cálculo global do ICMS (`vBC`).
+    #[serde(rename = "Valor Total da Base de Cálculo do ICMS", default)]
+    v_bc: Option<f64>,
+
+    /// Valor total do ICMS (`vICMS`).
+    #[serde(rename = "Valor Total do ICMS", default)]
+    v_icms: Option<f64>,
+
+    /// Valor total do ICMS desonerado (`vICMSDeson`).
+    #[serde(rename = "Valor Total do ICMS Desonerado", default)]
+    v_icmsdeson: Option<f64>,
+
+    /// Valor total do FCP devido na UF de destino (`vFCPUFDest`).
+    #[serde(rename = "Valor Total do FCP da UF de Destino", default)]
+    v_fcpufdest: Option<f64>,
+
+    /// Valor total do ICMS diferido (`vICMSDif`).
+    #[serde(rename = "Valor Total do ICMS Diferido", default)]
+    v_icms_dif: Option<f64>,
+
+    /// Valor total do ICMS devido na UF de destino (`vICMSUFDest`).
+    #[serde(rename = "Valor Total do ICMS da UF de Destino", default)]
+    v_icmsufdest: Option<f64>,
+
+    /// Valor total do ICMS devido na UF do remetente (`vICMSUFRemet`).
+    #[serde(rename = "Valor Total do ICMS da UF do Remetente", default)]
+    v_icmsufremet: Option<f64>,
+
+    /// Valor total do FCP (`vFCP`).
+    #[serde(rename = "Valor Total do FCP", default)]
+    v_fcp: Option<f64>,
+
+    /// Base de cálculo global do ICMS ST (`vBCST`).
+    #[serde(rename = "Valor Total da Base de Cálculo do ICMS ST", default)]
+    v_bcst: Option<f64>,
+
+    /// Valor total do ICMS ST (`vST`).
+    #[serde(rename = "Valor Total do ICMS ST", default)]
+    v_st: Option<f64>,
+
+    /// Valor total do FCP retido por ST (`vFCPST`).
+    #[serde(rename = "Valor Total do FCP retido por ST", default)]
+    v_fcpst: Option<f64>,
+
+    /// Valor total do FCP retido anteriormente por ST (`vFCPSTRet`).
+    #[serde(rename = "Valor Total do FCP retido anteriormente por ST", default)]
+    v_fcpstret: Option<f64>,
+
+    /// Valor total dos produtos (`vProd`).
+    #[serde(rename = "Valor Total dos Produtos", default)]
+    v_prod: Option<f64>,
+
+    /// Valor total do frete (`vFrete`).
+    #[serde(rename = "Valor Total do Frete", default)]
+    v_frete: Option<f64>,
+
+    /// Valor total do seguro (`vSeg`).
+    #[serde(rename = "Valor Total do Seguro", default)]
+    v_seg: Option<f64>,
+
+    /// Valor total do desconto (`vDesc`).
+    #[serde(rename = "Valor Total do Desconto", default)]
+    v_desc: Option<f64>,
+
+    /// Valor total do Imposto de Importação (`vII`).
+    #[serde(rename = "Valor Total do II", default)]
+    v_ii: Option<f64>,
+
+    /// Valor total do IPI (`vIPI`).
+    #[serde(rename = "Valor Total do IPI", default)]
+    v_ipi: Option<f64>,
+
+    /// Valor total do IPI devolvido (`vIPIDevol`).
+    #[serde(rename = "Valor Total do IPI Devolvido", default)]
+    v_ipidevol: Option<f64>,
+
+    /// Valor total do PIS (`vPIS`).
+    #[serde(rename = "Valor Total de PIS/PASEP", default)]
+    v_pis: Option<f64>,
+
+    /// Valor total da COFINS (`vCOFINS`).
+    #[serde(rename = "Valor Total de COFINS", default)]
+    v_cofins: Option<f64>,
+
+    /// Valor total de outras despesas acessórias (`vOutro`).
+    #[serde(rename = "Valor Total de Outras Despesas Acessórias", default)]
+    v_outro: Option<f64>,
+
+    /// Valor Total da Nota Fiscal Eletrônica (`vNF`).
+    #[serde(rename = "Valor Total da NFe", default)]
+    pub valor_total_nfe: Option<f64>,
+
+    /// Valor aproximado total de tributos (`vTotTrib`).
+    #[serde(rename = "Valor Aproximado Total de Tributos", default)]
+    v_tot_trib: Option<f64>,
+
+    /// Quantidade tributada do ICMS monofásico próprio (`qBCMono`).
+    #[serde(rename = "Quantidade Tributada do ICMS Monofásico Próprio", default)]
+    q_bc_mono: Option<f64>,
+
+    /// Valor total do ICMS monofásico próprio (`vICMSMono`).
+    #[serde(rename = "Valor Total do ICMS Monofásico Próprio", default)]
+    v_icms_mono: Option<f64>,
+
+    /// Quantidade tributada do ICMS monofásico sujeito a retenção (`qBCMonoReten`).
+    #[serde(
+        rename = "Quantidade Tributada do ICMS Monofásico sujeito a Retenção",
+        default
+    )]
+    q_bc_mono_reten: Option<f64>,
+
+    /// Valor total do ICMS monofásico sujeito a retenção (`vICMSMonoReten`).
+    #[serde(rename = "Valor Total do ICMS Monofásico sujeito a Retenção", default)]
+    v_icms_mono_reten: Option<f64>,
+
+    /// Quantidade tributada do ICMS monofásico retido anteriormente (`qBCMonoRet`).
+    #[serde(
+        rename = "Quantidade Tributada do ICMS Monofásico Retido Anteriormente",
+        default
+    )]
+    q_bc_mono_ret: Option<f64>,
+
+    /// Valor total do ICMS monofásico retido anteriormente (`vICMSMonoRet`).
+    #[serde(
+        rename = "Valor Total do ICMS Monofásico Retido Anteriormente",
+        default
+    )]
+    v_icms_mono_ret: Option<f64>,
+}
+
+impl InfoNfeDocumento {
+    /// Determina se o documento é válido (chave presente e sem marcação de cancelamento).
+    pub fn is_valid(&self) -> bool {
+        self.nfe.is_some() && self.cancelado.is_none()
+    }
+
+    /// Operação com consumidor final (`indFinal = 1`).
+    pub fn is_consumidor_final(&self) -> bool {
+        self.consumidor_final
+            .as_deref()
+            .is_some_and(|indicador| indicador.starts_with('1'))
+    }
+
+    /// Devolução de mercadoria (`finNFe = 4`).
+    pub fn is_devolucao(&self) -> bool {
+        self.finalidade
+            .as_deref()
+            .is_some_and(|finalidade| finalidade.starts_with('4'))
+    }
+
+    /// Preenche as colunas de totais a partir do grupo `<ICMSTot>`.
+    fn set_totais_icms(&mut self, icmstot: &IcmsTot) {
+        self.v_bc = icmstot.v_bc.to_float64();
+        self.v_icms = icmstot.v_icms.to_float64();
+        self.v_icmsdeson = icmstot.v_icmsdeson.to_float64();
+        self.v_fcpufdest = icmstot.v_fcpufdest.to_float64();
+        self.v_icms_dif = icmstot.v_icms_dif.to_float64();
+        self.v_icmsufdest = icmstot.v_icmsufdest.to_float64();
+        self.v_icmsufremet = icmstot.v_icmsufremet.to_float64();
+        self.v_fcp = icmstot.v_fcp.to_float64();
+        self.v_bcst = icmstot.v_bcst.to_float64();
+        self.v_st = icmstot.v_st.to_float64();
+        self.v_fcpst = icmstot.v_fcpst.to_float64();
+        self.v_fcpstret = icmstot.v_fcpstret.to_float64();
+        self.v_prod = icmstot.v_prod.to_float64();
+        self.v_frete = icmstot.v_frete.to_float64();
+        self.v_seg = icmstot.v_seg.to_float64();
+        self.v_desc = icmstot.v_desc.to_float64();
+        self.v_ii = icmstot.v_ii.to_float64();
+        self.v_ipi = icmstot.v_ipi.to_float64();
+        self.v_ipidevol = icmstot.v_ipidevol.to_float64();
+        self.v_pis = icmstot.v_pis.to_float64();
+        self.v_cofins = icmstot.v_cofins.to_float64();
+        self.v_outro = icmstot.v_outro.to_float64();
+        self.valor_total_nfe = icmstot.v_nf.to_float64();
+        self.v_tot_trib = icmstot.v_tot_trib.to_float64();
+        self.q_bc_mono = icmstot.q_bc_mono.to_float64();
+        self.v_icms_mono = icmstot.v_icms_mono.to_float64();
+        self.q_bc_mono_reten = icmstot.q_bc_mono_reten.to_float64();
+        self.v_icms_mono_reten = icmstot.v_icms_mono_reten.to_float64();
+        self.q_bc_mono_ret = icmstot.q_bc_mono_ret.to_float64();
+        self.v_icms_mono_ret = icmstot.v_icms_mono_ret.to_float64();
+    }
+}
+
+impl KeysExtension for [InfoNfeDocumento] {
+    /// Extrai e consolida as chaves de acesso únicas das notas fiscais.
+    fn get_chaves(&self) -> BTreeSet<String> {
+        self.iter().flat_map(|info| info.nfe.clone()).collect()
+    }
+}
+
+impl InfoExtension for InfoNfeDocumento {}
+
+impl GetKey for InfoNfeDocumento {
+    fn get_chave(&self) -> Option<String> {
+        self.nfe.clone()
+    }
+}
+
+impl GetID<Option<String>> for InfoNfeDocumento {
+    /// Cada NF-e gera uma única linha, assim id: chave.
+    fn get_id(&self) -> Option<String> {
+        self.nfe.clone()
+    }
+}
+
+impl NfeProc {
+    /// Consolida os dados de cabeçalho da NF-e em uma única linha [`InfoNfeDocumento`].
+    ///
+    /// As colunas de CT-es correlacionados são preenchidas posteriormente,
+    /// após a resolução do grafo de correlações.
+    pub fn get_documento(&self) -> InfoNfeDocumento {
+        let mut documento = InfoNfeDocumento {
+            emitente_cnpj: self.get_emitente_cnpj(),
+            emitente_cpf: self.get_emitente_cpf(),
+            emitente_nome: self.get_emitente_nome(),
+            emitente_ender_estado: self.get_emitente_ender_estado(),
+            destinatario_cnpj: self.get_destinatario_cnpj(),
+            destinatario_cpf: self.get_destinatario_cpf(),
+            destinatario_nome: self.get_destinatario_nome(),
+            destinatario_ender_estado: self.get_destinatario_ender_estado(),
+            nfe: self.get_nfe(),
+            doc_tipo: "NFe".to_string(),
+            dh_autorizacao: self.prot_nfe.inf_prot.dh_recbto.get_not_empty(),
+            numero_da_nota: self.get_numero_da_nota(),
+            data_emissao: self.get_data_emissao(),
+            data_saida: self.get_data_saida(),
+            ..Default::default()
+        };
+
+        let Some(inf_nfe) = self.nfe.inf_nfe.as_ref() else {
+            return documento;
+        };
+
+        let ide = &inf_nfe.ide;
+        documento.modelo = ide.get_modelo();
+        documento.serie = ide.get_serie();
+        documento.tipo_operacao = ide.get_tipo_operacao();
+        documento.finalidade = ide.get_finalidade();
+        documento.natureza_operacao = ide.get_natureza_operacao();
+        documento.destino_operacao = ide.get_destino_operacao();
+        documento.consumidor_final = ide.get_consumidor_final();
+        documento.presenca_comprador = ide.get_presenca_comprador();
+        documento.nfes_referenciadas = ide.get_nfes_referenciadas();
+        documento.outros_documentos_referenciados = ide.get_outros_documentos_referenciados();
+
+        documento.numero_de_itens = inf_nfe.det.len();
+
+        let transp = &inf_nfe.transp;
+        documento.modalidade_frete = transp.get_modalidade_frete();
+        documento.transportadora_cnpj = transp.get_transportadora_cnpj();
+        documento.transportadora_cpf = transp.get_transportadora_cpf();
+        documento.transportadora_nome = transp.get_transportadora_nome();
+        documento.transportadora_estado = transp.get_transportadora_estado();
+        documento.placa_veiculo = transp.get_placa_veiculo();
+        documento.placas_reboques = transp.get_placas_reboques();
+        documento.quantidade_volumes = transp.get_quantidade_volumes();
+        documento.peso_liquido = transp.get_peso_liquido();
+        documento.peso_bruto = transp.get_peso_bruto();
+
+        if let Some(pag) = inf_nfe.pag.as_ref() {
+            documento.formas_pagamento = pag.formas_de_pagamento();
+            documento.valor_total_pago =
+                (!pag.det_pag.is_empty()).then(|| pag.valor_total_pago().round_float(2));
+            documento.valor_troco = pag.troco();
+        }
+
+        if let Some(icmstot) = inf_nfe.total.icmstot.as_ref() {
+            documento.set_totais_icms(icmstot);
+        }
+
+        documento
+    }
+}
+
+//----------------------------------------------------------------------------//
+//                                   Tests                                    //
+//----------------------------------------------------------------------------//
+
+/// Run tests with:
+/// cargo test -- --show-output tests_nfe_documento
+#[cfg(test)]
+mod tests_nfe_documento {
+    use super::*;
+    use crate::{StructExtension, XmlParserResult};
+    use std::path::Path;
+
+    #[test]
+    /// `cargo test -- --show-output documento_de_nfe_com_cabecalho`
+    fn documento_de_nfe_com_cabecalho() -> XmlParserResult<()> {
+        let path = Path::new("35250199999999999999550000000000021001034139_NFe.xml");
+        let nfe_proc = NfeProc::xml_parse(path)?;
+
+        let documento = nfe_proc.get_documento();
+        println!("documento: {documento:#?}");
+
+        assert_eq!(documento.nfe, nfe_proc.get_nfe());
+        assert_eq!(documento.modelo, Some("55".to_string()));
+        assert_eq!(documento.serie, Some("1".to_string()));
+        assert_eq!(documento.tipo_operacao, Some("1 - Saída".to_string()));
+        assert_eq!(documento.finalidade, Some("1 - NF-e normal".to_string()));
+        assert_eq!(
+            documento.destino_operacao,
+            Some("1 - Operação interna".to_string())
+        );
+        assert_eq!(
+            documento.consumidor_final,
+            Some("1 - Consumidor final".to_string())
+        );
+        assert_eq!(
+            documento.modalidade_frete,
+            Some("1 - Contratação do Frete por conta do Destinatário (FOB)".to_string())
+        );
+        assert_eq!(
+            documento.formas_pagamento,
+            ["15 - Boleto Bancário".to_string()]
+        );
+        assert_eq!(documento.valor_total_pago, Some(1234.56));
+        assert_eq!(documento.numero_de_itens, 1);
+
+        // Totais de ICMSTot
+        assert_eq!(documento.v_icms, Some(123.45));
+        assert_eq!(documento.v_pis, Some(12.34));
+        assert_eq!(documento.v_cofins, Some(56.78));
+        assert_eq!(documento.valor_total_nfe, Some(1234.56));
+        assert_eq!(documento.q_bc_mono, None);
+
+        Ok(())
+    }
+}
@@ -2,7 +2,7 @@
 //!
 //! Este módulo gerencia o mapeamento, desserialização e consolidação de dados
 //! contidos em documentos XML de eventos associados à Nota Fiscal Eletrônica (NF-e),
-//! tais como solicitações de cancelamento ou correções.
+//! tais como solicitações de cancelamento, correções e comprovantes de entrega.
 //!
 //! Módulo gerado originalmente a partir de múltiplos esquemas XML (arquivos `.xsd`)
 //! utilizando ferramentas de geração de instâncias do Apache XMLBeans (`xsd2inst`)
@@ -47,15 +47,20 @@ use chrono::NaiveDate;
 use serde::{Deserialize, Serialize};
 
 use crate::{
-    Arguments, GetKey, InfoExtension, Information, OptExt, REGEX_CANCELAMENTO, StructExtension,
-    get_naive_date_from_yyyy_mm_dd,
-    xml_structs::{agente::Agente, assinaturas::Signature, ret_evento::RetEvento},
+    Arguments, EventoEntrega, GetKey, InfoExtension, Information, OptExt, REGEX_CANCELAMENTO,
+    StructExtension, descrever_motivo_insucesso, get_naive_date_from_yyyy_mm_dd,
+    xml_structs::{
+        agente::Agente,
+        assinaturas::Signature,
+        ret_evento::{RetEvento, is_situacao_homologada},
+    },
 };
 
 /// Representação intermediária e consolidada de um Evento de NF-e.
 ///
 /// Esta estrutura unifica a identificação da NF-e vinculada, a data de emissão
-/// do evento e o indicador correspondente a cancelamento.
+/// do evento, o indicador correspondente a cancelamento e os dados exibidos na
+/// tabela "Eventos" (ver [`InfoEvento`](crate::InfoEvento)).
 #[derive(Debug, Default, Serialize, Deserialize, Clone)]
 pub struct InfoNfeEvento {
     /// Chave de acesso correspondente à NF-e vinculada.
@@ -64,6 +69,24 @@ pub struct InfoNfeEvento {
     pub dh_emi: Option<NaiveDate>,
     /// Indicador booleano que assinala se o evento corresponde a um cancelamento homologado.
     pub cancelado: bool,
+    /// Código do tipo de evento (`tpEvento`), p. ex. "110110" para a CC-e.
+    pub tp_evento: Option<String>,
+    /// Descrição do evento (`descEvento`).
+    pub desc_evento: Option<String>,
+    /// Número sequencial do evento para a mesma chave (`nSeqEvento`).
+    pub n_seq_evento: Option<u32>,
+    /// Data e hora do evento (`dhEvento`), no formato AAAA-MM-DDTHH:MM:SSTZD.
+    pub dh_evento: Option<String>,
+    /// Protocolo de registro do evento (`retEvento/infEvento/nProt`).
+    pub protocolo: Option<String>,
+    /// Situação do registro: `cStat - xMotivo`.
+    pub situacao: Option<String>,
+    /// Texto do evento: correção, justificativa ou documento vinculado.
+    pub detalhe: Option<String>,
+    /// Texto livre da Carta de Correção (`xCorrecao`), apenas no evento 110110.
+    pub x_correcao: Option<String>,
+    /// Comprovante ou insucesso de entrega e os respectivos cancelamentos.
+    pub entrega: Option<EventoEntrega>,
 }
 
 impl InfoExtension for InfoNfeEvento {}
@@ -75,6 +98,13 @@ impl GetKey for InfoNfeEvento {
     }
 }
 
+impl InfoNfeEvento {
+    /// Verifica se o evento foi registrado pela SEFAZ (`cStat` 135 ou 136).
+    pub fn is_homologado(&self) -> bool {
+        is_situacao_homologada(self.situacao.as_deref())
+    }
+}
+
 /// Representação estrutural direta do XML de validação do processo de Evento da NF-e (`<procEventoNFe>`).
 #[derive(Debug, Serialize, Deserialize)]
 pub struct ProcEventoNfe {
@@ -126,8 +156,16 @@ impl ProcEventoNfe {
     }
 
     /// Determina se o evento em questão corresponde a uma homologação de cancelamento.
+    ///
+    /// Com `tpEvento`, apenas o Cancelamento (110111) e o Cancelamento por Substituição
+    /// (110112) cancelam a NF-e: os cancelamentos de comprovante ou insucesso de entrega,
+    /// p. ex., cancelam apenas o evento referenciado.
     pub fn informacao_de_cancelamento(&self) -> bool {
-        REGEX_CANCELAMENTO.is_match(&self.evento.inf_evento.det_evento.desc_evento)
+        let inf_evento = &self.evento.inf_evento;
+        match inf_evento.tp_evento.get_not_empty() {
+            Some(tp_evento) => matches!(tp_evento.as_str(), "110111" | "110112"),
+            None => REGEX_CANCELAMENTO.is_match(&inf_evento.det_evento.desc_evento),
+        }
     }
 
     /// Obtém a data de emissão associada ao registro deste evento.
@@ -137,10 +175,24 @@ impl ProcEventoNfe {
 
     /// Retorna a estrutura consolidada `InfoNfeEvento` correspondente a este processo.
     pub fn get_info(&self) -> InfoNfeEvento {
+        let inf_evento = &self.evento.inf_evento;
+        let retorno = self.ret_evento.inf_evento.as_ref();
         InfoNfeEvento {
             nfe: self.get_nfe(),
             dh_emi: self.get_data_emissao(),
             cancelado: self.informacao_de_cancelamento(),
+            tp_evento: inf_evento.tp_evento.get_not_empty(),
+            desc_evento: Some(inf_evento.det_evento.desc_evento.as_str()).get_not_empty(),
+            n_seq_evento: inf_evento
+                .n_seq_evento
+                .as_deref()
+                .and_then(|n| n.trim().parse().ok()),
+            dh_evento: inf_evento.dh_evento.clone(),
+            protocolo: retorno.and_then(|r| r.n_prot.clone()),
+            situacao: retorno.and_then(|r| r.get_situacao()),
+            detalhe: inf_evento.det_evento.get_detalhe(),
+            x_correcao: inf_evento.det_evento.x_correcao.get_not_empty(),
+            entrega: inf_evento.det_evento.get_entrega(),
         }
     }
 }
@@ -292,6 +344,63 @@ pub struct DetEvento {
     #[serde(rename = "verAplic", default)]
     pub ver_aplic: Option<String>,
 
+    /// Data e hora da entrega da mercadoria (Comprovante de Entrega).
+    #[serde(rename = "dhEntrega", default)]
+    pub dh_entrega: Option<String>,
+
+    /// Documento de identificação de quem recebeu a mercadoria.
+    #[serde(rename = "nDoc", default)]
+    pub n_doc: Option<String>,
+
+    /// Nome de quem recebeu a mercadoria.
+    #[serde(rename = "xNome", default)]
+    pub x_nome: Option<String>,
+
+    /// Latitude do ponto de entrega ou da tentativa de entrega.
+    #[serde(rename = "latGPS", default)]
+    pub lat_gps: Option<String>,
+
+    /// Longitude do ponto de entrega ou da tentativa de entrega.
+    #[serde(rename = "longGPS", default)]
+    pub long_gps: Option<String>,
+
+    /// Hash (SHA-1, Base64) da imagem do comprovante de entrega.
+    #[serde(rename = "hashComprovante", default)]
+    pub hash_comprovante: Option<String>,
+
+    /// Data e hora de geração do hash do comprovante.
+    #[serde(rename = "dhHashComprovante", default)]
+    pub dh_hash_comprovante: Option<String>,
+
+    /// Data e hora da tentativa de entrega (Insucesso na Entrega).
+    #[serde(rename = "dhTentativaEntrega", default)]
+    pub dh_tentativa_entrega: Option<String>,
+
+    /// Número da tentativa de entrega.
+    #[serde(rename = "nTentativa", default)]
+    pub n_tentativa: Option<String>,
+
+    /// Motivo do insucesso: 1 - Recebedor não Encontrado; 2 - Recusa do Recebedor;
+    /// 3 - Endereço Inexistente; 4 - Outros.
+    #[serde(rename = "tpMotivo", default)]
+    pub tp_motivo: Option<String>,
+
+    /// Justificativa do insucesso, obrigatória para o motivo 4 - Outros.
+    #[serde(rename = "xJustMotivo", default)]
+    pub x_just_motivo: Option<String>,
+
+    /// Hash (SHA-1, Base64) da imagem da tentativa de entrega.
+    #[serde(rename = "hashTentativaEntrega", default)]
+    pub hash_tentativa_entrega: Option<String>,
+
+    /// Data e hora de geração do hash da tentativa.
+    #[serde(rename = "dhHashTentativaEntrega", default)]
+    pub dh_hash_tentativa_entrega: Option<String>,
+
+    /// Protocolo do evento cancelado (comprovante ou insucesso de entrega).
+    #[serde(rename = "nProtEvento", default)]
+    pub n_prot_evento: Option<String>,
+
     // =========================================================================
     // 3. CAMPOS ESTREITAMENTE OBRIGATÓRIOS (Sem default, posicionados na base)
     // =========================================================================
@@ -300,6 +409,61 @@ pub struct DetEvento {
     pub desc_evento: String,
 }
 
+impl DetEvento {
+    /// Texto do evento para a tabela "Eventos".
+    ///
+    /// CC-e: `xCorrecao`; cancelamento e manifestações: `xJust`; insucesso na entrega:
+    /// `xJustMotivo`; eventos de CT-e ou MDF-e autorizados para a NF-e: chave do
+    /// documento vinculado.
+    pub fn get_detalhe(&self) -> Option<String> {
+        let ctes = self.cte.iter().filter_map(|cte| cte.ch_cte.get_key());
+        let mdfes = self.mdfe.iter().filter_map(|mdfe| mdfe.ch_mdfe.get_key());
+        let partes: Vec<String> = [&self.x_correcao, &self.x_just, &self.x_just_motivo]
+            .into_iter()
+            .filter_map(|texto| texto.get_not_empty())
+            .chain(ctes.map(|chave| format!("CTe {chave}")))
+            .chain(mdfes.map(|chave| format!("MDFe {chave}")))
+            .collect();
+
+        (!partes.is_empty()).then(|| partes.join("; "))
+    }
+
+    /// Dados do comprovante ou do insucesso de entrega, ou o protocolo cancelado.
+    ///
+    /// O grupo de detalhes da NF-e não é aninhado por tipo de evento: o comprovante
+    /// traz `dhEntrega`, o insucesso `dhTentativaEntrega` e os cancelamentos `nProtEvento`.
+    pub fn get_entrega(&self) -> Option<EventoEntrega> {
+        if let Some(dh_entrega) = self.dh_entrega.get_not_empty() {
+            return Some(EventoEntrega {
+                dh_entrega: Some(dh_entrega),
+                documento: self.n_doc.get_not_empty(),
+                nome: self.x_nome.get_not_empty(),
+                latitude: self.lat_gps.get_not_empty(),
+                longitude: self.long_gps.get_not_empty(),
+                ..Default::default()
+            });
+        }
+
+        if let Some(dh_tentativa) = self.dh_tentativa_entrega.get_not_empty() {
+            return Some(EventoEntrega {
+                dh_entrega: Some(dh_tentativa),
+                latitude: self.lat_gps.get_not_empty(),
+                longitude: self.long_gps.get_not_empty(),
+                n_tentativa: self.n_tentativa.get_not_empty(),
+                motivo: descrever_motivo_insucesso(&self.tp_motivo, &self.x_just_motivo),
+                ..Default::default()
+            });
+        }
+
+        self.n_prot_evento
+            .get_not_empty()
+            .map(|protocolo| EventoEntrega {
+                protocolo_cancelado: Some(protocolo),
+                ..Default::default()
+            })
+    }
+}
+
 /// Dados correspondentes a Conhecimento de Transporte vinculado ao Evento (`<CTe>`).
 #[derive(Debug, Serialize, Deserialize)]
 pub struct Cte {
@@ -7,7 +7,197 @@
 //! pagamentos à vista, troco devolvido e detalhes de transações com cartões
 //! de crédito ou débito (se aplicável).
 
+use chrono::NaiveDate;
+use rust_xlsxwriter::serialize_option_datetime_to_excel;
 use serde::{Deserialize, Serialize};
+use struct_iterable::Iterable;
+
+use crate::{GetID, GetKey, InfoExtension, descrever_codigo, get_naive_date_from_yyyy_mm_dd};
+
+/// Indicador da forma de pagamento (`indPag`).
+const INDICADOR_DE_PAGAMENTO: [(&str, &str); 2] =
+    [("0", "Pagamento à Vista"), ("1", "Pagamento a Prazo")];
+
+/// Tipo de integração do pagamento com o sistema de automação (`tpIntegra`).
+const TIPO_DE_INTEGRACAO: [(&str, &str); 2] = [
+    ("1", "Pagamento integrado (TEF)"),
+    ("2", "Pagamento não integrado (POS)"),
+];
+
+/// Bandeiras de cartão (`tBand`) conforme tabela da SEFAZ.
+const BANDEIRAS_DE_CARTAO: [(&str, &str); 28] = [
+    ("01", "Visa"),
+    ("02", "Mastercard"),
+    ("03", "American Express"),
+    ("04", "Sorocred"),
+    ("05", "Diners Club"),
+    ("06", "Elo"),
+    ("07", "Hipercard"),
+    ("08", "Aura"),
+    ("09", "Cabal"),
+    ("10", "Alelo"),
+    ("11", "Banes Card"),
+    ("12", "CalCard"),
+    ("13", "Credz"),
+    ("14", "Discover"),
+    ("15", "GoodCard"),
+    ("16", "GreenCard"),
+    ("17", "Hiper"),
+    ("18", "JCB"),
+    ("19", "Mais"),
+    ("20", "MaxVan"),
+    ("21", "Policard"),
+    ("22", "RedeCompras"),
+    ("23", "Sodexo"),
+    ("24", "ValeCard"),
+    ("25", "Verocheque"),
+    ("26", "VR"),
+    ("27", "Ticket"),
+    ("99", "Outros"),
+];
+
+/// Meios de pagamento (`tPag`) conforme tabela da SEFAZ.
+const MEIOS_DE_PAGAMENTO: [(&str, &str); 19] = [
+    ("01", "Dinheiro"),
+    ("02", "Cheque"),
+    ("03", "Cartão de Crédito"),
+    ("04", "Cartão de Débito"),
+    ("05", "Crédito Loja"),
+    ("10", "Vale Alimentação"),
+    ("11", "Vale Refeição"),
+    ("12", "Vale Presente"),
+    ("13", "Vale Combustível"),
+    ("15", "Boleto Bancário"),
+    ("16", "Depósito Bancário"),
+    ("17", "Pagamento Instantâneo (PIX) - Dinâmico"),
+    ("18", "Transferência bancária, Carteira Digital"),
+    ("19", "Programa de fidelidade, Cashback, Crédito Virtual"),
+    ("20", "Pagamento Instantâneo (PIX) - Estático"),
+    ("21", "Crédito em Loja"),
+    (
+        "22",
+        "Pagamento Eletrônico não Informado - falha de hardware do sistema emissor",
+    ),
+    ("90", "Sem pagamento"),
+    ("99", "Outros"),
+];
+
+/// Linha da tabela "Pagamentos": uma por forma de pagamento (`<detPag>`) da NF-e.
+///
+/// Os dados de cabeçalho (emitente, destinatário, chave e data) são repetidos em
+/// cada linha para permitir a conciliação direta com extratos bancários.
+#[derive(Debug, Default, Serialize, Deserialize, Clone, Iterable)]
+pub struct InfoPagamento {
+    /// CNPJ do Emitente formatado.
+    #[serde(rename = "CNPJ do Emitente", default)]
+    pub emitente_cnpj: Option<String>,
+
+    /// CPF do Emitente formatado.
+    #[serde(rename = "CPF do Emitente", default)]
+    pub emitente_cpf: Option<String>,
+
+    /// CNPJ do Destinatário formatado.
+    #[serde(rename = "CNPJ do Destinatário", default)]
+    pub destinatario_cnpj: Option<String>,
+
+    /// CPF do Destinatário formatado.
+    #[serde(rename = "CPF do Destinatário", default)]
+    pub destinatario_cpf: Option<String>,
+
+    /// Chave de acesso da NF-e a que o pagamento pertence.
+    #[serde(rename = "Chave do Documento Fiscal", default)]
+    pub nfe: Option<String>,
+
+    /// Indicador se a NF-e correspondente foi cancelada.
+    #[serde(rename = "Cancelado", default)]
+    pub cancelado: Option<String>,
+
+    /// Número sequencial da Nota Fiscal.
+    #[serde(rename = "Nº do Documento Fiscal", default)]
+    pub numero_da_nota: Option<u32>,
+
+    /// Data de emissão da Nota Fiscal.
+    #[serde(
+        rename = "Data de Emissão",
+        serialize_with = "serialize_option_datetime_to_excel",
+        default
+    )]
+    pub data_emissao: Option<NaiveDate>,
+
+    /// Posição do `<detPag>` dentro do grupo `<pag>`, iniciando em 1.
+    #[serde(rename = "Nº do Pagamento", default)]
+    pub n_pag: Option<u32>,
+
+    /// Indicador da forma de pagamento (`indPag`).
+    #[serde(rename = "Indicador da Forma de Pagamento", default)]
+    pub indicador: Option<String>,
+
+    /// Meio de pagamento (`tPag`), acrescido de `xPag` quando informado.
+    #[serde(rename = "Meio de Pagamento", default)]
+    pub meio: Option<String>,
+
+    /// Valor do pagamento (`vPag`).
+    #[serde(rename = "Valor do Pagamento", default)]
+    pub valor: Option<f64>,
+
+    /// Data do pagamento (`dPag`).
+    #[serde(
+        rename = "Data do Pagamento",
+        serialize_with = "serialize_option_datetime_to_excel",
+        default
+    )]
+    pub data_pagamento: Option<NaiveDate>,
+
+    /// CNPJ da instituição de pagamento (`CNPJPag`).
+    #[serde(rename = "CNPJ da Instituição de Pagamento", default)]
+    pub cnpj_instituicao: Option<String>,
+
+    /// Tipo de integração do pagamento (`tpIntegra`).
+    #[serde(rename = "Tipo de Integração do Pagamento", default)]
+    pub tipo_integracao: Option<String>,
+
+    /// CNPJ da credenciadora do cartão (`card/CNPJ`).
+    #[serde(rename = "CNPJ da Credenciadora do Cartão", default)]
+    pub cnpj_credenciadora: Option<String>,
+
+    /// Bandeira da operadora do cartão (`tBand`).
+    #[serde(rename = "Bandeira do Cartão", default)]
+    pub bandeira: Option<String>,
+
+    /// Número de autorização da operação com cartão (`cAut`).
+    #[serde(rename = "Código de Autorização do Cartão", default)]
+    pub autorizacao: Option<String>,
+
+    /// Troco (`vTroco`) informado no grupo `<pag>`; refere-se à NF-e e não a este pagamento.
+    #[serde(rename = "Valor do Troco da NFe", default)]
+    pub troco: Option<f64>,
+}
+
+impl InfoPagamento {
+    /// Determina se o documento é válido (chave presente e sem marcação de cancelamento).
+    pub fn is_valid(&self) -> bool {
+        self.nfe.is_some() && self.cancelado.is_none()
+    }
+}
+
+impl InfoExtension for InfoPagamento {}
+
+impl GetKey for InfoPagamento {
+    fn get_chave(&self) -> Option<String> {
+        self.nfe.clone()
+    }
+}
+
+impl GetID<Option<(String, u32)>> for InfoPagamento {
+    /// id: (chave, nº do pagamento)
+    fn get_id(&self) -> Option<(String, u32)> {
+        if let (Some(nfe), Some(n_pag)) = (&self.nfe, self.n_pag) {
+            Some((nfe.clone(), n_pag))
+        } else {
+            None
+        }
+    }
+}
 
 /// Informações de Pagamento da Nota Fiscal Eletrônica (`<pag>`).
 ///
@@ -44,6 +234,44 @@ impl Pagamento {
     pub fn valor_total_pago(&self) -> f64 {
         self.det_pag.iter().filter_map(|det| det.valor()).sum()
     }
+
+    /// Lista os meios de pagamento distintos empregados, na ordem em que aparecem no XML.
+    pub fn formas_de_pagamento(&self) -> Vec<String> {
+        let mut formas: Vec<String> = Vec::new();
+        for forma in self.det_pag.iter().filter_map(|det| det.meio_pagamento()) {
+            if !formas.contains(&forma) {
+                formas.push(forma);
+            }
+        }
+        formas
+    }
+
+    /// Gera uma linha [`InfoPagamento`] por `<detPag>`, sem os dados de cabeçalho da NF-e.
+    pub fn get_info_pagamentos(&self) -> Vec<InfoPagamento> {
+        let troco = self.troco();
+        self.det_pag
+            .iter()
+            .zip(1..)
+            .map(|(det, n_pag)| {
+                let card = det.card.as_ref();
+                InfoPagamento {
+                    n_pag: Some(n_pag),
+                    indicador: descrever_codigo(&det.ind_pag, &INDICADOR_DE_PAGAMENTO),
+                    meio: det.meio_pagamento(),
+                    valor: det.valor(),
+                    data_pagamento: get_naive_date_from_yyyy_mm_dd(&det.d_pag),
+                    cnpj_instituicao: det.cnpj_pag.as_deref().map(|c| c.trim().to_string()),
+                    tipo_integracao: card.and_then(|c| c.descrever_integracao()),
+                    cnpj_credenciadora: card.and_then(|c| c.get_cnpj()).map(str::to_string),
+                    bandeira: card.and_then(|c| c.get_bandeira()),
+                    autorizacao: card
+                        .and_then(|c| c.c_aut.as_deref().map(|a| a.trim().to_string())),
+                    troco,
+                    ..Default::default()
+                }
+            })
+            .collect()
+    }
 }
 
 /// Detalhes de uma forma de pagamento empregada (`<detPag>`).
@@ -94,6 +322,17 @@ impl DetalhesPagamento {
             .and_then(|v| v.trim().replace(',', ".").parse::<f64>().ok())
     }
 
+    /// Descreve o meio de pagamento (`tPag`), p. ex. `"03 - Cartão de Crédito"`.
+    ///
+    /// Para o código `99` (Outros), acrescenta a descrição livre informada em `xPag`.
+    pub fn meio_pagamento(&self) -> Option<String> {
+        let meio = descrever_codigo(&self.t_pag, &MEIOS_DE_PAGAMENTO)?;
+        match self.x_pag.as_deref().map(str::trim) {
+            Some(x_pag) if !x_pag.is_empty() => Some(format!("{meio}: {x_pag}")),
+            _ => Some(meio),
+        }
+    }
+
     /// Tenta extrair o indicador de pagamento como um valor numérico `u8`.
     pub fn indicador_pagamento(&self) -> Option<u8> {
         self.ind_pag
@@ -141,4 +380,14 @@ impl Card {
             .as_ref()
             .and_then(|v| v.trim().parse::<u8>().ok())
     }
+
+    /// Descreve o tipo de integração (`tpIntegra`), p. ex. `"2 - Pagamento não integrado (POS)"`.
+    pub fn descrever_integracao(&self) -> Option<String> {
+        descrever_codigo(&self.tp_integra, &TIPO_DE_INTEGRACAO)
+    }
+
+    /// Descreve a bandeira do cartão (`tBand`), p. ex. `"01 - Visa"`.
+    pub fn get_bandeira(&self) -> Option<String> {
+        descrever_codigo(&self.t_band, &BANDEIRAS_DE_CARTAO)
+    }
 }
@@ -7,6 +7,7 @@
 //! tais como Carta de Correção Eletrônica (CC-e), Cancelamento ou Manifestação do Destinatário.
 
 use super::assinaturas::Signature;
+use crate::OptExt;
 use serde::{Deserialize, Serialize};
 
 /// Estrutura correspondente ao protocolo de resposta do lote de eventos fiscais (`<retEvento>`).
@@ -118,6 +119,14 @@ impl RetInfEvento {
             .is_some_and(|stat| stat == "135" || stat == "136")
     }
 
+    /// Situação do registro no formato `cStat - xMotivo` (ver [`is_situacao_homologada`]).
+    pub fn get_situacao(&self) -> Option<String> {
+        match (self.c_stat.get_not_empty(), self.x_motivo.get_not_empty()) {
+            (Some(stat), Some(motivo)) => Some(format!("{stat} - {motivo}")),
+            (stat, motivo) => stat.or(motivo),
+        }
+    }
+
     /// Retorna uma referência à chave de acesso do documento afetado (NF-e ou CT-e).
     ///
     /// Retorna `None` se ambas as chaves estiverem ausentes. Esta operação é executada
@@ -127,3 +136,11 @@ impl RetInfEvento {
         self.ch_nfe.as_deref().or(self.ch_cte.as_deref())
     }
 }
+
+/// Verifica se a situação `cStat - xMotivo` (ver [`RetInfEvento::get_situacao`]) é de
+/// evento registrado pela SEFAZ, nos mesmos códigos de [`RetInfEvento::is_success`].
+pub fn is_situacao_homologada(situacao: Option<&str>) -> bool {
+    situacao
+        .and_then(|situacao| situacao.split(" - ").next())
+        .is_some_and(|c_stat| matches!(c_stat.trim(), "135" | "136"))
+}
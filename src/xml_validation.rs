@@ -139,7 +139,10 @@ fn get_cte_expected_tags() -> HashSet<&'static str> {
     expected.extend(serde_introspect::<cte_detalhamento::UnidadeTransp>());
     expected.extend(serde_introspect::<cte_detalhamento::VeicNovos>());
     expected.extend(serde_introspect::<cte_evento::DetEvento>());
+    expected.extend(serde_introspect::<cte_evento::EvCancCeCte>());
     expected.extend(serde_introspect::<cte_evento::EvCancCte>());
+    expected.extend(serde_introspect::<cte_evento::EvCancIeCte>());
+    expected.extend(serde_introspect::<cte_evento::EvCeCte>());
     expected.extend(serde_introspect::<cte_evento::EvCceCte>());
     expected.extend(serde_introspect::<cte_evento::EvCteAutorizadoMdfe>());
     expected.extend(serde_introspect::<cte_evento::EvCteComplementar>());
@@ -149,8 +152,11 @@ fn get_cte_expected_tags() -> HashSet<&'static str> {
     expected.extend(serde_introspect::<cte_evento::EvCteRegPassagemAuto>());
     expected.extend(serde_introspect::<cte_evento::EvCteSubcontratacao>());
     expected.extend(serde_introspect::<cte_evento::EvCteSubstituido>());
+    expected.extend(serde_introspect::<cte_evento::EvIeCte>());
+    expected.extend(serde_introspect::<cte_evento::EvPrestDesacordo>());
     expected.extend(serde_introspect::<cte_evento::Evento>());
     expected.extend(serde_introspect::<cte_evento::InfCorrecao>());
+    expected.extend(serde_introspect::<cte_evento::InfEntrega>());
     expected.extend(serde_introspect::<cte_evento::InfEvento>());
     expected.extend(serde_introspect::<cte_evento::InfPass>());
     expected.extend(serde_introspect::<cte_evento::InfoCteEvento>());